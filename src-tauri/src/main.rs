@@ -8,6 +8,9 @@ use winreg::enums::*;
 #[cfg(target_os = "windows")]
 use winreg::RegKey;
 
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
 use chrono::{Local, Utc};
 use futures_util::{SinkExt, StreamExt};
 use quick_xml::de::from_str;
@@ -16,13 +19,14 @@ use quick_xml::se::to_string;
 use quick_xml::{Reader, Writer};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
 use std::time::UNIX_EPOCH;
 use tauri::path::BaseDirectory;
 use tauri::State;
@@ -30,11 +34,9 @@ use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_fs::FsExt;
 use tauri::{LogicalSize, PhysicalPosition};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use unrar;
 use url::Url;
 use uuid::Uuid;
 use zip::ZipArchive;
-use base64::{engine::general_purpose, Engine as _};
 
 // --- STRUCTS ---
 
@@ -128,6 +130,8 @@ struct LocalModInfo {
     file_id: Option<String>,
     version: Option<String>,
     install_source: Option<String>,
+    // Game build the mod was installed under
+    game_build: Option<String>,
 }
 
 #[derive(Serialize, Clone)]
@@ -136,6 +140,18 @@ struct ModRenderData {
     enabled: bool,
     priority: u32,
     local_info: Option<LocalModInfo>,
+    // How the build the mod was installed under compares to the current one
+    build_status: Option<ModBuildStatus>,
+}
+
+// Build IDs change on every patch, so a mismatch only says the game changed since the
+// install, not that the mod is broken
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ModBuildStatus {
+    DifferentBuild,
+    // Installed before builds were recorded
+    Unrecorded,
 }
 
 #[derive(Serialize, Clone)]
@@ -150,6 +166,35 @@ struct GamePaths {
     game_root_path: String,
     settings_root_path: String,
     version_type: String,
+    game_build: Option<String>,
+}
+
+// A game copy found by one of the detection backends
+#[derive(Clone, Debug, PartialEq)]
+struct DetectedInstall {
+    path: PathBuf,
+    version_type: String,
+    // Which backend found it: steam, gog, gamepass, heroic, lutris or bottles
+    source: String,
+    // What that launcher calls the game (Heroic app name, Lutris slug)
+    launcher_id: Option<String>,
+}
+
+impl DetectedInstall {
+    fn new(path: PathBuf, version_type: &str, source: &str) -> Self {
+        DetectedInstall {
+            path,
+            version_type: version_type.to_string(),
+            source: source.to_string(),
+            launcher_id: None,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", test))]
+    fn with_launcher_id(mut self, launcher_id: &str) -> Self {
+        self.launcher_id = Some(launcher_id.to_string()).filter(|id| !id.is_empty());
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -161,12 +206,45 @@ struct ProfileModEntry {
     //Track which specific folders from the zip were installed
     #[serde(default)]
     installed_options: Option<Vec<String>>,
+    // Priority and enabled flag of each installed folder
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    folders: Vec<ProfileFolderState>,
+    // Game build the mod was installed under; written back to mod_info.json on apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    game_build: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 struct ModProfileData {
     name: String,
     mods: Vec<ProfileModEntry>,
+    // Optional tie to one entry of the installation registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    installation_id: Option<String>,
+    #[serde(default)]
+    disable_all_mods: bool,
+    // Load order for folders no entry has claimed yet (collections before their
+    // archives are installed); resolved on the next apply
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pending_load_order: Vec<ProfileFolderState>,
+    // Extra Binaries/SETTINGS files (graphics, keybindings, ...) the profile carries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    settings_files: Vec<ProfileSettingsFile>,
+    // Layered profiles: `mods` adds to or replaces the parent's entries, `removed` drops
+    // parent entries by filename and `folder_overrides` re-prioritizes inherited folders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    folder_overrides: Vec<ProfileFolderState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct ProfileSettingsFile {
+    name: String,
+    // None until the file has existed in the game folder
+    content: Option<String>,
 }
 
 #[derive(Serialize, Clone)]
@@ -177,12 +255,68 @@ struct ProfileSwitchProgress {
     file_progress: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 struct GlobalAppConfig {
     custom_download_path: Option<String>,
     custom_library_path: Option<String>,
     #[serde(default)] // Default to false/null if missing in old configs
     legacy_migration_done: bool,
+    #[serde(default)]
+    game_installations: Vec<GameInstallation>,
+    #[serde(default)]
+    active_installation: Option<String>,
+    // Last profile applied to the game folder
+    #[serde(default)]
+    active_profile: Option<String>,
+    // Live changes the active profile failed to pick up
+    #[serde(default)]
+    active_profile_dirty: bool,
+    // Entries of the active profile its last apply could not deploy; snapshots keep them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    undeployed_entries: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct GameInstallation {
+    id: String,
+    path: String,
+    version_type: String,
+    // Detection backend (steam, gog, heroic, ...) or "manual"
+    source: String,
+    // Last build seen for this installation
+    #[serde(default)]
+    game_build: Option<String>,
+    // Heroic app name or Lutris slug found by detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    launcher_id: Option<String>,
+    // Saved launch strategy; None means the default for this kind of install
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    launch: Option<LaunchStrategy>,
+}
+
+#[derive(Clone)]
+struct GameContext {
+    installation: GameInstallation,
+    game_path: PathBuf,
+    game_build: Option<String>,
+    // When game_build was last detected
+    build_checked_at: std::time::Instant,
+}
+
+// The game can update while the app is open; its build is looked up again this often
+const GAME_BUILD_RECHECK_SECS: u64 = 60;
+
+#[derive(Default)]
+struct GameContextState {
+    context: Mutex<Option<GameContext>>,
+}
+
+#[derive(Serialize)]
+struct GameInstallationInfo {
+    #[serde(flatten)]
+    installation: GameInstallation,
+    active: bool,
+    available: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -208,7 +342,7 @@ const CLEAN_MXML_TEMPLATE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
 static DIR_LOCK: Mutex<()> = Mutex::new(());
 
 struct StartupState {
-    pending_nxm: Mutex<Option<String>>,
+    nxm_queue: Mutex<NxmQueue>,
 }
 
 // --- HELPER FUNCTIONS ---
@@ -240,6 +374,104 @@ fn smart_deploy_file(source: &Path, dest: &Path) -> Result<(), String> {
     Ok(())
 }
 
+// Serializes GCMODSETTINGS data back into the exact formatting the game writes
+fn serialize_settings_xml(root: &SettingsData) -> Result<String, String> {
+    let unformatted_xml =
+        to_string(root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
+    let mut reader = Reader::from_str(&unformatted_xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => writer
+                .write_event(event)
+                .map_err(|e| format!("XML formatting error: {:?}", e))?,
+            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+        }
+    }
+    let buf = writer.into_inner();
+    let xml_body = String::from_utf8(buf)
+        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+
+    Ok(
+        format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
+            .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
+            .replace(
+                " name=\"Dependencies\" value=\"\"",
+                " name=\"Dependencies\"",
+            )
+            .replace("\"/>", "\" />"),
+    )
+}
+
+// The properties Singularity sets itself; everything else in an entry is the game's
+const MANAGED_MOD_PROPERTIES: [&str; 3] = ["Name", "ModPriority", "Enabled"];
+
+// Author, ID, EnabledVR, Dependencies, ... of one GCMODSETTINGS entry
+fn mod_entry_metadata(entry: &ModEntry) -> BTreeMap<String, String> {
+    entry
+        .properties
+        .iter()
+        .filter(|p| !MANAGED_MOD_PROPERTIES.contains(&p.name.as_str()))
+        .map(|p| (p.name.clone(), p.value.clone().unwrap_or_default()))
+        .collect()
+}
+
+// Same layout the frontend uses in addNewModToXml. `metadata` holds the entry's
+// remaining properties as the game last wrote them; missing ones get the defaults.
+fn build_mod_entry(
+    priority: usize,
+    folder_name: &str,
+    enabled: bool,
+    metadata: &BTreeMap<String, String>,
+) -> ModEntry {
+    let enabled_str = if enabled { "true" } else { "false" };
+    let layout = [
+        ("Name", folder_name.to_uppercase()),
+        ("Author", String::new()),
+        ("ID", "0".to_string()),
+        ("AuthorID", "0".to_string()),
+        ("LastUpdated", "0".to_string()),
+        ("ModPriority", priority.to_string()),
+        ("Enabled", enabled_str.to_string()),
+        ("EnabledVR", enabled_str.to_string()),
+        ("Dependencies", String::new()),
+    ];
+
+    let mut properties: Vec<ModProperty> = layout
+        .iter()
+        .map(|(name, default)| {
+            let value = match metadata.get(*name) {
+                Some(v) if !MANAGED_MOD_PROPERTIES.contains(name) => v.clone(),
+                _ => default.clone(),
+            };
+            ModProperty {
+                name: name.to_string(),
+                value: Some(value),
+            }
+        })
+        .collect();
+    // Properties from newer game versions are kept as they were
+    properties.extend(
+        metadata
+            .iter()
+            .filter(|(name, _)| !layout.iter().any(|(known, _)| known == name))
+            .filter(|(name, _)| !MANAGED_MOD_PROPERTIES.contains(&name.as_str()))
+            .map(|(name, value)| ModProperty {
+                name: name.clone(),
+                value: Some(value.clone()),
+            }),
+    );
+
+    ModEntry {
+        entry_name: "Data".to_string(),
+        entry_value: "GcModSettingsInfo".to_string(),
+        index: priority.to_string(),
+        properties,
+    }
+}
+
 fn deploy_structure_recursive(source: &Path, dest: &Path) -> Result<(), String> {
     if !dest.exists() {
         fs::create_dir_all(dest).map_err(|e| e.to_string())?;
@@ -317,6 +549,8 @@ fn rotate_logs(app: &AppHandle) {
 }
 
 fn log_internal(app: &AppHandle, level: &str, message: &str) {
+    let message = redact_log_secrets(message);
+    let message = message.as_str();
     if let Ok(log_path) = get_log_file_path(app) {
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
         let log_entry = format!("[{}] [{}] {}\n", timestamp, level, message);
@@ -501,6 +735,102 @@ fn get_config_file_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data.join("config.json"))
 }
 
+// Held for every read and write of config.json; command threads, the game monitor and
+// background profile syncs all touch it
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_config() -> MutexGuard<'static, ()> {
+    CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn load_app_config(app: &AppHandle) -> GlobalAppConfig {
+    let _guard = lock_config();
+    read_app_config(app)
+}
+
+// Caller holds CONFIG_LOCK
+fn read_app_config(app: &AppHandle) -> GlobalAppConfig {
+    let Ok(config_path) = get_config_file_path(app) else {
+        return GlobalAppConfig::default();
+    };
+    if !config_path.exists() {
+        return GlobalAppConfig::default();
+    }
+    let parse = || -> Result<GlobalAppConfig, String> {
+        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    };
+    match parse() {
+        Ok(config) => config,
+        Err(_) => {
+            // Another process (an editor, a sync tool) may still be writing it; look once more
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            match parse() {
+                Ok(config) => config,
+                Err(e) => {
+                    set_aside_unreadable_config(app, &config_path, &e);
+                    GlobalAppConfig::default()
+                }
+            }
+        }
+    }
+}
+
+// Moves a config.json that no longer parses out of the way, so the next save
+// starts fresh without destroying the user's settings
+fn set_aside_unreadable_config(app: &AppHandle, config_path: &Path, error: &str) {
+    let backup_path = config_path.with_file_name(format!(
+        "config.json.bad-{}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    match fs::rename(config_path, &backup_path) {
+        Ok(()) => log_internal(
+            app,
+            "ERROR",
+            &format!(
+                "config.json could not be read ({}). It was moved to {:?} and default settings are used.",
+                error, backup_path
+            ),
+        ),
+        Err(rename_err) => log_internal(
+            app,
+            "ERROR",
+            &format!(
+                "config.json could not be read ({}) or moved aside ({}).",
+                error, rename_err
+            ),
+        ),
+    }
+}
+
+// Loads, changes and saves the config as one step, so concurrent updates don't undo
+// each other. `change` must not load or save the config itself.
+fn update_app_config<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut GlobalAppConfig) -> T,
+) -> Result<T, String> {
+    let _guard = lock_config();
+    let mut config = read_app_config(app);
+    let before = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    let result = change(&mut config);
+    if serde_json::to_string(&config).map_err(|e| e.to_string())? != before {
+        write_config_file(&get_config_file_path(app)?, &config)?;
+    }
+    Ok(result)
+}
+
+// Written to a temporary file first and renamed over config.json, so a reader never
+// sees a half-written file. Caller holds CONFIG_LOCK.
+fn write_config_file(config_path: &Path, config: &GlobalAppConfig) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let temp_path = config_path.with_file_name(format!("config.json.tmp-{}", Uuid::new_v4()));
+    fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+    fs::rename(&temp_path, config_path).map_err(|e| {
+        fs::remove_file(&temp_path).ok();
+        e.to_string()
+    })
+}
+
 fn get_downloads_dir(app: &AppHandle) -> Result<PathBuf, String> {
     // 1. Check if user has a custom path in config.json
     if let Ok(config_path) = get_config_file_path(app) {
@@ -548,50 +878,12 @@ fn get_state_file_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join("window-state.json"))
 }
 
-fn find_game_path() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        return find_steam_path()
-            .or_else(find_gog_path)
-            .or_else(find_gamepass_path);
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        // 1. Get the User's Home Directory
-        let home = std::env::var("HOME").ok()?;
-        let home_path = PathBuf::from(home);
-
-        // 2. Common Steam Library Locations on Linux
-        let possible_paths = vec![
-            home_path.join(".steam/steam/steamapps/common/No Man's Sky"),
-            home_path.join(".local/share/Steam/steamapps/common/No Man's Sky"),
-            // Flatpak Steam path
-            home_path
-                .join(".var/app/com.valvesoftware.Steam/data/Steam/steamapps/common/No Man's Sky"),
-        ];
-
-        for path in possible_paths {
-            // Check for the Binaries folder to verify it's a real install
-            if path.join("Binaries").exists() {
-                return Some(path);
-            }
-        }
-
-        return None;
-    }
-
-    // Fallback for MacOS or other OS
-    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
-    return None;
-}
+const GOG_GAME_IDS: [&str; 2] = ["1446213994", "1446223351"];
 
 #[cfg(target_os = "windows")]
 fn find_gog_path() -> Option<PathBuf> {
     let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-    let known_ids = ["1446213994", "1446223351"];
-
-    for id in known_ids {
+    for id in GOG_GAME_IDS {
         let key_path = format!(r"SOFTWARE\WOW6432Node\GOG.com\Games\{}", id);
 
         if let Ok(gog_key) = hklm.open_subkey(&key_path) {
@@ -606,46 +898,6 @@ fn find_gog_path() -> Option<PathBuf> {
     None
 }
 
-#[cfg(target_os = "windows")]
-fn find_steam_path() -> Option<PathBuf> {
-    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-    if let Ok(steam_key) = hklm.open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam") {
-        if let Ok(steam_path_str) = steam_key.get_value::<String, _>("InstallPath") {
-            let steam_path = PathBuf::from(steam_path_str);
-            let mut library_folders = vec![steam_path.clone()];
-
-            let vdf_path = steam_path.join("steamapps").join("libraryfolders.vdf");
-            if let Ok(content) = fs::read_to_string(&vdf_path) {
-                for line in content.lines() {
-                    if let Some(path_str) = line.split('"').nth(3) {
-                        let p = PathBuf::from(path_str.replace("\\\\", "\\"));
-                        if p.exists() {
-                            library_folders.push(p);
-                        }
-                    }
-                }
-            }
-
-            for folder in library_folders {
-                let manifest_path = folder.join("steamapps").join("appmanifest_275850.acf");
-                if let Ok(content) = fs::read_to_string(manifest_path) {
-                    if let Some(dir_str) = content
-                        .lines()
-                        .find(|l| l.contains("\"installdir\""))
-                        .and_then(|l| l.split('"').nth(3))
-                    {
-                        let game_path = folder.join("steamapps").join("common").join(dir_str);
-                        if game_path.is_dir() {
-                            return Some(game_path);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    None
-}
-
 #[cfg(target_os = "windows")]
 fn find_gamepass_path() -> Option<PathBuf> {
     let default_path = PathBuf::from("C:\\XboxGames\\No Man's Sky\\Content");
@@ -679,501 +931,1515 @@ fn find_gamepass_path() -> Option<PathBuf> {
     None
 }
 
-fn extract_archive<F>(
-    archive_path: &Path,
-    destination: &Path, // <--- CHANGED: Exact path where files go
-    on_progress: F,
-) -> Result<(), String>
-// <--- CHANGED: Returns (), path is already known
-where
-    F: Fn(u64),
-{
-    if !destination.exists() {
-        fs::create_dir_all(destination).map_err(|e| format!("Could not create dest dir: {}", e))?;
-    }
+// --- STEAM LIBRARY DETECTION ---
+const NMS_STEAM_APP_ID: &str = "275850";
 
-    let abs_archive_path = archive_path
-        .canonicalize()
-        .map_err(|e| format!("Invalid archive path '{}': {}", archive_path.display(), e))?;
+// Minimal KeyValues (VDF/ACF) tree. Keys keep their file order and are matched case-insensitively.
+#[derive(Debug, Clone, PartialEq)]
+enum VdfValue {
+    Str(String),
+    Map(Vec<(String, VdfValue)>),
+}
 
-    let extension = archive_path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+impl VdfValue {
+    fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::Str(_) => None,
+        }
+    }
 
-    match extension.as_str() {
-        "zip" => {
-            let file = fs::File::open(&abs_archive_path).map_err(|e| e.to_string())?;
-            let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::Str(s) => Some(s),
+            VdfValue::Map(_) => None,
+        }
+    }
 
-            let total_files = archive.len();
-            for i in 0..total_files {
-                let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+    fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Map(entries) => entries,
+            VdfValue::Str(_) => &[],
+        }
+    }
+}
 
-                let outpath = match file.enclosed_name() {
-                    Some(path) => destination.join(path), // Use destination directly
-                    None => continue,
-                };
+#[derive(Debug, PartialEq)]
+enum VdfToken {
+    Text(String),
+    Open,
+    Close,
+}
 
-                if file.name().ends_with('/') {
-                    fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-                } else {
-                    if let Some(p) = outpath.parent() {
-                        if !p.exists() {
-                            fs::create_dir_all(&p).map_err(|e| e.to_string())?;
-                        }
+fn tokenize_vdf(content: &str) -> Result<Vec<VdfToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(VdfToken::Open),
+            '}' => tokens.push(VdfToken::Close),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(other) => text.push(other),
+                            None => return Err("Unterminated escape in VDF string".to_string()),
+                        },
+                        Some(other) => text.push(other),
+                        None => return Err("Unterminated VDF string".to_string()),
                     }
-                    let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
-                    io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
                 }
-
-                let pct = ((i as u64 + 1) * 100) / total_files as u64;
-                on_progress(pct);
+                tokens.push(VdfToken::Text(text));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            // Platform conditionals like [$WIN32] are not needed for Steam's own files
+            '[' => {
+                for next in chars.by_ref() {
+                    if next == ']' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut text = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '"' | '{' | '}') {
+                        break;
+                    }
+                    text.push(next);
+                    chars.next();
+                }
+                tokens.push(VdfToken::Text(text));
             }
         }
-        "rar" => {
-            let _guard = DIR_LOCK.lock().map_err(|e| e.to_string())?;
-            let original_dir = env::current_dir().map_err(|e| e.to_string())?;
+    }
+    Ok(tokens)
+}
 
-            // Change to destination
-            env::set_current_dir(destination).map_err(|e| e.to_string())?;
+fn parse_vdf(content: &str) -> Result<VdfValue, String> {
+    fn parse_entries(
+        tokens: &mut std::iter::Peekable<std::vec::IntoIter<VdfToken>>,
+        nested: bool,
+    ) -> Result<Vec<(String, VdfValue)>, String> {
+        let mut entries = Vec::new();
+        loop {
+            let key = match tokens.next() {
+                Some(VdfToken::Text(key)) => key,
+                Some(VdfToken::Close) if nested => return Ok(entries),
+                None if !nested => return Ok(entries),
+                None => return Err("Unexpected end of VDF data".to_string()),
+                Some(token) => return Err(format!("Unexpected VDF token: {:?}", token)),
+            };
+            let value = match tokens.next() {
+                Some(VdfToken::Text(value)) => VdfValue::Str(value),
+                Some(VdfToken::Open) => VdfValue::Map(parse_entries(tokens, true)?),
+                _ => return Err(format!("Missing value for VDF key '{}'", key)),
+            };
+            entries.push((key, value));
+        }
+    }
 
-            let extract_result = (|| -> Result<(), String> {
-                let mut archive = unrar::Archive::new(&abs_archive_path)
-                    .open_for_processing()
-                    .map_err(|e| format!("{:?}", e))?;
+    let mut tokens = tokenize_vdf(content)?.into_iter().peekable();
+    Ok(VdfValue::Map(parse_entries(&mut tokens, false)?))
+}
 
-                while let Ok(Some(header)) = archive.read_header() {
-                    archive = header.extract().map_err(|e| format!("{:?}", e))?;
-                    on_progress(0);
-                }
-                Ok(())
-            })();
+fn read_vdf_file(path: &Path) -> Option<VdfValue> {
+    let content = fs::read_to_string(path).ok()?;
+    parse_vdf(&content).ok()
+}
 
-            let _ = env::set_current_dir(&original_dir);
-            extract_result?;
-            on_progress(100);
-        }
-        "7z" => {
-            on_progress(50);
-            sevenz_rust::decompress_file(&abs_archive_path, destination)
-                .map_err(|e| e.to_string())?;
-            on_progress(100);
+// The Steam root is always a library; libraryfolders.vdf lists the others.
+// Older files map "1" -> path, newer ones "1" -> { "path" ... }.
+fn steam_library_folders(steam_root: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![steam_root.to_path_buf()];
+
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    if let Some(vdf) = read_vdf_file(&vdf_path) {
+        let libraries = vdf
+            .get("libraryfolders")
+            .or_else(|| vdf.get("LibraryFolders"));
+        for (key, value) in libraries.map(|l| l.entries()).unwrap_or_default() {
+            if !key.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let path = match value {
+                VdfValue::Str(path) => Some(path.as_str()),
+                VdfValue::Map(_) => value.get("path").and_then(|p| p.as_str()),
+            };
+            if let Some(path) = path {
+                let folder = PathBuf::from(path);
+                if folder.is_dir() && !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
         }
-        _ => return Err(format!("Unsupported file type: .{}", extension)),
     }
+    folders
+}
 
-    Ok(())
+fn find_app_in_steam_libraries(libraries: &[PathBuf], app_id: &str) -> Option<PathBuf> {
+    for folder in libraries {
+        let manifest_path = folder
+            .join("steamapps")
+            .join(format!("appmanifest_{}.acf", app_id));
+        let Some(manifest) = read_vdf_file(&manifest_path) else {
+            continue;
+        };
+        let install_dir = manifest
+            .get("AppState")
+            .and_then(|state| state.get("installdir"))
+            .and_then(|dir| dir.as_str());
+        if let Some(install_dir) = install_dir {
+            let game_path = folder.join("steamapps").join("common").join(install_dir);
+            if game_path.join("Binaries").is_dir() {
+                return Some(game_path);
+            }
+        }
+    }
+    None
 }
 
-// --- TAURI COMMANDS ---
+// Inside our own Flatpak the XDG variables point at the sandbox, not at the host's dirs
+#[cfg(target_os = "linux")]
+fn host_xdg_dir(var: &str, home: &Path, default: &str) -> PathBuf {
+    match std::env::var(var) {
+        Ok(dir) if std::env::var("FLATPAK_ID").is_err() && !dir.is_empty() => PathBuf::from(dir),
+        _ => home.join(default),
+    }
+}
 
-#[derive(Serialize, Deserialize)]
-struct HttpResponse {
-    status: u16,
-    status_text: String,
-    body: String,
-    headers: HashMap<String, String>,
+// Native, Flatpak and Snap Steam all keep their own root. Symlinked roots
+// (~/.steam/steam -> ~/.local/share/Steam) are de-duplicated.
+#[cfg(target_os = "linux")]
+fn linux_steam_roots() -> Vec<PathBuf> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Vec::new();
+    };
+    let home_path = PathBuf::from(home);
+    let data_home = host_xdg_dir("XDG_DATA_HOME", &home_path, ".local/share");
+
+    let candidates = vec![
+        data_home.join("Steam"),
+        home_path.join(".local/share/Steam"),
+        home_path.join(".steam/steam"),
+        home_path.join(".steam/root"),
+        // Flatpak Steam
+        home_path.join(".var/app/com.valvesoftware.Steam/data/Steam"),
+        home_path.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+        // Snap Steam
+        home_path.join("snap/steam/common/.local/share/Steam"),
+        home_path.join("snap/steam/common/.steam/steam"),
+    ];
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for candidate in candidates {
+        if !candidate.join("steamapps").is_dir() {
+            continue;
+        }
+        let resolved = candidate.canonicalize().unwrap_or(candidate);
+        if !roots.contains(&resolved) {
+            roots.push(resolved);
+        }
+    }
+    roots
 }
 
-#[tauri::command]
-async fn http_request(
-    url: String,
-    method: Option<String>,
-    headers: Option<HashMap<String, String>>,
-) -> Result<HttpResponse, String> {
-    let client = reqwest::Client::new();
-    let method = method.unwrap_or_else(|| "GET".to_string());
+fn find_steam_path() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let steam_roots: Vec<PathBuf> = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam")
+        .and_then(|key| key.get_value::<String, _>("InstallPath"))
+        .map(|path| vec![PathBuf::from(path)])
+        .unwrap_or_default();
 
-    let mut request = match method.to_uppercase().as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        "HEAD" => client.head(&url),
-        _ => return Err(format!("Unsupported HTTP method: {}", method)),
-    };
+    #[cfg(target_os = "linux")]
+    let steam_roots = linux_steam_roots();
 
-    // Add headers if provided
-    if let Some(headers_map) = headers {
-        for (key, value) in headers_map {
-            request = request.header(&key, &value);
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let steam_roots: Vec<PathBuf> = Vec::new();
+
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for root in &steam_roots {
+        for folder in steam_library_folders(root) {
+            if !libraries.contains(&folder) {
+                libraries.push(folder);
+            }
         }
     }
+    find_app_in_steam_libraries(&libraries, NMS_STEAM_APP_ID)
+}
 
-    let response = request.send().await.map_err(|e| {
-        format!("HTTP request failed: {}", e)
-    })?;
+// --- LINUX LAUNCHER DETECTION ---
+// Non-Steam copies on Linux run under Wine through a launcher. Each backend reads the
+// launcher's own records and only reports folders that contain Binaries/.
+
+// Game root for an exe at <root>/Binaries/NMS.exe
+#[cfg(any(target_os = "linux", test))]
+fn game_root_from_exe(exe: &Path) -> Option<PathBuf> {
+    let binaries = exe.parent()?;
+    if !binaries
+        .file_name()?
+        .to_string_lossy()
+        .eq_ignore_ascii_case("Binaries")
+    {
+        return None;
+    }
+    binaries.parent().map(Path::to_path_buf)
+}
 
-    let status = response.status().as_u16();
-    let status_text = response.status().canonical_reason().unwrap_or("").to_string();
+#[cfg(any(target_os = "linux", test))]
+fn is_nms_title(title: &str) -> bool {
+    let normalized: String = title
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    normalized.contains("nomanssky")
+}
 
-    // Extract response headers
-    let mut response_headers = HashMap::new();
-    for (name, value) in response.headers() {
-        if let Ok(value_str) = value.to_str() {
-            response_headers.insert(name.to_string().to_lowercase(), value_str.to_string());
+// Heroic's gog_store/installed.json: { "installed": [ { "appName", "install_path", ... } ] }
+// Returns (install path, app name) pairs.
+#[cfg(any(target_os = "linux", test))]
+fn parse_heroic_gog_installed(content: &str) -> Vec<(PathBuf, String)> {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    json.get("installed")
+        .and_then(|i| i.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|game| {
+            let app_name = game.get("appName").and_then(|a| a.as_str()).unwrap_or("");
+            let install_path = game
+                .get("install_path")
+                .and_then(|p| p.as_str())
+                .unwrap_or("");
+            GOG_GAME_IDS.contains(&app_name) || is_nms_title(install_path)
+        })
+        .filter_map(|game| {
+            let path = game.get("install_path").and_then(|p| p.as_str())?;
+            let app_name = game.get("appName").and_then(|a| a.as_str()).unwrap_or("");
+            Some((PathBuf::from(path), app_name.to_string()))
+        })
+        .collect()
+}
+
+// Legendary's installed.json (Epic via Heroic): { "<AppName>": { "title", "install_path", ... } }
+// Returns (install path, app name) pairs.
+#[cfg(any(target_os = "linux", test))]
+fn parse_legendary_installed(content: &str) -> Vec<(PathBuf, String)> {
+    let Ok(Value::Object(games)) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    games
+        .iter()
+        .filter(|(_, game)| {
+            game.get("title")
+                .and_then(|t| t.as_str())
+                .is_some_and(is_nms_title)
+        })
+        .filter_map(|(app_name, game)| {
+            let path = game.get("install_path").and_then(|p| p.as_str())?;
+            Some((PathBuf::from(path), app_name.clone()))
+        })
+        .collect()
+}
+
+// Lutris names game configs <slug>-<timestamp>.yml
+#[cfg(any(target_os = "linux", test))]
+fn lutris_slug_from_config_name(file_name: &str) -> &str {
+    let stem = Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name);
+    match stem.rsplit_once('-') {
+        Some((slug, stamp)) if !slug.is_empty() && stamp.chars().all(|c| c.is_ascii_digit()) => {
+            slug
         }
+        _ => stem,
     }
+}
 
-    // Check if this is an image request by content-type or URL
-    let content_type = response_headers.get("content-type").unwrap_or(&String::new()).to_lowercase();
-    let is_image = content_type.starts_with("image/") ||
-                   url.contains(".jpg") || url.contains(".jpeg") ||
-                   url.contains(".png") || url.contains(".gif") ||
-                   url.contains(".webp");
+// Lutris game YAML: game.exe points at Binaries/NMS.exe, game.working_dir is a fallback
+#[cfg(any(target_os = "linux", test))]
+fn parse_lutris_game_yaml(content: &str) -> Option<PathBuf> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(content).ok()?;
+    let game = yaml.get("game")?;
 
-    let body = if is_image {
-        // For images, get bytes and encode as base64
-        let bytes = response.bytes().await.map_err(|e| {
-            format!("Failed to read response bytes: {}", e)
-        })?;
-        general_purpose::STANDARD.encode(bytes)
-    } else {
-        // For text content, get as string
-        response.text().await.map_err(|e| {
-            format!("Failed to read response body: {}", e)
-        })?
-    };
+    if let Some(exe) = game.get("exe").and_then(|e| e.as_str()) {
+        let exe_path = PathBuf::from(exe);
+        if let Some(root) = game_root_from_exe(&exe_path) {
+            return Some(root);
+        }
+        // Relative exe paths are resolved against the Wine prefix
+        if exe_path.is_relative() {
+            if let Some(prefix) = game.get("prefix").and_then(|p| p.as_str()) {
+                return game_root_from_exe(&Path::new(prefix).join(exe_path));
+            }
+        }
+    }
+    game.get("working_dir")
+        .and_then(|w| w.as_str())
+        .map(PathBuf::from)
+        .and_then(|dir| {
+            if dir
+                .file_name()?
+                .to_string_lossy()
+                .eq_ignore_ascii_case("Binaries")
+            {
+                dir.parent().map(Path::to_path_buf)
+            } else {
+                Some(dir)
+            }
+        })
+}
 
-    Ok(HttpResponse {
-        status,
-        status_text,
-        body,
-        headers: response_headers,
-    })
+// Bottles bottle.yml: External_Programs: { <uuid>: { name, path } }
+#[cfg(any(target_os = "linux", test))]
+fn parse_bottle_yaml(content: &str) -> Vec<PathBuf> {
+    let Ok(yaml) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return Vec::new();
+    };
+    let Some(programs) = yaml.get("External_Programs").and_then(|p| p.as_mapping()) else {
+        return Vec::new();
+    };
+    programs
+        .values()
+        .filter_map(|program| program.get("path").and_then(|p| p.as_str()))
+        .filter_map(|exe| game_root_from_exe(Path::new(exe)))
+        .filter(|root| is_nms_title(&root.to_string_lossy()))
+        .collect()
 }
 
-#[tauri::command]
-fn get_all_mods_for_render(app: AppHandle) -> Result<Vec<ModRenderData>, String> {
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let mods_path = game_path.join("GAMEDATA").join("MODS");
+// Installs living under a steamapps folder are Steam (Proton) copies and Xbox app
+// folders are Game Pass; everything else (Lutris, Bottles, portable copies) is the
+// DRM-free GOG build.
+fn guess_version_type(path: &Path) -> &'static str {
+    let has_component = |name: &str| {
+        path.components()
+            .any(|c| c.as_os_str().to_string_lossy().eq_ignore_ascii_case(name))
+    };
+    if has_component("steamapps") {
+        "Steam"
+    } else if has_component("XboxGames") || has_component("WindowsApps") {
+        "GamePass"
+    } else {
+        "GOG"
+    }
+}
 
-    let settings_dir = game_path.join("Binaries").join("SETTINGS");
-    let settings_file_path = settings_dir.join("GCMODSETTINGS.MXML");
+#[cfg(target_os = "linux")]
+fn linux_config_dirs(app_dir: &str, flatpak_id: &str, flatpak_kind: &str) -> Vec<PathBuf> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Vec::new();
+    };
+    let home_path = PathBuf::from(home);
+    let base = match flatpak_kind {
+        "data" => host_xdg_dir("XDG_DATA_HOME", &home_path, ".local/share"),
+        _ => host_xdg_dir("XDG_CONFIG_HOME", &home_path, ".config"),
+    };
+    let mut dirs = vec![base.join(app_dir)];
+    let flatpak_dir = home_path
+        .join(".var/app")
+        .join(flatpak_id)
+        .join(flatpak_kind)
+        .join(app_dir);
+    if !dirs.contains(&flatpak_dir) {
+        dirs.push(flatpak_dir);
+    }
+    dirs
+}
 
-    // Debug: List contents of Binaries and SETTINGS
-    //let binaries_dir = game_path.join("Binaries");
-    //log_internal(&app, "DEBUG", &format!("Checking for Binaries dir at: {}", binaries_dir.display()));
-    /*
-    match std::fs::read_dir(&binaries_dir) {
-        Ok(entries) => {
-            let files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().into_owned()).collect();
-            log_internal(&app, "DEBUG", &format!("Binaries dir contents: {:?}", files));
-        },
-        Err(e) => {
-            log_internal(&app, "DEBUG", &format!("Failed to read Binaries dir: {}", e));
+#[cfg(target_os = "linux")]
+fn find_heroic_installs() -> Vec<DetectedInstall> {
+    let mut installs = Vec::new();
+    for heroic_dir in linux_config_dirs("heroic", "com.heroicgameslauncher.hgl", "config") {
+        if let Ok(content) = fs::read_to_string(heroic_dir.join("gog_store/installed.json")) {
+            for (path, app_name) in parse_heroic_gog_installed(&content) {
+                installs
+                    .push(DetectedInstall::new(path, "GOG", "heroic").with_launcher_id(&app_name));
+            }
         }
-    }*/
-    //log_internal(&app, "DEBUG", &format!("Checking for SETTINGS dir at: {}", settings_dir.display()));
-    /*
-    match std::fs::read_dir(&settings_dir) {
-        Ok(entries) => {
-            let files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().into_owned()).collect();
-            log_internal(&app, "DEBUG", &format!("SETTINGS dir contents: {:?}", files));
-        },
-        Err(e) => {
-            log_internal(&app, "DEBUG", &format!("Failed to read SETTINGS dir: {}", e));
+        let legendary = heroic_dir.join("legendaryConfig/legendary/installed.json");
+        if let Ok(content) = fs::read_to_string(legendary) {
+            for (path, app_name) in parse_legendary_installed(&content) {
+                installs
+                    .push(DetectedInstall::new(path, "Epic", "heroic").with_launcher_id(&app_name));
+            }
         }
-    }*/
-
-    if !settings_file_path.exists() {
-        log_internal(&app, "DEBUG", &format!("GCMODSETTINGS.MXML not found at: {}", settings_file_path.display()));
-        return Ok(Vec::new());
     }
+    installs
+}
 
-    // 1. Scan Disk for Real Folders
-    // We create a Set of Uppercase names for easy comparison
-    let mut real_folders_map: HashMap<String, String> = HashMap::new();
-    let mut real_folders_set: std::collections::HashSet<String> = std::collections::HashSet::new();
+#[cfg(target_os = "linux")]
+fn find_lutris_installs() -> Vec<DetectedInstall> {
+    // Lutris moved game configs from ~/.config to ~/.local/share in 0.5.13
+    let mut game_dirs: Vec<PathBuf> = Vec::new();
+    game_dirs.extend(linux_config_dirs("lutris", "net.lutris.Lutris", "config"));
+    game_dirs.extend(linux_config_dirs("lutris", "net.lutris.Lutris", "data"));
+
+    let mut installs = Vec::new();
+    for dir in game_dirs {
+        let Ok(entries) = fs::read_dir(dir.join("games")) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("yml" | "yaml")
+            ) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(root) = parse_lutris_game_yaml(&content) {
+                if is_nms_title(&root.to_string_lossy())
+                    || is_nms_title(&entry.file_name().to_string_lossy())
+                {
+                    let version_type = guess_version_type(&root);
+                    let slug = lutris_slug_from_config_name(&entry.file_name().to_string_lossy())
+                        .to_string();
+                    installs.push(
+                        DetectedInstall::new(root, version_type, "lutris").with_launcher_id(&slug),
+                    );
+                }
+            }
+        }
+    }
+    installs
+}
 
-    if let Ok(entries) = fs::read_dir(&mods_path) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let real_name = entry.file_name().to_string_lossy().into_owned();
-                let upper_name = real_name.to_uppercase();
-                real_folders_map.insert(upper_name.clone(), real_name);
-                real_folders_set.insert(upper_name);
+#[cfg(target_os = "linux")]
+fn find_bottles_installs() -> Vec<DetectedInstall> {
+    let mut installs = Vec::new();
+    for bottles_dir in linux_config_dirs("bottles", "com.usebottles.bottles", "data") {
+        let Ok(entries) = fs::read_dir(bottles_dir.join("bottles")) else {
+            continue;
+        };
+        for bottle in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let mut roots = fs::read_to_string(bottle.join("bottle.yml"))
+                .map(|content| parse_bottle_yaml(&content))
+                .unwrap_or_default();
+            // Games installed with the GOG installer but never added as a program
+            let drive_c = bottle.join("drive_c");
+            roots.push(drive_c.join("GOG Games/No Man's Sky"));
+            roots.push(drive_c.join("Program Files (x86)/GOG Galaxy/Games/No Man's Sky"));
+
+            for root in roots {
+                let version_type = guess_version_type(&root);
+                installs.push(DetectedInstall::new(root, version_type, "bottles"));
             }
         }
     }
+    installs
+}
 
+// Every installation we can find, in priority order (the first one is the default).
+fn detect_game_installs() -> Vec<DetectedInstall> {
+    let mut candidates: Vec<DetectedInstall> = Vec::new();
 
-    // 2. Read and Parse XML with extra debug
-    log_internal(&app, "DEBUG", &format!("Attempting to read GCMODSETTINGS.MXML at: {}", settings_file_path.display()));
-    let xml_content = match fs::read_to_string(&settings_file_path) {
-        Ok(content) => {
-            log_internal(&app, "DEBUG", &format!("Read GCMODSETTINGS.MXML successfully. Content length: {} bytes", content.len()));
-            // Dump the first 2048 bytes (or less) for debug
-            //let dump_len = content.len().min(2048);
-            //let dump = &content[..dump_len];
-            //log_internal(&app, "DEBUG", &format!("GCMODSETTINGS.MXML dump (first {} bytes):\n{}", dump_len, dump));
-            content
-        },
-        Err(e) => {
-            log_internal(&app, "ERROR", &format!("Failed to read GCMODSETTINGS.MXML: {}", e));
-            return Err(format!("Failed to read GCMODSETTINGS.MXML: {}", e));
+    #[cfg(target_os = "windows")]
+    {
+        candidates.extend(find_steam_path().map(|p| DetectedInstall::new(p, "Steam", "steam")));
+        candidates.extend(find_gog_path().map(|p| DetectedInstall::new(p, "GOG", "gog")));
+        candidates
+            .extend(find_gamepass_path().map(|p| DetectedInstall::new(p, "GamePass", "gamepass")));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        candidates.extend(find_steam_path().map(|p| DetectedInstall::new(p, "Steam", "steam")));
+        candidates.extend(find_heroic_installs());
+        candidates.extend(find_lutris_installs());
+        candidates.extend(find_bottles_installs());
+    }
+
+    let mut installs: Vec<DetectedInstall> = Vec::new();
+    for install in candidates {
+        if !install.path.join("Binaries").is_dir() {
+            continue;
         }
-    };
-    log_internal(&app, "DEBUG", "Parsing GCMODSETTINGS.MXML...");
-    let mut root: SettingsData = match from_str(&xml_content) {
-        Ok(parsed) => {
-            log_internal(&app, "DEBUG", "Parsed GCMODSETTINGS.MXML successfully.");
-            parsed
-        },
-        Err(e) => {
-            log_internal(&app, "ERROR", &format!("Failed to parse GCMODSETTINGS.MXML: {}", e));
-            return Err(format!("Failed to parse GCMODSETTINGS.MXML: {}", e));
+        let resolved = install.path.canonicalize().unwrap_or(install.path.clone());
+        if installs
+            .iter()
+            .any(|known| known.path.canonicalize().unwrap_or(known.path.clone()) == resolved)
+        {
+            continue;
         }
-    };
-
-    let mut dirty = false; // Track if we need to save changes
+        installs.push(install);
+    }
+    installs
+}
 
-    // 3. Clean Orphans (Entries in XML but not on Disk)
-    if let Some(prop) = root.properties.iter_mut().find(|p| p.name == "Data") {
-        let original_len = prop.mods.len();
+// --- GAME INSTALLATIONS ---
+// Known installations live in config.json with one marked active. Detection only
+// runs when the registry is empty or when the user asks for a rescan.
 
-        prop.mods.retain(|entry| {
-            let xml_name = entry.properties.iter()
-                .find(|p| p.name == "Name")
-                .and_then(|p| p.value.as_ref())
-                .map(|s| s.to_uppercase())
-                .unwrap_or_default();
+// Stable across rescans, so profiles can reference an installation by ID
+fn installation_id_for_path(path: &Path) -> String {
+    let normalized = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let digest = Sha256::digest(normalized.to_string_lossy().as_bytes());
+    hex::encode(&digest[..6])
+}
 
-            // Keep it ONLY if it exists on disk
-            real_folders_set.contains(&xml_name)
-        });
+fn is_installation_available(installation: &GameInstallation) -> bool {
+    Path::new(&installation.path).join("Binaries").is_dir()
+}
 
-        if prop.mods.len() != original_len {
-            dirty = true;
-            // Re-index priorities to avoid gaps
-            for (i, mod_entry) in prop.mods.iter_mut().enumerate() {
-                mod_entry.index = i.to_string();
-                if let Some(priority_prop) = mod_entry.properties.iter_mut().find(|p| p.name == "ModPriority") {
-                    priority_prop.value = Some(i.to_string());
-                }
+// Adds newly detected copies and drops detected ones that vanished. Entries the
+// user added by hand are never removed here.
+fn merge_detected_installations(config: &mut GlobalAppConfig, detected: &[DetectedInstall]) {
+    for install in detected {
+        let id = installation_id_for_path(&install.path);
+        if let Some(known) = config
+            .game_installations
+            .iter_mut()
+            .find(|known| known.id == id)
+        {
+            // Registries from older versions have no launcher IDs yet
+            if known.launcher_id.is_none() {
+                known.launcher_id = install.launcher_id.clone();
             }
+            continue;
         }
+        config.game_installations.push(GameInstallation {
+            id,
+            path: install.path.to_string_lossy().into_owned(),
+            version_type: install.version_type.clone(),
+            source: install.source.clone(),
+            game_build: None,
+            launcher_id: install.launcher_id.clone(),
+            launch: None,
+        });
     }
 
-    // 4. Save XML if we removed anything
-    if dirty {
-        // Reuse serialization logic (inline to avoid ownership issues)
-        let unformatted_xml = to_string(&root).map_err(|e| e.to_string())?;
-        let mut reader = Reader::from_str(&unformatted_xml);
-        reader.config_mut().trim_text(true);
-        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-        loop {
-            match reader.read_event() {
-                Ok(Event::Eof) => break,
-                Ok(event) => writer.write_event(event).unwrap(),
-                Err(e) => return Err(e.to_string()),
-            }
-        }
-        let buf = writer.into_inner();
-        let xml_body = String::from_utf8(buf).map_err(|e| e.to_string())?;
-        let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
-            .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
-            .replace(" name=\"Dependencies\" value=\"\"", " name=\"Dependencies\"")
-            .replace("\"/>", "\" />");
+    // The active entry stays even while unavailable, so the selection never changes silently
+    let active = config.active_installation.clone();
+    config.game_installations.retain(|known| {
+        known.source == "manual"
+            || is_installation_available(known)
+            || active.as_deref() == Some(known.id.as_str())
+    });
 
-        // We use a simplified write here directly since we have the path
-        let _ = fs::write(&settings_file_path, final_content);
-        log_internal(&app, "INFO", "Cleaned orphaned mods from GCMODSETTINGS.MXML");
+    let active_known = config.active_installation.as_ref().is_some_and(|id| {
+        config
+            .game_installations
+            .iter()
+            .any(|known| &known.id == id)
+    });
+    if !active_known {
+        config.active_installation = config.game_installations.first().map(|i| i.id.clone());
     }
+}
 
-    // 5. Build Render List
-    let mut mods_to_render = Vec::new();
+const KNOWN_VERSION_TYPES: [&str; 4] = ["Steam", "GOG", "GamePass", "Epic"];
 
-    if let Some(prop) = root.properties.iter().find(|p| p.name == "Data") {
-        for mod_entry in &prop.mods {
-            let xml_name_prop = mod_entry
-                .properties
-                .iter()
-                .find(|p| p.name == "Name")
-                .and_then(|p| p.value.as_ref());
+// Lists the required parts of a game folder that are missing
+fn missing_game_folder_parts(path: &Path) -> Vec<&'static str> {
+    ["Binaries", "Binaries/SETTINGS", "GAMEDATA"]
+        .into_iter()
+        .filter(|part| !path.join(part).is_dir())
+        .collect()
+}
 
-            if let Some(xml_name) = xml_name_prop {
-                // Get Real Name from Map
-                let folder_name = real_folders_map
-                    .get(&xml_name.to_uppercase())
-                    .cloned()
-                    .unwrap_or_else(|| xml_name.clone());
-
-                let enabled = mod_entry
-                    .properties
-                    .iter()
-                    .find(|p| p.name == "Enabled")
-                    .and_then(|p| p.value.as_deref())
-                    .unwrap_or("false")
-                    .eq_ignore_ascii_case("true");
+// Manual entries go first, so they win over anything auto-detection finds
+fn add_manual_installation(
+    config: &mut GlobalAppConfig,
+    path: &Path,
+    version_type: &str,
+) -> GameInstallation {
+    let id = installation_id_for_path(path);
+    config.game_installations.retain(|known| known.id != id);
+
+    let installation = GameInstallation {
+        id: id.clone(),
+        path: path.to_string_lossy().into_owned(),
+        version_type: version_type.to_string(),
+        source: "manual".to_string(),
+        game_build: None,
+        launcher_id: None,
+        launch: None,
+    };
+    let first_detected = config
+        .game_installations
+        .iter()
+        .position(|known| known.source != "manual")
+        .unwrap_or(config.game_installations.len());
+    config
+        .game_installations
+        .insert(first_detected, installation.clone());
+    config.active_installation = Some(id);
+    installation
+}
 
-                let priority = mod_entry
-                    .properties
-                    .iter()
-                    .find(|p| p.name == "ModPriority")
-                    .and_then(|p| p.value.as_ref())
-                    .and_then(|v| v.parse::<u32>().ok())
-                    .unwrap_or(0);
+fn rescan_game_installations(app: &AppHandle) -> Result<GlobalAppConfig, String> {
+    let detected = detect_game_installs();
+    let config = update_app_config(app, |config| {
+        merge_detected_installations(config, &detected);
+        config.clone()
+    })?;
+    invalidate_game_context(app);
+    Ok(config)
+}
 
-                let mod_info_path = mods_path.join(&folder_name).join("mod_info.json");
+// The installation every command operates on. An unavailable selection is an error;
+// switching to another copy is left to the user (see set_active_installation).
+fn resolve_active_installation(app: &AppHandle) -> Result<GameInstallation, String> {
+    let mut config = load_app_config(app);
+    if config.game_installations.is_empty() {
+        config = rescan_game_installations(app)?;
+    }
 
-                let local_info = if let Ok(content) = fs::read_to_string(&mod_info_path) {
-                    if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&content) {
-                        Some(LocalModInfo {
-                            folder_name: folder_name.clone(),
-                            mod_id: json_val.get("modId").or(json_val.get("id")).and_then(|v| v.as_str()).map(String::from),
-                            file_id: json_val.get("fileId").and_then(|v| v.as_str()).map(String::from),
-                            version: json_val.get("version").and_then(|v| v.as_str()).map(String::from),
-                            install_source: json_val.get("installSource").and_then(|v| v.as_str()).map(String::from),
-                        })
-                    } else { None }
-                } else { None };
+    let active = config
+        .active_installation
+        .as_ref()
+        .and_then(|id| config.game_installations.iter().find(|i| &i.id == id))
+        .ok_or_else(|| "Could not find game installation path.".to_string())?;
+    if !is_installation_available(active) {
+        let err = format!(
+            "The active game installation is unavailable: {}. Reconnect it or pick another installation.",
+            active.path
+        );
+        log_internal(app, "WARN", &err);
+        return Err(err);
+    }
+    Ok(active.clone())
+}
 
-                mods_to_render.push(ModRenderData {
-                    folder_name: folder_name,
-                    enabled,
-                    priority,
-                    local_info,
-                });
+// The resolved installation, cached in managed state so commands don't re-run detection
+// on every call. Re-resolved when the folder disappears or another copy is selected.
+fn game_context(app: &AppHandle) -> Result<GameContext, String> {
+    let state = app.state::<GameContextState>();
+    let cached = state.context.lock().map_err(|e| e.to_string())?.clone();
+
+    // Every change of the active installation invalidates the cache, so it is still current
+    if let Some(mut ctx) = cached {
+        if ctx.game_path.join("Binaries").is_dir() {
+            if ctx.build_checked_at.elapsed().as_secs() >= GAME_BUILD_RECHECK_SECS {
+                let game_build = detect_game_build(&ctx.installation);
+                if game_build != ctx.game_build {
+                    record_game_build(app, &ctx.installation.id, game_build.as_deref());
+                    ctx.game_build = game_build;
+                }
+                ctx.build_checked_at = std::time::Instant::now();
+                *state.context.lock().map_err(|e| e.to_string())? = Some(ctx.clone());
             }
+            return Ok(ctx);
         }
+        log_internal(
+            app,
+            "WARN",
+            &format!("Cached game path is no longer present: {:?}", ctx.game_path),
+        );
     }
 
-    mods_to_render.sort_by_key(|m| m.priority);
+    // Not holding the lock here: resolving may rescan, which invalidates the cache
+    let installation = resolve_active_installation(app)?;
+    let game_build = detect_game_build(&installation);
+    record_game_build(app, &installation.id, game_build.as_deref());
+    let ctx = GameContext {
+        game_path: PathBuf::from(&installation.path),
+        installation,
+        game_build,
+        build_checked_at: std::time::Instant::now(),
+    };
+    *state.context.lock().map_err(|e| e.to_string())? = Some(ctx.clone());
+    Ok(ctx)
+}
 
-    Ok(mods_to_render)
+// Called whenever the registry or the active selection changes
+fn invalidate_game_context(app: &AppHandle) {
+    if let Ok(mut cached) = app.state::<GameContextState>().context.lock() {
+        *cached = None;
+    }
+}
+
+fn game_paths_for_installation(app: &AppHandle, installation: &GameInstallation) -> GamePaths {
+    // Grant frontend filesystem access to the game directory
+    let path = PathBuf::from(&installation.path);
+    if let Err(e) = app.fs_scope().allow_directory(&path, true) {
+        log_internal(
+            app,
+            "WARN",
+            &format!("Failed to expand fs scope for game path: {}", e),
+        );
+    } else {
+        log_internal(
+            app,
+            "INFO",
+            &format!("Expanded fs scope for game path: {:?}", path),
+        );
+    }
+
+    GamePaths {
+        game_root_path: installation.path.clone(),
+        settings_root_path: installation.path.clone(),
+        version_type: installation.version_type.clone(),
+        game_build: detect_game_build(installation),
+    }
 }
 
 #[tauri::command]
-async fn install_mod_from_archive(
+fn list_game_installations(app: AppHandle) -> Result<Vec<GameInstallationInfo>, String> {
+    let config = rescan_game_installations(&app)?;
+    Ok(config
+        .game_installations
+        .iter()
+        .map(|installation| GameInstallationInfo {
+            active: config.active_installation.as_deref() == Some(installation.id.as_str()),
+            available: is_installation_available(installation),
+            installation: installation.clone(),
+        })
+        .collect())
+}
+
+// Registers a user-chosen game folder, makes it active and returns its paths.
+// `version_type` is guessed from the path when not given.
+#[tauri::command]
+fn set_game_path(
     app: AppHandle,
-    archive_path_str: String,
-    download_id: String,
-) -> Result<InstallationAnalysis, String> {
-    let id_for_progress = download_id.clone();
-    let app_handle_for_extract = app.clone();
+    path: String,
+    version_type: Option<String>,
+) -> Result<GamePaths, String> {
+    let game_path = PathBuf::from(path.trim());
+    if !game_path.is_dir() {
+        return Err(format!("Folder not found: {}", game_path.display()));
+    }
 
-    let progress_callback = move |pct: u64| {
-        let _ = app_handle_for_extract.emit(
-            "install-progress",
-            InstallProgressPayload {
-                id: id_for_progress.clone(),
-                step: format!("Extracting: {}%", pct),
-                progress: Some(pct),
-            },
+    let missing = missing_game_folder_parts(&game_path);
+    if !missing.is_empty() {
+        let err = format!(
+            "'{}' is not a No Man's Sky installation. Missing: {}",
+            game_path.display(),
+            missing.join(", ")
         );
-    };
+        log_internal(&app, "WARN", &err);
+        return Err(err);
+    }
 
-    let emit_progress = |step: &str| {
-        let _ = app.emit(
-            "install-progress",
-            InstallProgressPayload {
-                id: download_id.clone(),
-                step: step.to_string(),
-                progress: None,
-            },
-        );
+    let version_type = match version_type {
+        Some(v) if KNOWN_VERSION_TYPES.contains(&v.as_str()) => v,
+        Some(v) => return Err(format!("Unknown game version type: {}", v)),
+        None => guess_version_type(&game_path).to_string(),
     };
 
-    emit_progress("Initializing...");
+    let installation = update_app_config(&app, |config| {
+        add_manual_installation(config, &game_path, &version_type)
+    })?;
+    invalidate_game_context(&app);
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Game path set manually: {} ({})",
+            installation.path, version_type
+        ),
+    );
+    Ok(game_paths_for_installation(&app, &installation))
+}
 
-    let archive_path = PathBuf::from(&archive_path_str);
-    let downloads_dir = get_downloads_dir(&app)?;
-    let library_dir = get_library_dir(&app)?; // <--- CHANGE: Use Library Dir
+// Removes a registry entry. Detected copies come back on the next rescan.
+#[tauri::command]
+fn remove_game_installation(app: AppHandle, installation_id: String) -> Result<(), String> {
+    update_app_config(&app, |config| {
+        let before = config.game_installations.len();
+        config
+            .game_installations
+            .retain(|i| i.id != installation_id);
+        if config.game_installations.len() == before {
+            return Err(format!("Unknown installation: {}", installation_id));
+        }
+        if config.active_installation.as_deref() == Some(installation_id.as_str()) {
+            config.active_installation = config.game_installations.first().map(|i| i.id.clone());
+        }
+        Ok(())
+    })??;
+    invalidate_game_context(&app);
+    Ok(())
+}
 
-    // 1. Copying Phase
-    emit_progress("Copying to library...");
-    let archive_path_clone = archive_path.clone();
-    let downloads_dir_clone = downloads_dir.clone();
+#[tauri::command]
+fn set_active_installation(app: AppHandle, installation_id: String) -> Result<GamePaths, String> {
+    let installation = update_app_config(&app, |config| {
+        let installation = config
+            .game_installations
+            .iter()
+            .find(|i| i.id == installation_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown installation: {}", installation_id))?;
+        if !is_installation_available(&installation) {
+            return Err(format!(
+                "The installation at '{}' is not available.",
+                installation.path
+            ));
+        }
+        config.active_installation = Some(installation.id.clone());
+        Ok(installation)
+    })??;
+    invalidate_game_context(&app);
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Active installation set to {} ({})",
+            installation.path, installation.version_type
+        ),
+    );
+    Ok(game_paths_for_installation(&app, &installation))
+}
 
-    let (final_archive_path, _) =
-        tauri::async_runtime::spawn_blocking(move || -> Result<(PathBuf, bool), String> {
-            if !downloads_dir_clone.exists() {
-                fs::create_dir_all(&downloads_dir_clone).map_err(|e| e.to_string())?;
-            }
+// --- GAME BUILD DETECTION ---
+
+// Steam: buildid from the appmanifest next to steamapps/common/<installdir>
+fn read_steam_build(game_path: &Path) -> Option<String> {
+    let steamapps = game_path.parent()?.parent()?;
+    let manifest = read_vdf_file(&steamapps.join(format!("appmanifest_{}.acf", NMS_STEAM_APP_ID)))?;
+    manifest
+        .get("AppState")?
+        .get("buildid")?
+        .as_str()
+        .filter(|id| !id.is_empty() && *id != "0")
+        .map(String::from)
+}
 
-            let in_downloads = if let (Ok(p1), Ok(p2)) = (
-                archive_path_clone.canonicalize(),
-                downloads_dir_clone.canonicalize(),
-            ) {
-                p1.starts_with(p2)
-            } else {
-                false
-            };
+// GOG: goggame-<id>.info in the game root carries the Galaxy build ID
+fn read_gog_build(game_path: &Path) -> Option<String> {
+    GOG_GAME_IDS.iter().find_map(|id| {
+        let content = fs::read_to_string(game_path.join(format!("goggame-{}.info", id))).ok()?;
+        let info: Value = serde_json::from_str(&content).ok()?;
+        json_id_to_string(info.get("buildId")).or_else(|| json_id_to_string(info.get("version")))
+    })
+}
 
-            if !in_downloads {
-                let file_name = archive_path_clone
-                    .file_name()
-                    .ok_or("Invalid filename".to_string())?;
-                let target_path = downloads_dir_clone.join(file_name);
-                fs::copy(&archive_path_clone, &target_path).map_err(|e| e.to_string())?;
-                Ok((target_path, false))
-            } else {
-                Ok((archive_path_clone, true))
+// Game Pass: package version from appxmanifest.xml, one level above Content
+fn read_gamepass_build(game_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(game_path.parent()?.join("appxmanifest.xml")).ok()?;
+    let mut reader = Reader::from_str(&content);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.name().as_ref() == b"Identity" => {
+                return e
+                    .attributes()
+                    .flatten()
+                    .find(|a| a.key.as_ref() == b"Version")
+                    .map(|a| String::from_utf8_lossy(&a.value).into_owned());
             }
-        })
-        .await
-        .map_err(|e| e.to_string())??;
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
 
-    let final_archive_path_str = final_archive_path.to_string_lossy().into_owned();
+// Fallback: the PE header (first 4 KiB) holds the link timestamp, which changes every
+// build, so hashing it with the file size gives a stable per-build fingerprint.
+fn fingerprint_game_exe(game_path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let exe_path = game_path.join("Binaries").join("NMS.exe");
+    let size = fs::metadata(&exe_path).ok()?.len();
+    let mut header = Vec::with_capacity(4096);
+    fs::File::open(&exe_path)
+        .ok()?
+        .take(4096)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(&header);
+    Some(format!("exe-{}", hex::encode(&hasher.finalize()[..6])))
+}
 
-    // 2. Library Preparation / Extraction Phase
-    // Construct a unique folder name based on the zip filename (e.g. "MyMod.zip_unpacked")
-    let zip_name = final_archive_path.file_name().unwrap().to_string_lossy();
-    let library_folder_name = format!("{}_unpacked", zip_name);
-    let library_mod_path = library_dir.join(&library_folder_name);
+fn detect_game_build(installation: &GameInstallation) -> Option<String> {
+    let game_path = Path::new(&installation.path);
+    let store_build = match installation.version_type.as_str() {
+        "Steam" => read_steam_build(game_path),
+        "GOG" => read_gog_build(game_path),
+        "GamePass" => read_gamepass_build(game_path),
+        _ => None,
+    };
+    store_build.or_else(|| fingerprint_game_exe(game_path))
+}
 
-    // Clone for thread
-    let final_archive_path_clone = final_archive_path.clone();
-    let library_mod_path_clone = library_mod_path.clone();
+// Nothing to report while the current build is unknown
+fn mod_build_status(
+    mod_build: Option<&str>,
+    current_build: Option<&str>,
+) -> Option<ModBuildStatus> {
+    match (mod_build, current_build?) {
+        (Some(m), c) if m != c => Some(ModBuildStatus::DifferentBuild),
+        (Some(_), _) => None,
+        (None, _) => Some(ModBuildStatus::Unrecorded),
+    }
+}
 
-    // Start Task
-    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
-        // OPTIMIZATION: Check if already extracted
-        if library_mod_path_clone.exists() {
-            // Optional: You could verify contents here, but for now assume if folder exists, it's good.
-            // We skip extraction!
-            return Ok(());
+// Remembers the last build seen per installation and logs game updates
+fn record_game_build(app: &AppHandle, installation_id: &str, build: Option<&str>) {
+    let Some(build) = build else {
+        return;
+    };
+    let recorded = update_app_config(app, |config| {
+        let entry = config
+            .game_installations
+            .iter_mut()
+            .find(|i| i.id == installation_id)?;
+        if entry.game_build.as_deref() == Some(build) {
+            return None;
         }
+        entry.game_build.replace(build.to_string())
+    });
+    match recorded {
+        Ok(Some(previous)) => log_internal(
+            app,
+            "WARN",
+            &format!(
+                "Game updated: build {} -> {}. Mods may need updates.",
+                previous, build
+            ),
+        ),
+        Ok(None) => {}
+        Err(e) => log_internal(app, "WARN", &format!("Failed to record game build: {}", e)),
+    }
+}
 
-        // If not, extract to the permanent library folder
-        extract_archive(
-            &final_archive_path_clone,
-            &library_mod_path_clone,
-            progress_callback,
-        )
-    })
-    .await
-    .map_err(|e| e.to_string())??;
+fn extract_archive<F>(
+    archive_path: &Path,
+    destination: &Path, // <--- CHANGED: Exact path where files go
+    on_progress: F,
+) -> Result<(), String>
+// <--- CHANGED: Returns (), path is already known
+where
+    F: Fn(u64),
+{
+    if !destination.exists() {
+        fs::create_dir_all(destination).map_err(|e| format!("Could not create dest dir: {}", e))?;
+    }
 
-    // 3. Analysis Phase (Scanning the Library)
-    emit_progress("Analyzing structure...");
+    let abs_archive_path = archive_path
+        .canonicalize()
+        .map_err(|e| format!("Invalid archive path '{}': {}", archive_path.display(), e))?;
 
-    let library_mod_path_clone = library_mod_path.clone();
+    let extension = archive_path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
 
-    let (folder_entries, installable_paths) = tauri::async_runtime::spawn_blocking(move || {
-        let installable =
-            scan_for_installable_mods(&library_mod_path_clone, &library_mod_path_clone);
+    match extension.as_str() {
+        "zip" => {
+            let file = fs::File::open(&abs_archive_path).map_err(|e| e.to_string())?;
+            let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
 
-        let entries: Vec<_> = fs::read_dir(&library_mod_path_clone)
-            .map_err(|e| e.to_string())?
-            .filter_map(Result::ok)
-            .filter(|entry| entry.path().is_dir())
-            .collect();
+            let total_files = archive.len();
+            for i in 0..total_files {
+                let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
 
-        Ok::<_, String>((entries, installable))
-    })
-    .await
-    .map_err(|e| e.to_string())??;
+                let outpath = match file.enclosed_name() {
+                    Some(path) => destination.join(path), // Use destination directly
+                    None => continue,
+                };
 
-    // We pass the library folder name as the ID now
-    let library_id = library_folder_name;
+                if file.name().ends_with('/') {
+                    fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+                } else {
+                    if let Some(p) = outpath.parent() {
+                        if !p.exists() {
+                            fs::create_dir_all(p).map_err(|e| e.to_string())?;
+                        }
+                    }
+                    let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
+                    io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
+                }
 
-    // CASE A: Multiple Options
-    if installable_paths.len() > 1 {
+                let pct = ((i as u64 + 1) * 100) / total_files as u64;
+                on_progress(pct);
+            }
+        }
+        "rar" => {
+            let _guard = DIR_LOCK.lock().map_err(|e| e.to_string())?;
+            let original_dir = env::current_dir().map_err(|e| e.to_string())?;
+
+            // Change to destination
+            env::set_current_dir(destination).map_err(|e| e.to_string())?;
+
+            let extract_result = (|| -> Result<(), String> {
+                let mut archive = unrar::Archive::new(&abs_archive_path)
+                    .open_for_processing()
+                    .map_err(|e| format!("{:?}", e))?;
+
+                while let Ok(Some(header)) = archive.read_header() {
+                    archive = header.extract().map_err(|e| format!("{:?}", e))?;
+                    on_progress(0);
+                }
+                Ok(())
+            })();
+
+            let _ = env::set_current_dir(&original_dir);
+            extract_result?;
+            on_progress(100);
+        }
+        "7z" => {
+            on_progress(50);
+            sevenz_rust::decompress_file(&abs_archive_path, destination)
+                .map_err(|e| e.to_string())?;
+            on_progress(100);
+        }
+        _ => return Err(format!("Unsupported file type: .{}", extension)),
+    }
+
+    Ok(())
+}
+
+// --- TAURI COMMANDS ---
+
+#[derive(Serialize, Deserialize)]
+struct HttpResponse {
+    status: u16,
+    status_text: String,
+    body: String,
+    headers: HashMap<String, String>,
+}
+
+#[tauri::command]
+async fn http_request(
+    url: String,
+    method: Option<String>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<HttpResponse, String> {
+    let client = reqwest::Client::new();
+    let method = method.unwrap_or_else(|| "GET".to_string());
+
+    let mut request = match method.to_uppercase().as_str() {
+        "GET" => client.get(&url),
+        "POST" => client.post(&url),
+        "PUT" => client.put(&url),
+        "DELETE" => client.delete(&url),
+        "HEAD" => client.head(&url),
+        _ => return Err(format!("Unsupported HTTP method: {}", method)),
+    };
+
+    // Add headers if provided
+    if let Some(headers_map) = headers {
+        for (key, value) in headers_map {
+            request = request.header(&key, &value);
+        }
+    }
+
+    let response = request.send().await.map_err(|e| {
+        format!("HTTP request failed: {}", e)
+    })?;
+
+    let status = response.status().as_u16();
+    let status_text = response.status().canonical_reason().unwrap_or("").to_string();
+
+    // Extract response headers
+    let mut response_headers = HashMap::new();
+    for (name, value) in response.headers() {
+        if let Ok(value_str) = value.to_str() {
+            response_headers.insert(name.to_string().to_lowercase(), value_str.to_string());
+        }
+    }
+
+    // Check if this is an image request by content-type or URL
+    let content_type = response_headers.get("content-type").unwrap_or(&String::new()).to_lowercase();
+    let is_image = content_type.starts_with("image/") ||
+                   url.contains(".jpg") || url.contains(".jpeg") ||
+                   url.contains(".png") || url.contains(".gif") ||
+                   url.contains(".webp");
+
+    let body = if is_image {
+        // For images, get bytes and encode as base64
+        let bytes = response.bytes().await.map_err(|e| {
+            format!("Failed to read response bytes: {}", e)
+        })?;
+        general_purpose::STANDARD.encode(bytes)
+    } else {
+        // For text content, get as string
+        response.text().await.map_err(|e| {
+            format!("Failed to read response body: {}", e)
+        })?
+    };
+
+    Ok(HttpResponse {
+        status,
+        status_text,
+        body,
+        headers: response_headers,
+    })
+}
+
+#[tauri::command]
+fn get_all_mods_for_render(app: AppHandle) -> Result<Vec<ModRenderData>, String> {
+    let GameContext {
+        game_path,
+        game_build: current_build,
+        ..
+    } = game_context(&app)?;
+    let mods_path = game_path.join("GAMEDATA").join("MODS");
+
+    let settings_dir = game_path.join("Binaries").join("SETTINGS");
+    let settings_file_path = settings_dir.join("GCMODSETTINGS.MXML");
+
+    // Debug: List contents of Binaries and SETTINGS
+    //let binaries_dir = game_path.join("Binaries");
+    //log_internal(&app, "DEBUG", &format!("Checking for Binaries dir at: {}", binaries_dir.display()));
+    /*
+    match std::fs::read_dir(&binaries_dir) {
+        Ok(entries) => {
+            let files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+            log_internal(&app, "DEBUG", &format!("Binaries dir contents: {:?}", files));
+        },
+        Err(e) => {
+            log_internal(&app, "DEBUG", &format!("Failed to read Binaries dir: {}", e));
+        }
+    }*/
+    //log_internal(&app, "DEBUG", &format!("Checking for SETTINGS dir at: {}", settings_dir.display()));
+    /*
+    match std::fs::read_dir(&settings_dir) {
+        Ok(entries) => {
+            let files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+            log_internal(&app, "DEBUG", &format!("SETTINGS dir contents: {:?}", files));
+        },
+        Err(e) => {
+            log_internal(&app, "DEBUG", &format!("Failed to read SETTINGS dir: {}", e));
+        }
+    }*/
+
+    if !settings_file_path.exists() {
+        log_internal(&app, "DEBUG", &format!("GCMODSETTINGS.MXML not found at: {}", settings_file_path.display()));
+        return Ok(Vec::new());
+    }
+
+    // 1. Scan Disk for Real Folders
+    // We create a Set of Uppercase names for easy comparison
+    let mut real_folders_map: HashMap<String, String> = HashMap::new();
+    let mut real_folders_set: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    if let Ok(entries) = fs::read_dir(&mods_path) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                let real_name = entry.file_name().to_string_lossy().into_owned();
+                let upper_name = real_name.to_uppercase();
+                real_folders_map.insert(upper_name.clone(), real_name);
+                real_folders_set.insert(upper_name);
+            }
+        }
+    }
+
+
+    // 2. Read and Parse XML with extra debug
+    log_internal(&app, "DEBUG", &format!("Attempting to read GCMODSETTINGS.MXML at: {}", settings_file_path.display()));
+    let xml_content = match fs::read_to_string(&settings_file_path) {
+        Ok(content) => {
+            log_internal(&app, "DEBUG", &format!("Read GCMODSETTINGS.MXML successfully. Content length: {} bytes", content.len()));
+            // Dump the first 2048 bytes (or less) for debug
+            //let dump_len = content.len().min(2048);
+            //let dump = &content[..dump_len];
+            //log_internal(&app, "DEBUG", &format!("GCMODSETTINGS.MXML dump (first {} bytes):\n{}", dump_len, dump));
+            content
+        },
+        Err(e) => {
+            log_internal(&app, "ERROR", &format!("Failed to read GCMODSETTINGS.MXML: {}", e));
+            return Err(format!("Failed to read GCMODSETTINGS.MXML: {}", e));
+        }
+    };
+    log_internal(&app, "DEBUG", "Parsing GCMODSETTINGS.MXML...");
+    let mut root: SettingsData = match from_str(&xml_content) {
+        Ok(parsed) => {
+            log_internal(&app, "DEBUG", "Parsed GCMODSETTINGS.MXML successfully.");
+            parsed
+        },
+        Err(e) => {
+            log_internal(&app, "ERROR", &format!("Failed to parse GCMODSETTINGS.MXML: {}", e));
+            return Err(format!("Failed to parse GCMODSETTINGS.MXML: {}", e));
+        }
+    };
+
+    let mut dirty = false; // Track if we need to save changes
+
+    // 3. Clean Orphans (Entries in XML but not on Disk)
+    if let Some(prop) = root.properties.iter_mut().find(|p| p.name == "Data") {
+        let original_len = prop.mods.len();
+
+        prop.mods.retain(|entry| {
+            let xml_name = entry.properties.iter()
+                .find(|p| p.name == "Name")
+                .and_then(|p| p.value.as_ref())
+                .map(|s| s.to_uppercase())
+                .unwrap_or_default();
+
+            // Keep it ONLY if it exists on disk
+            real_folders_set.contains(&xml_name)
+        });
+
+        if prop.mods.len() != original_len {
+            dirty = true;
+            // Re-index priorities to avoid gaps
+            for (i, mod_entry) in prop.mods.iter_mut().enumerate() {
+                mod_entry.index = i.to_string();
+                if let Some(priority_prop) = mod_entry.properties.iter_mut().find(|p| p.name == "ModPriority") {
+                    priority_prop.value = Some(i.to_string());
+                }
+            }
+        }
+    }
+
+    // 4. Save XML if we removed anything
+    if dirty {
+        // Reuse serialization logic (inline to avoid ownership issues)
+        let unformatted_xml = to_string(&root).map_err(|e| e.to_string())?;
+        let mut reader = Reader::from_str(&unformatted_xml);
+        reader.config_mut().trim_text(true);
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Eof) => break,
+                Ok(event) => writer.write_event(event).unwrap(),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        let buf = writer.into_inner();
+        let xml_body = String::from_utf8(buf).map_err(|e| e.to_string())?;
+        let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
+            .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
+            .replace(" name=\"Dependencies\" value=\"\"", " name=\"Dependencies\"")
+            .replace("\"/>", "\" />");
+
+        // We use a simplified write here directly since we have the path
+        let _ = fs::write(&settings_file_path, final_content);
+        log_internal(&app, "INFO", "Cleaned orphaned mods from GCMODSETTINGS.MXML");
+    }
+
+    // 5. Build Render List
+    let mut mods_to_render = Vec::new();
+
+    if let Some(prop) = root.properties.iter().find(|p| p.name == "Data") {
+        for mod_entry in &prop.mods {
+            let xml_name_prop = mod_entry
+                .properties
+                .iter()
+                .find(|p| p.name == "Name")
+                .and_then(|p| p.value.as_ref());
+
+            if let Some(xml_name) = xml_name_prop {
+                // Get Real Name from Map
+                let folder_name = real_folders_map
+                    .get(&xml_name.to_uppercase())
+                    .cloned()
+                    .unwrap_or_else(|| xml_name.clone());
+
+                let enabled = mod_entry
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "Enabled")
+                    .and_then(|p| p.value.as_deref())
+                    .unwrap_or("false")
+                    .eq_ignore_ascii_case("true");
+
+                let priority = mod_entry
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "ModPriority")
+                    .and_then(|p| p.value.as_ref())
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .unwrap_or(0);
+
+                let mod_info_path = mods_path.join(&folder_name).join("mod_info.json");
+
+                let local_info = if let Ok(content) = fs::read_to_string(&mod_info_path) {
+                    if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&content) {
+                        Some(LocalModInfo {
+                            folder_name: folder_name.clone(),
+                            mod_id: json_val
+                                .get("modId")
+                                .or(json_val.get("id"))
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                            file_id: json_val
+                                .get("fileId")
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                            version: json_val
+                                .get("version")
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                            install_source: json_val
+                                .get("installSource")
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                            game_build: json_val
+                                .get("gameBuild")
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                        })
+                    } else { None }
+                } else { None };
+
+                // Untracked mods already carry their own marker
+                let build_status = local_info.as_ref().and_then(|i| {
+                    mod_build_status(i.game_build.as_deref(), current_build.as_deref())
+                });
+
+                mods_to_render.push(ModRenderData {
+                    folder_name,
+                    enabled,
+                    priority,
+                    local_info,
+                    build_status,
+                });
+            }
+        }
+    }
+
+    mods_to_render.sort_by_key(|m| m.priority);
+
+    Ok(mods_to_render)
+}
+
+#[tauri::command]
+async fn install_mod_from_archive(
+    app: AppHandle,
+    archive_path_str: String,
+    download_id: String,
+) -> Result<InstallationAnalysis, String> {
+    let id_for_progress = download_id.clone();
+    let app_handle_for_extract = app.clone();
+
+    let progress_callback = move |pct: u64| {
+        let _ = app_handle_for_extract.emit(
+            "install-progress",
+            InstallProgressPayload {
+                id: id_for_progress.clone(),
+                step: format!("Extracting: {}%", pct),
+                progress: Some(pct),
+            },
+        );
+    };
+
+    let emit_progress = |step: &str| {
+        let _ = app.emit(
+            "install-progress",
+            InstallProgressPayload {
+                id: download_id.clone(),
+                step: step.to_string(),
+                progress: None,
+            },
+        );
+    };
+
+    emit_progress("Initializing...");
+
+    let archive_path = PathBuf::from(&archive_path_str);
+    let downloads_dir = get_downloads_dir(&app)?;
+    let library_dir = get_library_dir(&app)?; // <--- CHANGE: Use Library Dir
+
+    // 1. Copying Phase
+    emit_progress("Copying to library...");
+    let archive_path_clone = archive_path.clone();
+    let downloads_dir_clone = downloads_dir.clone();
+
+    let (final_archive_path, _) =
+        tauri::async_runtime::spawn_blocking(move || -> Result<(PathBuf, bool), String> {
+            if !downloads_dir_clone.exists() {
+                fs::create_dir_all(&downloads_dir_clone).map_err(|e| e.to_string())?;
+            }
+
+            let in_downloads = if let (Ok(p1), Ok(p2)) = (
+                archive_path_clone.canonicalize(),
+                downloads_dir_clone.canonicalize(),
+            ) {
+                p1.starts_with(p2)
+            } else {
+                false
+            };
+
+            if !in_downloads {
+                let file_name = archive_path_clone
+                    .file_name()
+                    .ok_or("Invalid filename".to_string())?;
+                let target_path = downloads_dir_clone.join(file_name);
+                fs::copy(&archive_path_clone, &target_path).map_err(|e| e.to_string())?;
+                Ok((target_path, false))
+            } else {
+                Ok((archive_path_clone, true))
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let final_archive_path_str = final_archive_path.to_string_lossy().into_owned();
+
+    // 2. Library Preparation / Extraction Phase
+    // Construct a unique folder name based on the zip filename (e.g. "MyMod.zip_unpacked")
+    let zip_name = final_archive_path.file_name().unwrap().to_string_lossy();
+    let library_folder_name = format!("{}_unpacked", zip_name);
+    let library_mod_path = library_dir.join(&library_folder_name);
+
+    // Clone for thread
+    let final_archive_path_clone = final_archive_path.clone();
+    let library_mod_path_clone = library_mod_path.clone();
+
+    // Start Task
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        // OPTIMIZATION: Check if already extracted
+        if library_mod_path_clone.exists() {
+            // Optional: You could verify contents here, but for now assume if folder exists, it's good.
+            // We skip extraction!
+            return Ok(());
+        }
+
+        // If not, extract to the permanent library folder
+        extract_archive(
+            &final_archive_path_clone,
+            &library_mod_path_clone,
+            progress_callback,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // 3. Analysis Phase (Scanning the Library)
+    emit_progress("Analyzing structure...");
+
+    let library_mod_path_clone = library_mod_path.clone();
+
+    let (folder_entries, installable_paths) = tauri::async_runtime::spawn_blocking(move || {
+        let installable =
+            scan_for_installable_mods(&library_mod_path_clone, &library_mod_path_clone);
+
+        let entries: Vec<_> = fs::read_dir(&library_mod_path_clone)
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .collect();
+
+        Ok::<_, String>((entries, installable))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // We pass the library folder name as the ID now
+    let library_id = library_folder_name;
+
+    // CASE A: Multiple Options
+    if installable_paths.len() > 1 {
         emit_progress("Waiting for selection...");
         return Ok(InstallationAnalysis {
             successes: vec![],
@@ -1186,1680 +2452,6259 @@ async fn install_mod_from_archive(
         });
     }
 
-    // CASE B: Single Deep Folder
-    if installable_paths.len() == 1 {
-        emit_progress("Finalizing...");
-        let mut analysis =
-            finalize_installation(app, library_id, vec![installable_paths[0].clone()], true)?;
-        analysis.active_archive_path = Some(final_archive_path_str);
-        return Ok(analysis);
+    // CASE B: Single Deep Folder
+    if installable_paths.len() == 1 {
+        emit_progress("Finalizing...");
+        let mut analysis =
+            finalize_installation(app, library_id, vec![installable_paths[0].clone()], true)?;
+        analysis.active_archive_path = Some(final_archive_path_str);
+        return Ok(analysis);
+    }
+
+    // CASE C: Fallback
+    if folder_entries.len() > 1 {
+        let folder_names: Vec<String> = folder_entries
+            .iter()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+
+        emit_progress("Waiting for selection...");
+        return Ok(InstallationAnalysis {
+            successes: vec![],
+            conflicts: vec![],
+            messy_archive_path: None,
+            active_archive_path: Some(final_archive_path_str),
+            selection_needed: true,
+            temp_id: Some(library_id),
+            available_folders: Some(folder_names),
+        });
+    }
+
+    // CASE D: Install All
+    emit_progress("Finalizing...");
+    let mut analysis = finalize_installation(app, library_id, vec![], false)?;
+    analysis.active_archive_path = Some(final_archive_path_str);
+
+    Ok(analysis)
+}
+
+#[tauri::command]
+fn finalize_installation(
+    app: AppHandle,
+    library_id: String,
+    selected_folders: Vec<String>,
+    flatten_paths: bool
+) -> Result<InstallationAnalysis, String> {
+    log_internal(&app, "INFO", &format!("Finalizing installation. Source: {}, Flatten: {}", library_id, flatten_paths));
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+
+    let game_path = game_context(&app)?.game_path;
+    let mods_path = game_path.join("GAMEDATA").join("MODS");
+    fs::create_dir_all(&mods_path).map_err(|e| e.to_string())?;
+
+    let library_dir = get_library_dir(&app)?;
+    let source_root = library_dir.join(&library_id);
+
+    if !source_root.exists() {
+        let err = format!("Library folder missing: {:?}", source_root);
+        log_internal(&app, "ERROR", &err);
+        return Err(err);
+    }
+
+    // Load existing mods for conflict checking
+    let mut installed_mods_by_id: HashMap<String, String> = HashMap::new();
+    if let Ok(entries) = fs::read_dir(&mods_path) {
+        for entry in entries.filter_map(Result::ok) {
+            if let Some(info) = read_mod_info(&entry.path()) {
+                if let (Some(mod_id), Some(folder_name)) = (info.mod_id, entry.path().file_name().and_then(|n| n.to_str())) {
+                    installed_mods_by_id.insert(mod_id, folder_name.to_string());
+                }
+            }
+        }
+    }
+
+    let staging_dir = get_staging_dir(&app)?;
+    let conflict_staging_path = staging_dir.join(format!("conflict_{}", Utc::now().timestamp_millis()));
+
+    let mut successes = Vec::new();
+    let mut conflicts = Vec::new();
+
+    // If selected_folders is empty (or just contained "."), fallback to all top-level
+    let items_to_process = if selected_folders.is_empty() || (selected_folders.len() == 1 && selected_folders[0] == ".") {
+        log_internal(&app, "INFO", "No specific folders selected. Scanning all top-level folders.");
+        fs::read_dir(&source_root)
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect::<Vec<String>>()
+    } else {
+        log_internal(&app, "INFO", &format!("Selected folders to install: {:?}", selected_folders));
+        selected_folders
+    };
+
+    struct DeployOp {
+        source: PathBuf,
+        dest_name: String,
+    }
+    let mut ops: Vec<DeployOp> = Vec::new();
+
+    for relative_path_str in items_to_process {
+        // Handle the dot "." explicitly if it still sneaks in
+        let source_path = if relative_path_str == "." {
+            source_root.clone()
+        } else {
+            source_root.join(&relative_path_str)
+        };
+
+        if !source_path.exists() { continue; }
+
+        if flatten_paths {
+            // "Smart Extract": Look deeper for the actual mod root
+            let deep_candidates = scan_for_installable_mods(&source_path, &source_path);
+
+            if !deep_candidates.is_empty() {
+                for deep_rel in deep_candidates {
+                    let deep_source = if deep_rel == "." { source_path.clone() } else { source_path.join(&deep_rel) };
+                    let folder_name = deep_source.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+
+                    // --- DEDUPLICATION CHECK ---
+                    if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
+                        ops.push(DeployOp { source: deep_source, dest_name: folder_name });
+                    }
+                }
+            } else {
+                // No deep structure found, use current
+                let folder_name = source_path.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+
+                // --- DEDUPLICATION CHECK ---
+                if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
+                    ops.push(DeployOp { source: source_path, dest_name: folder_name });
+                }
+            }
+        } else {
+            // Exact Install: Use the folder structure exactly as selected
+            let folder_name = source_path.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+
+            // --- DEDUPLICATION CHECK ---
+            if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
+                ops.push(DeployOp { source: source_path, dest_name: folder_name });
+            }
+        }
+    }
+
+    for op in ops {
+        let mut conflict_found = false;
+
+        if let Some(info) = read_mod_info(&op.source) {
+            if let Some(mod_id) = info.mod_id {
+                if let Some(old_folder_name) = installed_mods_by_id.get(&mod_id) {
+                    if !conflict_staging_path.exists() { fs::create_dir_all(&conflict_staging_path).map_err(|e| e.to_string())?; }
+
+                    let staged_mod_path = conflict_staging_path.join(&op.dest_name);
+
+                    if let Err(e) = deploy_structure_recursive(&op.source, &staged_mod_path) {
+                        log_internal(&app, "ERROR", &format!("Failed to stage conflict: {}", e));
+                    }
+
+                    conflicts.push(ModConflictInfo {
+                        new_mod_name: op.dest_name.clone(),
+                        temp_path: staged_mod_path.to_string_lossy().into_owned(),
+                        old_mod_folder_name: old_folder_name.clone(),
+                    });
+                    conflict_found = true;
+                }
+            }
+        }
+
+        if !conflict_found {
+            let final_dest_path = mods_path.join(&op.dest_name);
+            log_internal(&app, "INFO", &format!("Deploying mod folder: {}", op.dest_name));
+
+            if final_dest_path.exists() {
+                if !conflict_staging_path.exists() { fs::create_dir_all(&conflict_staging_path).map_err(|e| e.to_string())?; }
+                let staged_mod_path = conflict_staging_path.join(&op.dest_name);
+
+                if let Err(e) = deploy_structure_recursive(&op.source, &staged_mod_path) {
+                    log_internal(&app, "ERROR", &format!("Failed to stage overwrite: {}", e));
+                }
+
+                conflicts.push(ModConflictInfo {
+                    new_mod_name: op.dest_name.clone(),
+                    temp_path: staged_mod_path.to_string_lossy().into_owned(),
+                    old_mod_folder_name: op.dest_name.clone(),
+                });
+            } else {
+                if let Err(e) = deploy_structure_recursive(&op.source, &final_dest_path) {
+                    let err_msg = format!("Failed to deploy {}: {}", op.dest_name, e);
+                    log_internal(&app, "ERROR", &err_msg);
+                    return Err(err_msg);
+                }
+
+                successes.push(ModInstallInfo {
+                    name: op.dest_name,
+                    temp_path: final_dest_path.to_string_lossy().into_owned(),
+                });
+            }
+        }
+    }
+
+    log_internal(&app, "INFO", "Installation finalization complete.");
+
+    Ok(InstallationAnalysis {
+        successes,
+        conflicts,
+        messy_archive_path: None,
+        active_archive_path: None,
+        selection_needed: false,
+        temp_id: None,
+        available_folders: None,
+    })
+}
+
+#[tauri::command]
+fn resolve_conflict(
+    app: AppHandle,
+    new_mod_name: String,
+    old_mod_folder_name: String,
+    temp_mod_path_str: String,
+    replace: bool,
+) -> Result<(), String> {
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+    let game_path = game_context(&app)?.game_path;
+    let mods_path = game_path.join("GAMEDATA").join("MODS");
+    let old_mod_path = mods_path.join(&old_mod_folder_name);
+    let final_new_mod_path = mods_path.join(&new_mod_name);
+    let temp_mod_path = PathBuf::from(&temp_mod_path_str);
+
+    if replace {
+        if old_mod_path.exists() {
+            fs::remove_dir_all(&old_mod_path)
+                .map_err(|e| format!("Failed to remove old mod: {}", e))?;
+        }
+        // CHANGE: Use safe move (temp is in AppData, destination is Game folder)
+        move_dir_safely(&temp_mod_path, &final_new_mod_path)?;
+    } else {
+        // Just delete the staged folder in AppData
+        fs::remove_dir_all(&temp_mod_path)
+            .map_err(|e| format!("Failed to cleanup temp mod folder: {}", e))?;
+    }
+
+    // Clean up parent conflict container if empty
+    if let Some(parent) = temp_mod_path.parent() {
+        if parent.exists() && parent.read_dir().is_ok_and(|mut i| i.next().is_none()) {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+    sync_active_profile_in_background(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_settings_file(app: AppHandle) -> Result<String, String> {
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+    if let Ok(GameContext { game_path, .. }) = game_context(&app) {
+        let settings_file = game_path
+            .join("Binaries")
+            .join("SETTINGS")
+            .join("GCMODSETTINGS.MXML");
+        if settings_file.exists() {
+            fs::remove_file(&settings_file).map_err(|e| {
+                format!(
+                    "Failed to delete file at '{}': {}",
+                    settings_file.display(),
+                    e
+                )
+            })?;
+            Ok("alertDeleteSuccess".to_string())
+        } else {
+            Ok("alertDeleteNotFound".to_string())
+        }
+    } else {
+        Err("alertDeleteError".to_string())
+    }
+}
+
+#[tauri::command]
+fn detect_game_installation(app: AppHandle) -> Option<GamePaths> {
+    log_internal(&app, "INFO", "Starting Game Detection...");
+
+    if let Ok(GameContext { installation, .. }) = game_context(&app) {
+        let settings_dir = Path::new(&installation.path)
+            .join("Binaries")
+            .join("SETTINGS");
+
+        if settings_dir.exists() {
+            log_internal(
+                &app,
+                "INFO",
+                &format!(
+                    "Using {} installation ({}): {}",
+                    installation.version_type, installation.source, installation.path
+                ),
+            );
+            return Some(game_paths_for_installation(&app, &installation));
+        }
+    }
+
+    log_internal(
+        &app,
+        "WARN",
+        "Game detection failed: No valid installation found.",
+    );
+    None
+}
+
+#[tauri::command]
+fn open_mods_folder(app: AppHandle) -> Result<(), String> {
+    if let Ok(GameContext { game_path, .. }) = game_context(&app) {
+        let mods_path = game_path.join("GAMEDATA").join("MODS");
+        fs::create_dir_all(&mods_path).map_err(|e| {
+            format!(
+                "Could not create MODS folder at '{}': {}",
+                mods_path.display(),
+                e
+            )
+        })?;
+        open::that(&mods_path).map_err(|e| {
+            format!(
+                "Could not open MODS folder at '{}': {}",
+                mods_path.display(),
+                e
+            )
+        })?;
+        Ok(())
+    } else {
+        Err("Game path not found.".to_string())
+    }
+}
+
+#[tauri::command]
+fn save_file(app: AppHandle, file_path: String, content: String) -> Result<(), String> {
+    log_internal(&app, "INFO", &format!("Saving MXML to: {}", file_path));
+    // Reorders and enable toggles reach the live settings file through here
+    let is_live_settings = game_context(&app)
+        .is_ok_and(|ctx| is_live_settings_path(Path::new(&file_path), &ctx.game_path));
+    if is_live_settings {
+        ensure_game_not_running(&app)?;
+        ensure_no_bisect(&app)?;
+    }
+
+    fs::write(&file_path, content).map_err(|e| {
+        let err = format!("Failed to write to file '{}': {}", file_path, e);
+        log_internal(&app, "ERROR", &err);
+        err
+    })?;
+
+    if is_live_settings {
+        sync_active_profile_in_background(&app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn resize_window(window: tauri::Window, width: f64) -> Result<(), String> {
+    let current_height = window.outer_size().map_err(|e| e.to_string())?.height;
+    window
+        .set_size(LogicalSize::new(width, current_height as f64))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn rename_mod_folder(
+    app: AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<Vec<ModRenderData>, String> {
+    log_internal(
+        &app,
+        "INFO",
+        &format!("Requesting rename: '{}' -> '{}'", old_name, new_name),
+    );
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+
+    let game_path = game_context(&app)?.game_path;
+    let mods_path = game_path.join("GAMEDATA").join("MODS");
+
+    let old_path = mods_path.join(&old_name);
+    let new_path = mods_path.join(&new_name);
+
+    // 1. Validation
+    if !old_path.exists() {
+        return Err("Original mod folder not found.".to_string());
+    }
+    if new_path.exists() {
+        return Err("A mod with the new name already exists.".to_string());
+    }
+
+    // Reads source zip from mod_info.json and renames the folder inside the Library
+    if let Some(info) = read_mod_info(&old_path) {
+        if let Some(source_zip) = info.install_source {
+            if let Ok(library_dir) = get_library_dir(&app) {
+                let lib_unpacked = library_dir.join(format!("{}_unpacked", source_zip));
+                let lib_old_path = lib_unpacked.join(&old_name);
+                let lib_new_path = lib_unpacked.join(&new_name);
+
+                if lib_old_path.exists() && !lib_new_path.exists() {
+                    // Try to rename in library. If it fails, it log it but don't stop the game rename.
+                    if let Err(e) = fs::rename(&lib_old_path, &lib_new_path) {
+                        log_internal(
+                            &app,
+                            "WARN",
+                            &format!("Failed to sync rename to Library: {}", e),
+                        );
+                    } else {
+                        log_internal(
+                            &app,
+                            "INFO",
+                            "Synced folder rename to Library successfully.",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // 2. Rename Folder (Physically moves the folder, keeping the casing of new_name)
+    fs::rename(&old_path, &new_path).map_err(|e| {
+        let err = format!("Failed to rename folder: {}", e);
+        log_internal(&app, "ERROR", &err);
+        err
+    })?;
+
+    // 3. Update XML
+    // Reuse the logic from update_mod_name_in_xml but integrated here to avoid double-parsing.
+    let settings_file = game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+    // Saving the live settings already syncs the active profile
+    let mut synced = false;
+    if settings_file.exists() {
+        match update_mod_name_in_xml(app.clone(), old_name.clone(), new_name.clone()) {
+            Ok(new_xml) => {
+                synced = save_file(
+                    app.clone(),
+                    settings_file.to_string_lossy().to_string(),
+                    new_xml,
+                )
+                .is_ok();
+            }
+            Err(e) => {
+                log_internal(
+                    &app,
+                    "WARN",
+                    &format!("Folder renamed, but XML update failed: {}", e),
+                );
+            }
+        }
+    }
+    // The folder itself still moved, so the profile has to follow it
+    if !synced {
+        sync_active_profile_in_background(&app);
+    }
+
+    // 4. Return fresh list
+    get_all_mods_for_render(app)
+}
+
+#[tauri::command]
+fn delete_mod(app: AppHandle, mod_name: String) -> Result<Vec<ModRenderData>, String> {
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+    log_internal(
+        &app,
+        "INFO",
+        &format!("Requesting deletion of mod: {}", mod_name),
+    );
+
+    let game_path = game_context(&app)?.game_path;
+    let settings_file_path = game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+    let mod_to_delete_path = game_path.join("GAMEDATA").join("MODS").join(&mod_name);
+
+    if mod_to_delete_path.exists() {
+        if let Err(e) = fs::remove_dir_all(&mod_to_delete_path) {
+            log_internal(
+                &app,
+                "ERROR",
+                &format!("Failed to delete folder {}: {}", mod_name, e),
+            );
+            return Err(format!("Failed to delete mod folder: {}", e));
+        }
+        log_internal(
+            &app,
+            "INFO",
+            &format!("Deleted folder: {:?}", mod_to_delete_path),
+        );
+    } else {
+        log_internal(
+            &app,
+            "WARN",
+            &format!("Folder not found for deletion: {:?}", mod_to_delete_path),
+        );
+    }
+
+    let xml_content = fs::read_to_string(&settings_file_path)
+        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
+    let mut root: SettingsData =
+        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+
+    for prop in root.properties.iter_mut() {
+        if prop.name == "Data" {
+            prop.mods.retain(|entry| {
+                let entry_name = entry
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "Name")
+                    .and_then(|p| p.value.as_deref())
+                    .unwrap_or("");
+
+                !entry_name.eq_ignore_ascii_case(&mod_name)
+            });
+
+            prop.mods.sort_by_key(|entry| {
+                entry
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "ModPriority")
+                    .and_then(|p| p.value.as_ref())
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .unwrap_or(u32::MAX)
+            });
+            for (i, mod_entry) in prop.mods.iter_mut().enumerate() {
+                mod_entry.index = i.to_string();
+            }
+            break;
+        }
+    }
+
+    let unformatted_xml =
+        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
+    let mut reader = Reader::from_str(&unformatted_xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => writer.write_event(event).unwrap(),
+            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+        }
+    }
+    let buf = writer.into_inner();
+    let xml_body = String::from_utf8(buf)
+        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+
+    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
+        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
+        .replace(
+            " name=\"Dependencies\" value=\"\"",
+            " name=\"Dependencies\"",
+        )
+        .replace("\"/>", "\" />");
+
+    fs::write(&settings_file_path, &final_content)
+        .map_err(|e| format!("Failed to save updated GCMODSETTINGS.MXML: {}", e))?;
+
+    sync_active_profile_in_background(&app);
+    get_all_mods_for_render(app)
+}
+
+#[tauri::command]
+fn reorder_mods(app: AppHandle, ordered_mod_names: Vec<String>) -> Result<String, String> {
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+    let game_path = game_context(&app)?.game_path;
+    let settings_file_path = game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+
+    let xml_content = fs::read_to_string(&settings_file_path)
+        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
+    let mut root: SettingsData =
+        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+
+    if let Some(prop) = root.properties.iter_mut().find(|p| p.name == "Data") {
+        let mut mods_map: HashMap<String, ModEntry> = prop
+            .mods
+            .drain(..)
+            .map(|entry| {
+                let name = entry
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "Name")
+                    .and_then(|p| p.value.as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+                (name, entry)
+            })
+            .collect();
+
+        let mut sorted_mods: Vec<ModEntry> = Vec::new();
+        for (new_priority, mod_name_upper) in ordered_mod_names
+            .iter()
+            .map(|n| n.to_uppercase())
+            .enumerate()
+        {
+            if let Some(mut mod_entry) = mods_map.remove(&mod_name_upper) {
+                let new_order_str = new_priority.to_string();
+                mod_entry.index = new_order_str.clone();
+                if let Some(priority_prop) = mod_entry
+                    .properties
+                    .iter_mut()
+                    .find(|p| p.name == "ModPriority")
+                {
+                    priority_prop.value = Some(new_order_str);
+                }
+                sorted_mods.push(mod_entry);
+            }
+        }
+
+        sorted_mods.extend(mods_map.into_values());
+        prop.mods = sorted_mods;
+    }
+
+    let unformatted_xml =
+        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
+
+    let mut reader = Reader::from_str(&unformatted_xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => writer.write_event(event).unwrap(),
+            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+        }
+    }
+    let buf = writer.into_inner();
+    let xml_body = String::from_utf8(buf)
+        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+
+    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
+        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
+        .replace(
+            " name=\"Dependencies\" value=\"\"",
+            " name=\"Dependencies\"",
+        )
+        .replace("\"/>", "\" />");
+
+    Ok(final_content)
+}
+
+#[tauri::command]
+fn update_mod_name_in_xml(
+    app: AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<String, String> {
+    let game_path = game_context(&app)?.game_path;
+    let settings_file_path = game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+
+    let xml_content = fs::read_to_string(&settings_file_path)
+        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
+    let mut root: SettingsData =
+        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+
+    let mut mod_found = false;
+    for prop in root.properties.iter_mut() {
+        if prop.name == "Data" {
+            if let Some(mod_entry) = prop.mods.iter_mut().find(|entry| {
+                if let Some(name_prop) = entry.properties.iter().find(|p| p.name == "Name") {
+                    if let Some(val) = name_prop.value.as_deref() {
+                        val.eq_ignore_ascii_case(&old_name)
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                }
+            }) {
+                if let Some(name_prop) = mod_entry.properties.iter_mut().find(|p| p.name == "Name")
+                {
+                    name_prop.value = Some(new_name.to_uppercase());
+                    mod_found = true;
+                }
+            }
+            break;
+        }
+    }
+
+    if !mod_found {
+        return Err(format!(
+            "Could not find a mod entry with the name '{}' in the XML file.",
+            old_name
+        ));
+    }
+
+    let unformatted_xml =
+        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
+
+    let mut reader = Reader::from_str(&unformatted_xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => writer.write_event(event).unwrap(),
+            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+        }
+    }
+    let buf = writer.into_inner();
+    let xml_body = String::from_utf8(buf)
+        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+
+    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
+        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
+        .replace(
+            " name=\"Dependencies\" value=\"\"",
+            " name=\"Dependencies\"",
+        )
+        .replace("\"/>", "\" />");
+
+    Ok(final_content)
+}
+
+#[tauri::command]
+fn update_mod_id_in_json(
+    app: AppHandle,
+    mod_folder_name: String,
+    new_mod_id: String,
+) -> Result<(), String> {
+    let game_path = game_context(&app)?.game_path;
+    let mod_info_path = game_path
+        .join("GAMEDATA")
+        .join("MODS")
+        .join(&mod_folder_name)
+        .join("mod_info.json");
+
+    if !mod_info_path.exists() {
+        return Err(format!(
+            "mod_info.json not found for mod '{}'.",
+            mod_folder_name
+        ));
+    }
+
+    let content = fs::read_to_string(&mod_info_path)
+        .map_err(|e| format!("Failed to read mod_info.json: {}", e))?;
+
+    let mut json_value: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse mod_info.json: {}", e))?;
+
+    if let Some(obj) = json_value.as_object_mut() {
+        obj.insert("id".to_string(), Value::String(new_mod_id));
+    } else {
+        return Err("mod_info.json is not a valid JSON object.".to_string());
+    }
+
+    let new_content = serde_json::to_string_pretty(&json_value)
+        .map_err(|e| format!("Failed to serialize updated JSON: {}", e))?;
+
+    fs::write(&mod_info_path, new_content)
+        .map_err(|e| format!("Failed to write updated mod_info.json: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn ensure_mod_info(
+    app: AppHandle,
+    mod_folder_name: String,
+    mod_id: String,
+    file_id: String,
+    version: String,
+    install_source: String,
+) -> Result<(), String> {
+    let GameContext {
+        game_path,
+        game_build,
+        ..
+    } = game_context(&app)?;
+    let mod_info_path = game_path
+        .join("GAMEDATA")
+        .join("MODS")
+        .join(&mod_folder_name)
+        .join("mod_info.json");
+
+    let mut json_value: Value;
+
+    if mod_info_path.exists() {
+        let content = fs::read_to_string(&mod_info_path).map_err(|e| e.to_string())?;
+        json_value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    } else {
+        json_value = serde_json::json!({});
+    }
+
+    if let Some(obj) = json_value.as_object_mut() {
+        if !mod_id.is_empty() {
+            obj.insert("modId".to_string(), Value::String(mod_id));
+        }
+        if !file_id.is_empty() {
+            obj.insert("fileId".to_string(), Value::String(file_id));
+        }
+        if !version.is_empty() {
+            obj.insert("version".to_string(), Value::String(version));
+        }
+        // Save the source zip name so Profile Save knows where this folder came from
+        obj.insert("installSource".to_string(), Value::String(install_source));
+        // Remember which game build this was installed under
+        if let Some(build) = game_build {
+            obj.insert("gameBuild".to_string(), Value::String(build));
+        }
+    }
+
+    let new_content = serde_json::to_string_pretty(&json_value).map_err(|e| e.to_string())?;
+    fs::write(&mod_info_path, new_content).map_err(|e| e.to_string())?;
+
+    // Profiles group folders by installSource, so a new install only counts from here on
+    sync_active_profile_in_background(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_nexus_api_key(app: tauri::AppHandle) -> Result<String, String> {
+    match load_api_key(&app)? {
+        Some(key) => Ok(key),
+        None => Err("No API Key found. Please log in.".to_string()),
+    }
+}
+
+#[tauri::command]
+fn unregister_nxm_protocol() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        hkcu.delete_subkey_all("Software\\Classes\\nxm")
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let home = std::env::var("HOME").map_err(|_| "Could not find HOME".to_string())?;
+        let desktop_file =
+            PathBuf::from(home).join(".local/share/applications/nxm-handler.desktop");
+        if desktop_file.exists() {
+            fs::remove_file(desktop_file).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn is_protocol_handler_registered() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_path_str) = exe_path.to_str() {
+                let hkcr = RegKey::predef(HKEY_CLASSES_ROOT);
+                if let Ok(command_key) = hkcr.open_subkey("nxm\\shell\\open\\command") {
+                    if let Ok(command_val) = command_key.get_value::<String, _>("") {
+                        return command_val.contains(exe_path_str);
+                    }
+                }
+            }
+        }
+        return false;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let home = std::env::var("HOME").unwrap_or_default();
+        let desktop_file =
+            PathBuf::from(home).join(".local/share/applications/nxm-handler.desktop");
+        desktop_file.exists()
+    }
+}
+
+#[tauri::command]
+fn register_nxm_protocol() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+        let exe_path_str = exe_path.to_string_lossy();
+        let command = format!("\"{}\" \"%1\"", exe_path_str);
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let (nxm_key, _) = hkcu
+            .create_subkey("Software\\Classes\\nxm")
+            .map_err(|e| e.to_string())?;
+
+        nxm_key
+            .set_value("", &"URL:NXM Protocol")
+            .map_err(|e| e.to_string())?;
+        nxm_key
+            .set_value("URL Protocol", &"")
+            .map_err(|e| e.to_string())?;
+
+        let (command_key, _) = nxm_key
+            .create_subkey_with_flags("shell\\open\\command", KEY_WRITE)
+            .map_err(|e| e.to_string())?;
+        command_key
+            .set_value("", &command)
+            .map_err(|e| e.to_string())?;
+
+        println!("Successfully registered nxm:// protocol handler to current user.");
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+        let home = std::env::var("HOME").map_err(|_| "Could not find HOME".to_string())?;
+
+        // 1. Create the .desktop file content
+        let desktop_content = format!(
+            "[Desktop Entry]\n\
+            Type=Application\n\
+            Name=Singularity Mod Manager\n\
+            Exec=\"{}\" %u\n\
+            StartupNotify=false\n\
+            MimeType=x-scheme-handler/nxm;\n",
+            exe_path.to_string_lossy()
+        );
+
+        // 2. Write to ~/.local/share/applications/nxm-handler.desktop
+        let apps_dir = PathBuf::from(&home).join(".local/share/applications");
+        if !apps_dir.exists() {
+            fs::create_dir_all(&apps_dir).map_err(|e| e.to_string())?;
+        }
+        let desktop_file = apps_dir.join("nxm-handler.desktop");
+        fs::write(&desktop_file, desktop_content).map_err(|e| e.to_string())?;
+
+        // 3. Register the mimetype using xdg-mime
+        use std::process::Command;
+        Command::new("xdg-mime")
+            .args(["default", "nxm-handler.desktop", "x-scheme-handler/nxm"])
+            .output()
+            .map_err(|e| format!("Failed to run xdg-mime: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn download_mod_archive(
+    app: AppHandle,
+    download_url: String,
+    file_name: String,
+    download_id: Option<String>,
+) -> Result<DownloadResult, String> {
+    log_internal(
+        &app,
+        "INFO",
+        &format!("Starting download request for: {}", file_name),
+    );
+
+    let downloads_path = get_downloads_dir(&app)?;
+    let final_archive_path = downloads_path.join(&file_name);
+
+    let mut response = reqwest::get(&download_url).await.map_err(|e| {
+        let err = format!("Failed to initiate HTTP request: {}", e);
+        log_internal(&app, "ERROR", &err);
+        err
+    })?;
+
+    if !response.status().is_success() {
+        let err = format!("Download failed with HTTP status: {}", response.status());
+        log_internal(&app, "ERROR", &err);
+        return Err(err);
+    }
+
+    let total_size = response.content_length().unwrap_or(0);
+    log_internal(
+        &app,
+        "INFO",
+        &format!("Connection established. Content-Length: {}", total_size),
+    );
+
+    let mut file = fs::File::create(&final_archive_path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+
+    let mut downloaded: u64 = 0;
+
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        downloaded += chunk.len() as u64;
+
+        if let Some(id) = &download_id {
+            if let Some(pct) = (downloaded * 100).checked_div(total_size) {
+                // Don't log every percentage to disk, too spammy. Frontend handles visual progress.
+                let _ = app.emit(
+                    "install-progress",
+                    InstallProgressPayload {
+                        id: id.clone(),
+                        step: format!("Downloading: {}%", pct),
+                        progress: Some(pct),
+                    },
+                );
+            }
+        }
+    }
+
+    let metadata = fs::metadata(&final_archive_path).map_err(|e| e.to_string())?;
+    let file_size = metadata.len();
+
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Download finished. File: {:?} (Size: {} bytes)",
+            final_archive_path, file_size
+        ),
+    );
+
+    let created_time = metadata
+        .created()
+        .map_err(|e| e.to_string())?
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+
+    Ok(DownloadResult {
+        path: final_archive_path.to_string_lossy().into_owned(),
+        size: file_size,
+        created_at: created_time,
+    })
+}
+
+#[tauri::command]
+fn show_in_folder(path: String) {
+    #[cfg(target_os = "windows")]
+    {
+        use std::process::Command;
+        Command::new("explorer")
+            .args(["/select,", &path])
+            .spawn()
+            .unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+        let p = PathBuf::from(&path);
+
+        if let Some(parent) = p.parent() {
+            Command::new("xdg-open").arg(parent).spawn().ok();
+        } else {
+            Command::new("xdg-open").arg(path).spawn().ok();
+        }
+    }
+}
+
+#[tauri::command]
+fn delete_archive_file(path: String) -> Result<(), String> {
+    if Path::new(&path).exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_downloads_folder(app: AppHandle) -> Result<(), String> {
+    // 1. Clear Downloads
+    let downloads_path = get_downloads_dir(&app)?;
+    if downloads_path.exists() {
+        let entries = fs::read_dir(&downloads_path).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                fs::remove_file(path).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    // 2. Clear Library
+    let library_path = get_library_dir(&app)?;
+    if library_path.exists() {
+        let entries = fs::read_dir(&library_path).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Library contains folders, so remove_dir_all
+            if path.is_dir() {
+                fs::remove_dir_all(path).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// --- GAME LAUNCH ---
+
+// How an installation is started. Saved per installation in the registry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LaunchStrategy {
+    // steam://run/275850 (Proton on Linux)
+    SteamUri,
+    // heroic://launch?appName=...&runner=gog|legendary
+    HeroicUri {
+        app_name: String,
+        runner: String,
+    },
+    // lutris:rungame/<slug> or lutris:rungameid/<id>
+    LutrisRungame {
+        game: String,
+    },
+    // User template; {game_path}, {binaries} and {exe} are substituted
+    Custom {
+        command: String,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    // Open Binaries/NMS.exe directly (Windows)
+    Executable,
+}
+
+// Launcher IDs come from detection; when none was found the user has to fill them in
+fn default_launch_strategy(installation: &GameInstallation) -> LaunchStrategy {
+    let launcher_id = installation.launcher_id.clone().unwrap_or_default();
+    match (
+        installation.version_type.as_str(),
+        installation.source.as_str(),
+    ) {
+        ("Steam", _) => LaunchStrategy::SteamUri,
+        ("Epic", _) => LaunchStrategy::HeroicUri {
+            app_name: launcher_id,
+            runner: "legendary".to_string(),
+        },
+        (_, "heroic") => LaunchStrategy::HeroicUri {
+            app_name: launcher_id,
+            runner: "gog".to_string(),
+        },
+        (_, "lutris") => LaunchStrategy::LutrisRungame { game: launcher_id },
+        _ => LaunchStrategy::Executable,
+    }
+}
+
+fn is_flatpak() -> bool {
+    std::env::var("FLATPAK_ID").is_ok() || std::env::var("SINGULARITY_FLATPAK").is_ok()
+}
+
+// Splits a command template into arguments, honouring single and double quotes
+fn split_command_line(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote in launch command.".to_string());
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn parse_env_assignment(arg: &str) -> Option<(String, String)> {
+    let (key, value) = arg.split_once('=')?;
+    let valid_key = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid_key.then(|| (key.to_string(), value.to_string()))
+}
+
+fn expand_launch_template(template: &str, game_path: &Path) -> String {
+    let binaries = game_path.join("Binaries");
+    template
+        .replace("{game_path}", &game_path.to_string_lossy())
+        .replace("{binaries}", &binaries.to_string_lossy())
+        .replace("{exe}", &binaries.join("NMS.exe").to_string_lossy())
+}
+
+// Placeholders are filled in after splitting, so quotes in the game path ("No Man's Sky")
+// never reach the parser
+fn launch_template_args(template: &str, game_path: &Path) -> Result<Vec<String>, String> {
+    Ok(split_command_line(template)?
+        .iter()
+        .map(|arg| expand_launch_template(arg, game_path))
+        .collect())
+}
+
+// Inside the Flatpak sandbox, launchers live on the host and must be started through
+// flatpak-spawn; environment variables have to be forwarded explicitly.
+fn spawn_on_host(args: &[String], env: &HashMap<String, String>) -> Result<(), String> {
+    let (program, rest) = args
+        .split_first()
+        .ok_or_else(|| "Launch command is empty.".to_string())?;
+
+    let mut command = if is_flatpak() {
+        let mut command = std::process::Command::new("flatpak-spawn");
+        command.arg("--host");
+        for (key, value) in env {
+            command.arg(format!("--env={}={}", key, value));
+        }
+        command.arg(program);
+        command
+    } else {
+        let mut command = std::process::Command::new(program);
+        command.envs(env);
+        command
+    };
+    let mut child = command
+        .args(rest)
+        .spawn()
+        .map_err(|e| format!("Failed to start '{}': {}", program, e))?;
+    // Reap the process when it exits so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn open_uri_on_host(uri: &str) -> Result<(), String> {
+    if is_flatpak() {
+        // xdg-open can't handle launcher URIs inside the Flatpak sandbox
+        spawn_on_host(&["xdg-open".to_string(), uri.to_string()], &HashMap::new())
+    } else {
+        open::that(uri).map_err(|e| e.to_string())
+    }
+}
+
+fn run_launch_strategy(strategy: &LaunchStrategy, game_path: &Path) -> Result<(), String> {
+    match strategy {
+        LaunchStrategy::SteamUri => open_uri_on_host(&format!("steam://run/{}", NMS_STEAM_APP_ID)),
+        LaunchStrategy::HeroicUri { app_name, runner } => {
+            if app_name.is_empty() {
+                return Err("Set the Heroic app name for this installation first.".to_string());
+            }
+            open_uri_on_host(&format!(
+                "heroic://launch?appName={}&runner={}",
+                app_name, runner
+            ))
+        }
+        LaunchStrategy::LutrisRungame { game } => {
+            if game.is_empty() {
+                return Err("Set the Lutris game for this installation first.".to_string());
+            }
+            let uri = if game.chars().all(|c| c.is_ascii_digit()) {
+                format!("lutris:rungameid/{}", game)
+            } else {
+                format!("lutris:rungame/{}", game)
+            };
+            open_uri_on_host(&uri)
+        }
+        LaunchStrategy::Custom { command, env } => {
+            let mut args = launch_template_args(command, game_path)?;
+            // Shell-style VAR=value prefixes ("PROTON_LOG=1 gamemoderun ...") become env vars
+            let mut env = env.clone();
+            while let Some((key, value)) = args.first().and_then(|arg| parse_env_assignment(arg)) {
+                env.insert(key, value);
+                args.remove(0);
+            }
+            spawn_on_host(&args, &env)
+        }
+        LaunchStrategy::Executable => {
+            let exe_path = game_path.join("Binaries").join("NMS.exe");
+            if exe_path.exists() {
+                open::that(exe_path).map_err(|e| e.to_string())
+            } else {
+                Err("Could not find NMS.exe in Binaries folder.".to_string())
+            }
+        }
+    }
+}
+
+#[tauri::command]
+fn get_launch_strategy(app: AppHandle) -> Result<LaunchStrategy, String> {
+    let installation = game_context(&app)?.installation;
+    Ok(installation
+        .launch
+        .clone()
+        .unwrap_or_else(|| default_launch_strategy(&installation)))
+}
+
+// Saves the strategy for an installation (the active one when no ID is given).
+// Passing no strategy resets it to the default for that installation.
+#[tauri::command]
+fn set_launch_strategy(
+    app: AppHandle,
+    installation_id: Option<String>,
+    strategy: Option<LaunchStrategy>,
+) -> Result<(), String> {
+    if let Some(LaunchStrategy::Custom { command, .. }) = &strategy {
+        if split_command_line(command)?.is_empty() {
+            return Err("Launch command is empty.".to_string());
+        }
+    }
+
+    let installation_id = match installation_id {
+        Some(id) => id,
+        None => game_context(&app)?.installation.id,
+    };
+    update_app_config(&app, |config| {
+        let installation = config
+            .game_installations
+            .iter_mut()
+            .find(|i| i.id == installation_id)
+            .ok_or_else(|| format!("Unknown installation: {}", installation_id))?;
+        installation.launch = strategy;
+        Ok::<(), String>(())
+    })??;
+    invalidate_game_context(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn launch_game(app: AppHandle) -> Result<(), String> {
+    if find_game_process().is_some() {
+        return Err("No Man's Sky is already running.".to_string());
+    }
+    if let Err(e) = backup_saves_off_thread(&app, "launch").await {
+        log_internal(&app, "WARN", &e);
+    }
+    let ctx = game_context(&app)?;
+    let strategy = ctx
+        .installation
+        .launch
+        .clone()
+        .unwrap_or_else(|| default_launch_strategy(&ctx.installation));
+
+    log_internal(&app, "INFO", &format!("Launching game with {:?}", strategy));
+    run_launch_strategy(&strategy, &ctx.game_path).map_err(|e| {
+        log_internal(&app, "ERROR", &format!("Launch failed: {}", e));
+        e
+    })
+}
+
+// --- GAME PROCESS MONITOR ---
+
+const GAME_MONITOR_INTERVAL_SECS: u64 = 5;
+// Sessions shorter than this are logged as a possible crash; during a crash search the
+// prompt mentions it, but the user always decides
+const CRASH_SUSPECT_SECS: i64 = 90;
+const MAX_PLAY_SESSIONS: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PlaySession {
+    id: String,
+    pid: u32,
+    started_at: String,
+    #[serde(default)]
+    ended_at: Option<String>,
+    #[serde(default)]
+    duration_secs: Option<i64>,
+    profile: Option<String>,
+    installation_id: Option<String>,
+    game_build: Option<String>,
+    enabled_mods: Vec<String>,
+    #[serde(default)]
+    suspected_crash: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct GameProcessStatus {
+    running: bool,
+    session: Option<PlaySession>,
+}
+
+#[derive(Default)]
+struct GameProcessState {
+    session: Mutex<Option<PlaySession>>,
+}
+
+// Proton/Wine processes keep the Windows exe path somewhere in their command line
+#[cfg(any(target_os = "linux", test))]
+fn cmdline_runs_nms(cmdline: &[u8]) -> bool {
+    cmdline
+        .split(|b| *b == 0)
+        .map(String::from_utf8_lossy)
+        .any(|arg| {
+            arg.rsplit(['/', '\\'])
+                .next()
+                .is_some_and(|exe| exe.eq_ignore_ascii_case("NMS.exe"))
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn find_game_process() -> Option<u32> {
+    let own_pid = std::process::id();
+    fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| *pid != own_pid)
+        .find(|pid| {
+            let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+            comm.trim().eq_ignore_ascii_case("NMS.exe")
+                || fs::read(format!("/proc/{}/cmdline", pid))
+                    .is_ok_and(|cmdline| cmdline_runs_nms(&cmdline))
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn find_game_process() -> Option<u32> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Polled every few seconds, so it must not flash a console window
+    let output = Command::new("tasklist")
+        .args(["/FI", "IMAGENAME eq NMS.exe", "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.to_lowercase().starts_with("\"nms.exe\""))
+        .and_then(|line| line.split(',').nth(1))
+        .and_then(|pid| pid.trim_matches('"').parse().ok())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn find_game_process() -> Option<u32> {
+    None
+}
+
+fn ensure_game_not_running(app: &AppHandle) -> Result<(), String> {
+    if find_game_process().is_some() {
+        log_internal(
+            app,
+            "WARN",
+            "Refused to change mod files while the game is running.",
+        );
+        return Err("No Man's Sky is running. Close the game before changing mods.".to_string());
+    }
+    Ok(())
+}
+
+fn get_sessions_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_singularity_root(app)?.join("play_sessions.json"))
+}
+
+fn load_play_sessions(app: &AppHandle) -> Vec<PlaySession> {
+    get_sessions_file_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn start_play_session(app: &AppHandle, pid: u32) -> PlaySession {
+    let config = load_app_config(app);
+    let ctx = game_context(app).ok();
+    let enabled_mods = ctx
+        .as_ref()
+        .and_then(|ctx| {
+            let settings = ctx.game_path.join("Binaries/SETTINGS/GCMODSETTINGS.MXML");
+            from_str::<SettingsData>(&fs::read_to_string(settings).ok()?).ok()
+        })
+        .map(|root| enabled_mod_names(&root))
+        .unwrap_or_default();
+
+    PlaySession {
+        id: Uuid::new_v4().to_string(),
+        pid,
+        started_at: Utc::now().to_rfc3339(),
+        ended_at: None,
+        duration_secs: None,
+        profile: config.active_profile,
+        installation_id: ctx.as_ref().map(|ctx| ctx.installation.id.clone()),
+        game_build: ctx.and_then(|ctx| ctx.game_build),
+        enabled_mods,
+        suspected_crash: false,
+    }
+}
+
+fn finish_play_session(app: &AppHandle, mut session: PlaySession) -> PlaySession {
+    let ended_at = Utc::now();
+    let duration = chrono::DateTime::parse_from_rfc3339(&session.started_at)
+        .map(|started| (ended_at - started.with_timezone(&Utc)).num_seconds())
+        .unwrap_or(0);
+    session.ended_at = Some(ended_at.to_rfc3339());
+    session.duration_secs = Some(duration);
+    session.suspected_crash = duration < CRASH_SUSPECT_SECS;
+
+    let mut sessions = load_play_sessions(app);
+    sessions.push(session.clone());
+    if sessions.len() > MAX_PLAY_SESSIONS {
+        sessions.drain(..sessions.len() - MAX_PLAY_SESSIONS);
+    }
+    match (
+        get_sessions_file_path(app),
+        serde_json::to_string_pretty(&sessions),
+    ) {
+        (Ok(path), Ok(json)) => {
+            if let Err(e) = fs::write(path, json) {
+                log_internal(app, "ERROR", &format!("Failed to save play session: {}", e));
+            }
+        }
+        _ => log_internal(app, "ERROR", "Failed to save play session."),
+    }
+    session
+}
+
+fn start_game_monitor(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let pid = find_game_process();
+        let state = app.state::<GameProcessState>();
+        let current = state.session.lock().unwrap().clone();
+
+        let changed = match (pid, current) {
+            (Some(pid), None) => {
+                let session = start_play_session(&app, pid);
+                log_internal(
+                    &app,
+                    "INFO",
+                    &format!(
+                        "Game started (pid {}, profile {:?}, {} mods enabled)",
+                        pid,
+                        session.profile,
+                        session.enabled_mods.len()
+                    ),
+                );
+                *state.session.lock().unwrap() = Some(session.clone());
+                Some(GameProcessStatus {
+                    running: true,
+                    session: Some(session),
+                })
+            }
+            (None, Some(session)) => {
+                let session = finish_play_session(&app, session);
+                log_internal(
+                    &app,
+                    if session.suspected_crash {
+                        "WARN"
+                    } else {
+                        "INFO"
+                    },
+                    &format!(
+                        "Game exited after {}s{}",
+                        session.duration_secs.unwrap_or(0),
+                        if session.suspected_crash {
+                            " (possible crash)"
+                        } else {
+                            ""
+                        }
+                    ),
+                );
+                *state.session.lock().unwrap() = None;
+                Some(GameProcessStatus {
+                    running: false,
+                    session: Some(session),
+                })
+            }
+            _ => None,
+        };
+        if let Some(status) = changed {
+            let _ = app.emit("game-process-status", status);
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(GAME_MONITOR_INTERVAL_SECS));
+    });
+}
+
+#[tauri::command]
+fn get_game_status(state: State<'_, GameProcessState>) -> GameProcessStatus {
+    let session = state.session.lock().unwrap().clone();
+    GameProcessStatus {
+        running: session.is_some(),
+        session,
+    }
+}
+
+#[tauri::command]
+fn get_play_sessions(app: AppHandle, limit: Option<usize>) -> Vec<PlaySession> {
+    let mut sessions = load_play_sessions(&app);
+    sessions.reverse();
+    sessions.truncate(limit.unwrap_or(MAX_PLAY_SESSIONS));
+    sessions
+}
+
+// --- LAUNCH VALIDATION ---
+
+#[derive(Serialize, Debug)]
+struct LaunchIssue {
+    // "error" blocks a clean launch, "warning" is informational
+    severity: String,
+    code: String,
+    message: String,
+    mod_name: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct LaunchValidationReport {
+    ok: bool,
+    issues: Vec<LaunchIssue>,
+}
+
+fn launch_issue(
+    severity: &str,
+    code: &str,
+    message: String,
+    mod_name: Option<&str>,
+) -> LaunchIssue {
+    LaunchIssue {
+        severity: severity.to_string(),
+        code: code.to_string(),
+        message,
+        mod_name: mod_name.map(String::from),
+    }
+}
+
+// Dependencies are nested Property elements, which SettingsData doesn't model,
+// so they are read with a separate pass: mod name -> required mod names.
+fn read_mod_dependencies(xml: &str) -> HashMap<String, Vec<String>> {
+    let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+    let mut reader = Reader::from_str(xml);
+    let mut current_mod: Option<String> = None;
+    let mut depth = 0usize;
+    let mut dependencies_depth: Option<usize> = None;
+
+    let attr = |e: &quick_xml::events::BytesStart, key: &[u8]| {
+        e.attributes()
+            .flatten()
+            .find(|a| a.key.as_ref() == key)
+            .map(|a| String::from_utf8_lossy(&a.value).into_owned())
+    };
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                depth += 1;
+                if attr(&e, b"name").as_deref() == Some("Dependencies") {
+                    dependencies_depth = Some(depth);
+                }
+            }
+            Ok(Event::Empty(e)) => {
+                let name = attr(&e, b"name");
+                if dependencies_depth.is_some() {
+                    if let (Some(mod_name), Some(value)) = (&current_mod, attr(&e, b"value")) {
+                        if !value.is_empty() {
+                            dependencies
+                                .entry(mod_name.clone())
+                                .or_default()
+                                .push(value.to_uppercase());
+                        }
+                    }
+                } else if name.as_deref() == Some("Name") {
+                    current_mod = attr(&e, b"value").map(|v| v.to_uppercase());
+                }
+            }
+            Ok(Event::End(_)) => {
+                if dependencies_depth == Some(depth) {
+                    dependencies_depth = None;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    dependencies
+}
+
+// Extensions the game loads from a mod folder; geometry files end in .mbin.pc
+const GAME_DATA_EXTENSIONS: &[&str] = &[
+    "mbin", "pc", "exml", "mxml", "dds", "pak", "spv", "bin", "wem", "bnk", "ttf", "glsl",
+];
+
+// Readmes, licenses and preview images aren't loaded, so sharing them is no conflict
+fn is_game_data_file(relative: &str) -> bool {
+    Path::new(relative)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| {
+            GAME_DATA_EXTENSIONS
+                .iter()
+                .any(|g| e.eq_ignore_ascii_case(g))
+        })
+}
+
+fn collect_relative_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_relative_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative.to_string_lossy().replace('\\', "/").to_lowercase();
+            if relative != "mod_info.json" {
+                files.push(relative);
+            }
+        }
+    }
+}
+
+fn mod_property_value(entry: &ModEntry, name: &str) -> String {
+    entry
+        .properties
+        .iter()
+        .find(|p| p.name == name)
+        .and_then(|p| p.value.clone())
+        .unwrap_or_default()
+}
+
+fn mod_entries(root: &SettingsData) -> impl Iterator<Item = &ModEntry> {
+    root.properties
+        .iter()
+        .filter(|p| p.name == "Data")
+        .flat_map(|p| p.mods.iter())
+}
+
+// Upper-cased folder names of the mods enabled in GCMODSETTINGS
+fn enabled_mod_names(root: &SettingsData) -> Vec<String> {
+    mod_entries(root)
+        .filter(|e| mod_property_value(e, "Enabled").eq_ignore_ascii_case("true"))
+        .map(|e| mod_property_value(e, "Name").to_uppercase())
+        .collect()
+}
+
+fn build_launch_report(game_path: &Path) -> LaunchValidationReport {
+    let mut issues = Vec::new();
+    let mods_dir = game_path.join("GAMEDATA").join("MODS");
+    let settings_file = game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+
+    // 1. Stray .pak files are from the pre-4.0 mod format and are ignored by the game
+    if let Ok(entries) = fs::read_dir(&mods_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_pak = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pak"));
+            if path.is_file() && is_pak {
+                issues.push(launch_issue(
+                    "warning",
+                    "stray_pak",
+                    format!(
+                        "'{}' sits directly in MODS. Loose .pak files are not loaded by current game versions.",
+                        entry.file_name().to_string_lossy()
+                    ),
+                    None,
+                ));
+            }
+        }
+    }
+
+    // 2. The settings file must exist and parse
+    let content = match fs::read_to_string(&settings_file) {
+        Ok(content) => content,
+        Err(_) => {
+            issues.push(launch_issue(
+                "warning",
+                "settings_missing",
+                "GCMODSETTINGS.MXML does not exist yet. The game will create it, with all mods enabled.".to_string(),
+                None,
+            ));
+            return LaunchValidationReport { ok: true, issues };
+        }
+    };
+    let root: SettingsData = match from_str(&content) {
+        Ok(root) => root,
+        Err(e) => {
+            issues.push(launch_issue(
+                "error",
+                "settings_invalid",
+                format!("GCMODSETTINGS.MXML could not be parsed: {}", e),
+                None,
+            ));
+            return LaunchValidationReport { ok: false, issues };
+        }
+    };
+
+    let enabled = enabled_mod_names(&root);
+    let known: Vec<String> = mod_entries(&root)
+        .map(|e| mod_property_value(e, "Name").to_uppercase())
+        .collect();
+
+    // 3. DisableAllMods must be off when mods are enabled
+    let disable_all = root
+        .properties
+        .iter()
+        .find(|p| p.name == "DisableAllMods")
+        .and_then(|p| p.value.as_deref())
+        .unwrap_or("false");
+    if !enabled.is_empty() && disable_all.eq_ignore_ascii_case("true") {
+        issues.push(launch_issue(
+            "error",
+            "all_mods_disabled",
+            format!(
+                "DisableAllMods is true, so none of the {} enabled mods will load.",
+                enabled.len()
+            ),
+            None,
+        ));
+    }
+
+    // 4. Every enabled mod folder exists and has files
+    let folder_names: HashMap<String, String> = fs::read_dir(&mods_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    (name.to_uppercase(), name)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut mod_files: Vec<(String, Vec<String>)> = Vec::new();
+    for mod_name in &enabled {
+        let Some(folder) = folder_names.get(mod_name) else {
+            issues.push(launch_issue(
+                "error",
+                "mod_folder_missing",
+                format!("Enabled mod '{}' has no folder in MODS.", mod_name),
+                Some(mod_name),
+            ));
+            continue;
+        };
+        let folder_path = mods_dir.join(folder);
+        let mut files = Vec::new();
+        collect_relative_files(&folder_path, &folder_path, &mut files);
+        if files.is_empty() {
+            issues.push(launch_issue(
+                "error",
+                "mod_folder_empty",
+                format!("Enabled mod '{}' is an empty folder.", mod_name),
+                Some(mod_name),
+            ));
+            continue;
+        }
+        mod_files.push((mod_name.clone(), files));
+    }
+
+    // 5. Dependencies must be installed and enabled
+    for (mod_name, required) in read_mod_dependencies(&content) {
+        if !enabled.contains(&mod_name) {
+            continue;
+        }
+        for dependency in required {
+            let problem = if !known.contains(&dependency) {
+                Some("is not installed")
+            } else if !enabled.contains(&dependency) {
+                Some("is disabled")
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                issues.push(launch_issue(
+                    "error",
+                    "dependency_missing",
+                    format!(
+                        "'{}' requires '{}', which {}.",
+                        mod_name, dependency, problem
+                    ),
+                    Some(&mod_name),
+                ));
+            }
+        }
+    }
+
+    // 6. Enabled mods that replace the same files
+    let mut owners: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (mod_name, files) in &mod_files {
+        for file in files.iter().filter(|f| is_game_data_file(f)) {
+            owners
+                .entry(file.as_str())
+                .or_default()
+                .push(mod_name.as_str());
+        }
+    }
+    let mut overlaps: HashMap<(&str, &str), usize> = HashMap::new();
+    for mods in owners.values().filter(|mods| mods.len() > 1) {
+        for (i, first) in mods.iter().enumerate() {
+            for second in &mods[i + 1..] {
+                *overlaps.entry((first, second)).or_default() += 1;
+            }
+        }
+    }
+    let mut overlaps: Vec<_> = overlaps.into_iter().collect();
+    overlaps.sort();
+    for ((first, second), count) in overlaps {
+        issues.push(launch_issue(
+            "warning",
+            "file_conflict",
+            format!(
+                "'{}' and '{}' both change {} file(s). Only one version will be used.",
+                first, second, count
+            ),
+            Some(first),
+        ));
+    }
+
+    let ok = !issues.iter().any(|issue| issue.severity == "error");
+    LaunchValidationReport { ok, issues }
+}
+
+#[tauri::command]
+fn validate_launch(app: AppHandle) -> Result<LaunchValidationReport, String> {
+    let game_path = game_context(&app)?.game_path;
+    let report = build_launch_report(&game_path);
+    log_internal(
+        &app,
+        if report.ok { "INFO" } else { "WARN" },
+        &format!(
+            "Pre-launch check: {} issue(s), ok = {}",
+            report.issues.len(),
+            report.ok
+        ),
+    );
+    Ok(report)
+}
+
+// --- CRASH BISECTION ---
+// Narrows a startup crash down to one mod by repeatedly enabling half of the
+// remaining suspects. A mod is only named once a run with just that mod enabled has
+// crashed; the original GCMODSETTINGS is restored when the session ends.
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct BisectStep {
+    enabled: Vec<String>,
+    crashed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct BisectSession {
+    started_at: String,
+    settings_path: String,
+    original_settings: String,
+    suspects: Vec<String>,
+    // Mods enabled for the current test run; empty once finished
+    testing: Vec<String>,
+    steps: Vec<BisectStep>,
+    culprit: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BisectStatus {
+    suspects: Vec<String>,
+    testing: Vec<String>,
+    steps_done: usize,
+    steps_left: usize,
+    culprit: Option<String>,
+    finished: bool,
+}
+
+fn bisect_half(suspects: &[String]) -> Vec<String> {
+    suspects[..suspects.len().div_ceil(2)].to_vec()
+}
+
+fn advance_bisect(session: &mut BisectSession, crashed: bool) {
+    session.steps.push(BisectStep {
+        enabled: session.testing.clone(),
+        crashed,
+    });
+    session.suspects = if crashed {
+        session.testing.clone()
+    } else {
+        session
+            .suspects
+            .iter()
+            .filter(|name| !session.testing.contains(name))
+            .cloned()
+            .collect()
+    };
+
+    match session.suspects.len() {
+        0 => session.testing.clear(),
+        1 => {
+            // A suspect left over by elimination gets one run of its own before it is blamed
+            let confirmed = session
+                .steps
+                .last()
+                .is_some_and(|step| step.crashed && step.enabled == session.suspects);
+            if confirmed {
+                session.culprit = session.suspects.first().cloned();
+                session.testing.clear();
+            } else {
+                session.testing = session.suspects.clone();
+            }
+        }
+        _ => session.testing = bisect_half(&session.suspects),
+    }
+}
+
+fn bisect_status(session: &BisectSession) -> BisectStatus {
+    let finished = session.testing.is_empty();
+    let steps_left = if finished {
+        0
+    } else {
+        ((session.suspects.len() as f64).log2().ceil() as usize).max(1)
+    };
+    BisectStatus {
+        suspects: session.suspects.clone(),
+        testing: session.testing.clone(),
+        steps_done: session.steps.len(),
+        steps_left,
+        culprit: session.culprit.clone(),
+        finished,
+    }
+}
+
+fn get_bisect_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_singularity_root(app)?.join("bisect_session.json"))
+}
+
+fn load_bisect_session(app: &AppHandle) -> Option<BisectSession> {
+    let content = fs::read_to_string(get_bisect_file_path(app).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_bisect_session(app: &AppHandle, session: &BisectSession) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(session).map_err(|e| e.to_string())?;
+    fs::write(get_bisect_file_path(app)?, json).map_err(|e| e.to_string())
+}
+
+fn ensure_no_bisect(app: &AppHandle) -> Result<(), String> {
+    if load_bisect_session(app).is_some() {
+        return Err(
+            "A crash search is in progress. Finish it before changing mods or profiles."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// Enables exactly the given mods (and nothing else) in GCMODSETTINGS
+fn write_bisect_settings(settings_path: &Path, enabled: &[String]) -> Result<(), String> {
+    let content = fs::read_to_string(settings_path)
+        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
+    let mut root: SettingsData =
+        from_str(&content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+
+    for prop in root.properties.iter_mut() {
+        if prop.name == "DisableAllMods" {
+            prop.value = Some("false".to_string());
+        }
+        for entry in prop.mods.iter_mut() {
+            let name = mod_property_value(entry, "Name").to_uppercase();
+            let value = if enabled.contains(&name) {
+                "true"
+            } else {
+                "false"
+            };
+            for p in entry.properties.iter_mut() {
+                if p.name == "Enabled" || p.name == "EnabledVR" {
+                    p.value = Some(value.to_string());
+                }
+            }
+        }
+    }
+    fs::write(settings_path, serialize_settings_xml(&root)?).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn start_mod_bisect(app: AppHandle) -> Result<BisectStatus, String> {
+    ensure_game_not_running(&app)?;
+    if load_bisect_session(&app).is_some() {
+        return Err("A crash search is already in progress.".to_string());
+    }
+
+    let settings_path = game_context(&app)?
+        .game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML");
+    let original_settings = fs::read_to_string(&settings_path)
+        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
+    let root: SettingsData = from_str(&original_settings)
+        .map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+    let suspects = enabled_mod_names(&root);
+    if suspects.is_empty() {
+        return Err("No mods are enabled, so there is nothing to search.".to_string());
+    }
+
+    let session = BisectSession {
+        started_at: Utc::now().to_rfc3339(),
+        settings_path: settings_path.to_string_lossy().into_owned(),
+        original_settings,
+        testing: bisect_half(&suspects),
+        suspects,
+        steps: Vec::new(),
+        culprit: None,
+    };
+    // Persist the snapshot before touching the live file
+    save_bisect_session(&app, &session)?;
+    write_bisect_settings(&settings_path, &session.testing)?;
+
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Crash search started with {} suspects",
+            session.suspects.len()
+        ),
+    );
+    Ok(bisect_status(&session))
+}
+
+#[tauri::command]
+fn get_bisect_status(app: AppHandle) -> Option<BisectStatus> {
+    load_bisect_session(&app).map(|session| bisect_status(&session))
+}
+
+#[tauri::command]
+fn record_bisect_result(app: AppHandle, crashed: bool) -> Result<BisectStatus, String> {
+    ensure_game_not_running(&app)?;
+    let mut session = load_bisect_session(&app).ok_or("No crash search is in progress.")?;
+    if session.testing.is_empty() {
+        return Ok(bisect_status(&session));
+    }
+
+    advance_bisect(&mut session, crashed);
+    save_bisect_session(&app, &session)?;
+    if !session.testing.is_empty() {
+        write_bisect_settings(Path::new(&session.settings_path), &session.testing)?;
+    }
+
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Crash search step {}: {} ({} suspects left, culprit {:?})",
+            session.steps.len(),
+            if crashed { "crashed" } else { "passed" },
+            session.suspects.len(),
+            session.culprit
+        ),
+    );
+    Ok(bisect_status(&session))
+}
+
+#[tauri::command]
+fn finish_mod_bisect(app: AppHandle) -> Result<Option<String>, String> {
+    ensure_game_not_running(&app)?;
+    let session = load_bisect_session(&app).ok_or("No crash search is in progress.")?;
+    fs::write(&session.settings_path, &session.original_settings)
+        .map_err(|e| format!("Failed to restore GCMODSETTINGS.MXML: {}", e))?;
+    fs::remove_file(get_bisect_file_path(&app)?).map_err(|e| e.to_string())?;
+
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Crash search ended, mod settings restored (culprit {:?})",
+            session.culprit
+        ),
+    );
+    Ok(session.culprit)
+}
+
+// --- SAVE BACKUPS ---
+// Saves are zipped before profile switches and launches, since a save made with
+// one mod set can break when loaded with another.
+
+const MAX_AUTO_SAVE_BACKUPS: usize = 20;
+
+// Manual and pre-restore backups are only removed by the user
+fn is_auto_save_backup(backup: &SaveBackup) -> bool {
+    matches!(backup.reason.as_str(), "profile_switch" | "launch")
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SaveBackup {
+    id: String,
+    file_name: String,
+    created_at: String,
+    // Profile that was active when these saves were made
+    profile: Option<String>,
+    // profile_switch, launch, manual or pre_restore
+    reason: String,
+    installation_id: Option<String>,
+    save_path: String,
+    size_bytes: u64,
+}
+
+// <prefix>/drive_c/users/<user>/AppData/Roaming/HelloGames/NMS
+fn wine_prefix_save_dir(drive_c: &Path) -> Option<PathBuf> {
+    fs::read_dir(drive_c.join("users"))
+        .ok()?
+        .flatten()
+        .map(|user| user.path().join("AppData/Roaming/HelloGames/NMS"))
+        .find(|path| path.is_dir())
+}
+
+fn find_save_dir(game_path: &Path) -> Option<PathBuf> {
+    // Steam/GOG on Windows (Game Pass keeps its saves in a sync container we don't touch)
+    #[cfg(target_os = "windows")]
+    if let Ok(appdata) = env::var("APPDATA") {
+        let path = PathBuf::from(appdata).join("HelloGames").join("NMS");
+        if path.is_dir() {
+            return Some(path);
+        }
+    }
+
+    // Proton: steamapps/compatdata/<appid> next to steamapps/common/<game>
+    if let Some(steamapps) = game_path.parent().and_then(|p| p.parent()) {
+        let drive_c = steamapps
+            .join("compatdata")
+            .join(NMS_STEAM_APP_ID)
+            .join("pfx/drive_c");
+        if let Some(path) = wine_prefix_save_dir(&drive_c) {
+            return Some(path);
+        }
+    }
+
+    // Lutris/Bottles/Heroic installs that live inside their Wine prefix
+    game_path
+        .ancestors()
+        .find(|dir| dir.file_name().is_some_and(|name| name == "drive_c"))
+        .and_then(wine_prefix_save_dir)
+}
+
+fn zip_directory(source: &Path, zip_path: &Path) -> Result<(), String> {
+    fn add_dir(
+        writer: &mut zip::ZipWriter<fs::File>,
+        root: &Path,
+        dir: &Path,
+        options: zip::write::SimpleFileOptions,
+    ) -> Result<(), String> {
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            let name = path
+                .strip_prefix(root)
+                .map_err(|e| e.to_string())?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if path.is_dir() {
+                writer
+                    .add_directory(name, options)
+                    .map_err(|e| e.to_string())?;
+                add_dir(writer, root, &path, options)?;
+            } else {
+                writer
+                    .start_file(name, options)
+                    .map_err(|e| e.to_string())?;
+                let mut file = fs::File::open(&path).map_err(|e| e.to_string())?;
+                io::copy(&mut file, writer).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    let file = fs::File::create(zip_path).map_err(|e| e.to_string())?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    add_dir(&mut writer, source, source, options)?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn get_save_backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = get_singularity_root(app)?.join("save_backups");
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+fn load_save_backups(app: &AppHandle) -> Vec<SaveBackup> {
+    get_save_backups_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join("index.json")).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_save_backups(app: &AppHandle, backups: &[SaveBackup]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(backups).map_err(|e| e.to_string())?;
+    fs::write(get_save_backups_dir(app)?.join("index.json"), json).map_err(|e| e.to_string())
+}
+
+// Returns None when no save folder exists yet (e.g. the game was never started)
+fn backup_saves(app: &AppHandle, reason: &str) -> Result<Option<SaveBackup>, String> {
+    let ctx = game_context(app)?;
+    let Some(save_dir) = find_save_dir(&ctx.game_path) else {
+        log_internal(app, "INFO", "No save folder found, skipping save backup.");
+        return Ok(None);
+    };
+
+    let backups_dir = get_save_backups_dir(app)?;
+    let now = Local::now();
+    let id = format!(
+        "{}-{}",
+        now.format("%Y%m%d-%H%M%S"),
+        &Uuid::new_v4().simple().to_string()[..6]
+    );
+    let file_name = format!("{}.zip", id);
+    let zip_path = backups_dir.join(&file_name);
+    if let Err(e) = zip_directory(&save_dir, &zip_path) {
+        fs::remove_file(&zip_path).ok();
+        return Err(format!("Failed to back up saves: {}", e));
+    }
+
+    let backup = SaveBackup {
+        id,
+        file_name,
+        created_at: now.to_rfc3339(),
+        profile: load_app_config(app).active_profile,
+        reason: reason.to_string(),
+        installation_id: Some(ctx.installation.id),
+        save_path: save_dir.to_string_lossy().into_owned(),
+        size_bytes: fs::metadata(&zip_path).map(|m| m.len()).unwrap_or(0),
+    };
+
+    let mut backups = load_save_backups(app);
+    backups.push(backup.clone());
+
+    // Drop the oldest automatic backups
+    let auto_count = backups.iter().filter(|b| is_auto_save_backup(b)).count();
+    let mut to_remove = auto_count.saturating_sub(MAX_AUTO_SAVE_BACKUPS);
+    backups.retain(|b| {
+        if to_remove > 0 && is_auto_save_backup(b) {
+            to_remove -= 1;
+            fs::remove_file(backups_dir.join(&b.file_name)).ok();
+            return false;
+        }
+        true
+    });
+    write_save_backups(app, &backups)?;
+
+    log_internal(
+        app,
+        "INFO",
+        &format!("Backed up saves ({}) to {}", reason, backup.file_name),
+    );
+    Ok(Some(backup))
+}
+
+// Zipping a save folder can take a while; keep it off the command threads
+async fn backup_saves_off_thread(
+    app: &AppHandle,
+    reason: &'static str,
+) -> Result<Option<SaveBackup>, String> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backup_saves(&app, reason))
+        .await
+        .map_err(|e| format!("Save backup task failed: {}", e))?
+}
+
+#[tauri::command]
+fn list_save_backups(app: AppHandle) -> Vec<SaveBackup> {
+    let mut backups = load_save_backups(&app);
+    backups.reverse();
+    backups
+}
+
+#[tauri::command]
+async fn create_save_backup(app: AppHandle) -> Result<Option<SaveBackup>, String> {
+    backup_saves_off_thread(&app, "manual").await
+}
+
+#[tauri::command]
+async fn restore_save_backup(app: AppHandle, backup_id: String) -> Result<(), String> {
+    ensure_game_not_running(&app)?;
+    let backup = load_save_backups(&app)
+        .into_iter()
+        .find(|b| b.id == backup_id)
+        .ok_or("Backup not found")?;
+    let zip_path = get_save_backups_dir(&app)?.join(&backup.file_name);
+    let file = fs::File::open(&zip_path).map_err(|e| format!("Backup file missing: {}", e))?;
+    let archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    // Keep the current saves in case the restore was a mistake
+    backup_saves_off_thread(&app, "pre_restore").await?;
+
+    let save_dir = PathBuf::from(&backup.save_path);
+    tauri::async_runtime::spawn_blocking(move || restore_saves_from_archive(archive, &save_dir))
+        .await
+        .map_err(|e| format!("Save restore task failed: {}", e))??;
+
+    log_internal(
+        &app,
+        "INFO",
+        &format!(
+            "Restored saves from {} into {}",
+            backup.file_name, backup.save_path
+        ),
+    );
+    Ok(())
+}
+
+// Extracts next to the save folder and only swaps it in once the whole archive is out,
+// so a bad zip never leaves the player without their live saves
+fn restore_saves_from_archive(
+    mut archive: ZipArchive<fs::File>,
+    save_dir: &Path,
+) -> Result<(), String> {
+    let parent = save_dir
+        .parent()
+        .ok_or("Save folder has no parent directory")?;
+    let dir_name = save_dir
+        .file_name()
+        .ok_or("Save folder has no name")?
+        .to_string_lossy()
+        .to_string();
+    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let token = Uuid::new_v4();
+    let staging = parent.join(format!("{}.restore-{}", dir_name, token));
+    let old = parent.join(format!("{}.old-{}", dir_name, token));
+
+    if let Err(e) = archive.extract(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Could not extract save backup: {}", e));
+    }
+
+    let had_saves = save_dir.exists();
+    if had_saves {
+        if let Err(e) = fs::rename(save_dir, &old) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!("Could not move the current saves aside: {}", e));
+        }
+    }
+    if let Err(e) = fs::rename(&staging, save_dir) {
+        if had_saves {
+            let _ = fs::rename(&old, save_dir);
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!(
+            "Could not move the restored saves into place: {}",
+            e
+        ));
+    }
+    if had_saves {
+        let _ = fs::remove_dir_all(&old);
+    }
+    Ok(())
+}
+
+fn get_auth_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    if !app_data_dir.exists() {
+        fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(app_data_dir.join("auth.json"))
+}
+
+// --- DEPLOYMENT ROLLBACK ---
+
+// Lives next to MODS so moving folders aside is a rename on the same drive
+const ROLLBACK_DIR_NAME: &str = "MODS_ROLLBACK";
+const ROLLBACK_MANIFEST: &str = "rollback.json";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DeploymentSnapshot {
+    created_at: String,
+    profile: String,
+    // GCMODSETTINGS.MXML as it was; None when the game had no settings file
+    settings: Option<String>,
+}
+
+fn rollback_dir(game_path: &Path) -> PathBuf {
+    game_path.join("GAMEDATA").join(ROLLBACK_DIR_NAME)
+}
+
+fn live_settings_path(game_path: &Path) -> PathBuf {
+    game_path
+        .join("Binaries")
+        .join("SETTINGS")
+        .join("GCMODSETTINGS.MXML")
+}
+
+// Whether `file_path` names the live settings file, however the frontend spelled it
+fn is_live_settings_path(file_path: &Path, game_path: &Path) -> bool {
+    let canonical = |path: &Path| {
+        fs::canonicalize(path).ok().or_else(|| {
+            // Not written yet: resolve the folder and keep the file name
+            Some(
+                fs::canonicalize(path.parent()?)
+                    .ok()?
+                    .join(path.file_name()?),
+            )
+        })
+    };
+    match (
+        canonical(file_path),
+        canonical(&live_settings_path(game_path)),
+    ) {
+        (Some(a), Some(b)) => a == b,
+        _ => file_path == live_settings_path(game_path),
+    }
+}
+
+// The same entries a profile switch clears: mod folders and loose paks
+fn is_deployed_entry(path: &Path) -> bool {
+    path.is_dir() || path.extension().unwrap_or_default() == "pak"
+}
+
+fn remove_deployed_entries(mods_dir: &Path) -> Result<(), String> {
+    if !mods_dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(mods_dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        } else if is_deployed_entry(&path) {
+            fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+// Moves the deployed mods out of MODS and records the settings file, leaving MODS empty
+fn snapshot_deployment(game_path: &Path, profile_name: &str) -> Result<(), String> {
+    let mods_dir = game_path.join("GAMEDATA").join("MODS");
+    let backup_dir = rollback_dir(game_path);
+
+    // A leftover snapshot means the last switch never finished; its mods come back first
+    if backup_dir.exists() {
+        restore_deployment(game_path)?;
+    }
+    fs::create_dir_all(&mods_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+
+    // Written before anything moves so an interrupted snapshot can still be undone
+    let snapshot = DeploymentSnapshot {
+        created_at: Local::now().to_rfc3339(),
+        profile: profile_name.to_string(),
+        settings: fs::read_to_string(live_settings_path(game_path)).ok(),
+    };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    fs::write(backup_dir.join(ROLLBACK_MANIFEST), json).map_err(|e| e.to_string())?;
+
+    let entries = fs::read_dir(&mods_dir).map_err(|e| e.to_string())?;
+    for entry in entries.flatten() {
+        if !is_deployed_entry(&entry.path()) {
+            continue;
+        }
+        if let Err(e) = fs::rename(entry.path(), backup_dir.join(entry.file_name())) {
+            let moved_back = restore_deployment(game_path).err();
+            return Err(format!(
+                "Could not set aside {}: {}{}",
+                entry.file_name().to_string_lossy(),
+                e,
+                moved_back
+                    .map(|r| format!(" (restore failed: {})", r))
+                    .unwrap_or_default()
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Puts the snapshotted mods and settings back, replacing whatever was deployed since
+fn restore_deployment(game_path: &Path) -> Result<(), String> {
+    let mods_dir = game_path.join("GAMEDATA").join("MODS");
+    let backup_dir = rollback_dir(game_path);
+    if !backup_dir.exists() {
+        return Ok(());
+    }
+    let snapshot: Option<DeploymentSnapshot> =
+        fs::read_to_string(backup_dir.join(ROLLBACK_MANIFEST))
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok());
+
+    remove_deployed_entries(&mods_dir)?;
+    fs::create_dir_all(&mods_dir).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(&backup_dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+    {
+        if entry.file_name() == ROLLBACK_MANIFEST {
+            continue;
+        }
+        fs::rename(entry.path(), mods_dir.join(entry.file_name())).map_err(|e| {
+            format!(
+                "Could not restore {}: {}",
+                entry.file_name().to_string_lossy(),
+                e
+            )
+        })?;
+    }
+
+    // Without a readable manifest the current settings file is the best guess left
+    if let Some(snapshot) = snapshot {
+        let settings_path = live_settings_path(game_path);
+        match snapshot.settings {
+            Some(content) => fs::write(&settings_path, content).map_err(|e| e.to_string())?,
+            None => {
+                if settings_path.exists() {
+                    fs::remove_file(&settings_path).map_err(|e| e.to_string())?;
+                }
+            }
+        }
+    }
+    fs::remove_dir_all(&backup_dir).map_err(|e| e.to_string())
+}
+
+fn discard_deployment_snapshot(game_path: &Path) -> Result<(), String> {
+    let backup_dir = rollback_dir(game_path);
+    if backup_dir.exists() {
+        fs::remove_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Finishes the rollback of a profile switch that was cut off by a crash or forced close
+fn recover_interrupted_apply(app: &AppHandle) -> Result<(), String> {
+    // Nothing to recover before a game has been set up
+    let Ok(GameContext { game_path, .. }) = game_context(app) else {
+        return Ok(());
+    };
+    if !rollback_dir(&game_path).exists() {
+        return Ok(());
+    }
+    restore_deployment(&game_path)?;
+    log_internal(
+        app,
+        "WARN",
+        "Restored the mods of a profile switch that did not finish.",
+    );
+    Ok(())
+}
+
+// --- API KEY STORAGE ---
+
+const KEYRING_SERVICE: &str = "com.syzzle.Singularity";
+const KEYRING_USER: &str = "nexus-api-key";
+
+// Secrets that must never reach singularity.log
+static LOG_SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Key as last read or written; None until the first load
+#[derive(Default)]
+struct ApiKeyState {
+    cached: Mutex<Option<Option<String>>>,
+}
+
+// Fallback for systems without a secret store (e.g. headless Linux). The cipher key is derived
+// from values readable by anyone with access to this machine, so this only obfuscates the key.
+#[derive(Serialize, Deserialize)]
+struct EncryptedAuthFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn register_log_secret(secret: &str) {
+    if let Ok(mut secrets) = LOG_SECRETS.lock() {
+        if !secret.is_empty() && !secrets.iter().any(|s| s == secret) {
+            secrets.push(secret.to_string());
+        }
+    }
+}
+
+fn redact_log_secrets(message: &str) -> String {
+    let mut redacted = message.to_string();
+    if let Ok(secrets) = LOG_SECRETS.lock() {
+        for secret in secrets.iter() {
+            redacted = redacted.replace(secret.as_str(), "[REDACTED]");
+        }
+    }
+    redacted
+}
+
+// Secret Service calls block on their own runtime, so keep them off Tauri's async threads
+fn run_keyring_op<T: Send>(
+    op: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send,
+) -> keyring::Result<T> {
+    std::thread::scope(|s| {
+        s.spawn(|| op(&keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?))
+            .join()
+            .unwrap_or_else(|_| {
+                Err(keyring::Error::PlatformFailure(
+                    "Secure storage thread panicked".into(),
+                ))
+            })
+    })
+}
+
+fn get_encrypted_auth_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_auth_file_path(app)?.with_file_name("auth.enc"))
+}
+
+// Ties the fallback file to this machine and user account. Machine ID, user name and salt are
+// not secret, so this keeps the key out of plain sight but does not protect it.
+fn derive_auth_file_key(salt: &[u8]) -> Key {
+    let machine_id = fs::read_to_string("/etc/machine-id")
+        .or_else(|_| fs::read_to_string("/var/lib/dbus/machine-id"))
+        .unwrap_or_default();
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"singularity-auth-v1");
+    hasher.update(machine_id.trim().as_bytes());
+    hasher.update(user.as_bytes());
+    hasher.update(salt);
+    let digest: [u8; 32] = hasher.finalize().into();
+    Key::from(digest)
+}
+
+fn write_encrypted_api_key(path: &Path, api_key: &str) -> Result<(), String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = ChaCha20Poly1305::new(&derive_auth_file_key(&salt));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, api_key.as_bytes())
+        .map_err(|_| "Failed to encrypt API key".to_string())?;
+
+    let file = EncryptedAuthFile {
+        version: 1,
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to save encrypted auth file: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+fn read_encrypted_api_key(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: EncryptedAuthFile =
+        serde_json::from_str(&content).map_err(|_| "Invalid encrypted auth file".to_string())?;
+
+    let decode = |v: &str| {
+        general_purpose::STANDARD
+            .decode(v)
+            .map_err(|_| "Invalid encrypted auth file".to_string())
+    };
+    let salt = decode(&file.salt)?;
+    let nonce = decode(&file.nonce)?;
+    let ciphertext = decode(&file.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err("Invalid encrypted auth file".to_string());
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_auth_file_key(&salt));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "Stored API key could not be decrypted. Please log in again.".to_string())?;
+
+    String::from_utf8(plaintext)
+        .map(Some)
+        .map_err(|_| "Invalid encrypted auth file".to_string())
+}
+
+fn set_cached_api_key(app: &AppHandle, key: Option<String>) {
+    *app.state::<ApiKeyState>().cached.lock().unwrap() = Some(key);
+}
+
+// Returns false when the key only went to the obfuscated fallback file
+fn store_api_key(app: &AppHandle, api_key: &str) -> Result<bool, String> {
+    register_log_secret(api_key);
+    let encrypted_path = get_encrypted_auth_file_path(app)?;
+
+    let in_secret_store = match run_keyring_op(|entry| entry.set_password(api_key)) {
+        Ok(()) => {
+            log_internal(
+                app,
+                "INFO",
+                "Nexus API key saved to the system secret store.",
+            );
+            if encrypted_path.exists() {
+                let _ = fs::remove_file(&encrypted_path);
+            }
+            true
+        }
+        Err(e) => {
+            log_internal(
+                app,
+                "WARN",
+                &format!(
+                    "System secret store unavailable ({}). Saving the key to an obfuscated file, which is not secure storage.",
+                    e
+                ),
+            );
+            write_encrypted_api_key(&encrypted_path, api_key)?;
+            false
+        }
+    };
+    set_cached_api_key(app, Some(api_key.to_string()));
+
+    // Never leave a plaintext copy behind
+    let auth_path = get_auth_file_path(app)?;
+    if auth_path.exists() {
+        fs::remove_file(&auth_path).map_err(|e| e.to_string())?;
+    }
+    Ok(in_secret_store)
+}
+
+// Moves keys saved by older versions out of the plaintext auth.json
+fn migrate_plaintext_auth_file(app: &AppHandle) -> Result<(), String> {
+    let auth_path = get_auth_file_path(app)?;
+    if !auth_path.exists() {
+        return Ok(());
+    }
+
+    let legacy_key = fs::read_to_string(&auth_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|json| {
+            json.get("apikey")
+                .and_then(|k| k.as_str())
+                .map(String::from)
+        });
+
+    match legacy_key {
+        Some(key) => {
+            store_api_key(app, &key)?;
+            log_internal(
+                app,
+                "INFO",
+                "Migrated Nexus API key from auth.json to secure storage.",
+            );
+        }
+        None => {
+            log_internal(app, "WARN", "Removing unreadable legacy auth.json.");
+            fs::remove_file(&auth_path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn load_api_key(app: &AppHandle) -> Result<Option<String>, String> {
+    if let Some(key) = app.state::<ApiKeyState>().cached.lock().unwrap().clone() {
+        return Ok(key);
     }
+    migrate_plaintext_auth_file(app)?;
 
-    // CASE C: Fallback
-    if folder_entries.len() > 1 {
-        let folder_names: Vec<String> = folder_entries
-            .iter()
-            .map(|e| e.file_name().to_string_lossy().into_owned())
-            .collect();
+    match run_keyring_op(|entry| entry.get_password()) {
+        Ok(key) => {
+            register_log_secret(&key);
+            set_cached_api_key(app, Some(key.clone()));
+            return Ok(Some(key));
+        }
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => log_internal(
+            app,
+            "WARN",
+            &format!("Could not read the system secret store: {}", e),
+        ),
+    }
 
-        emit_progress("Waiting for selection...");
-        return Ok(InstallationAnalysis {
-            successes: vec![],
-            conflicts: vec![],
-            messy_archive_path: None,
-            active_archive_path: Some(final_archive_path_str),
-            selection_needed: true,
-            temp_id: Some(library_id),
-            available_folders: Some(folder_names),
-        });
+    let key = read_encrypted_api_key(&get_encrypted_auth_file_path(app)?)?;
+    if let Some(key) = &key {
+        register_log_secret(key);
     }
+    set_cached_api_key(app, key.clone());
+    Ok(key)
+}
 
-    // CASE D: Install All
-    emit_progress("Finalizing...");
-    let mut analysis = finalize_installation(app, library_id, vec![], false)?;
-    analysis.active_archive_path = Some(final_archive_path_str);
+fn delete_api_key(app: &AppHandle) -> Result<(), String> {
+    set_cached_api_key(app, None);
+    match run_keyring_op(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => log_internal(
+            app,
+            "WARN",
+            &format!("Could not remove key from the system secret store: {}", e),
+        ),
+    }
 
-    Ok(analysis)
+    for path in [get_encrypted_auth_file_path(app)?, get_auth_file_path(app)?] {
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
 }
 
-#[tauri::command]
-fn finalize_installation(
-    app: AppHandle,
-    library_id: String,
-    selected_folders: Vec<String>,
-    flatten_paths: bool
-) -> Result<InstallationAnalysis, String> {
-    log_internal(&app, "INFO", &format!("Finalizing installation. Source: {}, Flatten: {}", library_id, flatten_paths));
+// --- PROFILE MANAGEMENT ---
 
-    let game_path = find_game_path().ok_or_else(|| "Could not find game path.".to_string())?;
-    let mods_path = game_path.join("GAMEDATA").join("MODS");
-    fs::create_dir_all(&mods_path).map_err(|e| e.to_string())?;
+fn get_profiles_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let root = get_singularity_root(app)?;
+    let profiles = root.join("profiles");
+    if !profiles.exists() {
+        fs::create_dir_all(&profiles).map_err(|e| e.to_string())?;
+    }
+    Ok(profiles)
+}
 
-    let library_dir = get_library_dir(&app)?;
-    let source_root = library_dir.join(&library_id);
+const MAX_PROFILE_NAME_LEN: usize = 64;
+const PROFILE_NAME_FORBIDDEN: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// Checks a name for a new profile; returns it trimmed, as it will be stored. The rules
+// are the strictest of the platforms so profiles stay portable.
+fn validate_profile_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty.".to_string());
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile names can be at most {} characters long.",
+            MAX_PROFILE_NAME_LEN
+        ));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || PROFILE_NAME_FORBIDDEN.contains(c))
+    {
+        return Err(format!(
+            "Profile names cannot contain '{}'.",
+            c.escape_default()
+        ));
+    }
+    if name.starts_with('.') || name.ends_with('.') {
+        return Err("Profile names cannot start or end with a dot.".to_string());
+    }
+    let stem = name.split('.').next().unwrap_or_default().trim();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(stem))
+    {
+        return Err(format!("'{}' is reserved by the system.", name));
+    }
+    Ok(name.to_string())
+}
 
-    if !source_root.exists() {
-        let err = format!("Library folder missing: {:?}", source_root);
-        log_internal(&app, "ERROR", &err);
-        return Err(err);
+// Makes a usable profile name out of outside text (collection or package names);
+// empty when nothing usable is left
+fn sanitize_profile_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || PROFILE_NAME_FORBIDDEN.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let truncated: String = replaced
+        .trim()
+        .trim_matches('.')
+        .chars()
+        .take(MAX_PROFILE_NAME_LEN - 1)
+        .collect();
+    let cleaned = truncated.trim().trim_end_matches('.').to_string();
+    if cleaned.is_empty() || validate_profile_name(&cleaned).is_ok() {
+        cleaned
+    } else {
+        // Only reserved device names get here
+        format!("{}_", cleaned)
     }
+}
 
-    // Load existing mods for conflict checking
-    let mut installed_mods_by_id: HashMap<String, String> = HashMap::new();
-    if let Ok(entries) = fs::read_dir(&mods_path) {
-        for entry in entries.filter_map(Result::ok) {
-            if let Some(info) = read_mod_info(&entry.path()) {
-                if let (Some(mod_id), Some(folder_name)) = (info.mod_id, entry.path().file_name().and_then(|n| n.to_str())) {
-                    installed_mods_by_id.insert(mod_id, folder_name.to_string());
+// Path of an existing profile's JSON. Lookups only need to stay inside the profiles
+// folder; older profiles may predate the stricter rules for new names.
+fn profile_json_path(app: &AppHandle, profile_name: &str) -> Result<PathBuf, String> {
+    let file_name = format!("{}.json", profile_name);
+    if profile_name.trim().is_empty() || !is_plain_file_name(&file_name) {
+        return Err(format!("Invalid profile name: '{}'", profile_name));
+    }
+    Ok(get_profiles_dir(app)?.join(file_name))
+}
+
+// Validates a new profile name and makes sure no other profile already uses it.
+// File systems may ignore case, so neither does the check; `renaming` is allowed to
+// keep its own name with different casing.
+fn claim_profile_name(
+    app: &AppHandle,
+    new_name: &str,
+    renaming: Option<&str>,
+) -> Result<String, String> {
+    let name = validate_profile_name(new_name)?;
+    let taken = profile_names(app)?.into_iter().find(|existing| {
+        existing.eq_ignore_ascii_case(&name)
+            && !renaming.is_some_and(|old| old.eq_ignore_ascii_case(existing))
+    });
+    match taken {
+        Some(existing) => Err(format!("A profile named '{}' already exists.", existing)),
+        None => Ok(name),
+    }
+}
+
+// Writes a profile file that must not exist yet. claim_profile_name can race another
+// command, so the file is only ever created, never overwritten.
+fn create_profile_file(json_path: &Path, content: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(json_path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => format!(
+                "A profile named '{}' already exists.",
+                json_path.file_stem().unwrap_or_default().to_string_lossy()
+            ),
+            _ => e.to_string(),
+        })?;
+    file.write_all(content.as_bytes()).map_err(|e| {
+        fs::remove_file(json_path).ok();
+        e.to_string()
+    })
+}
+
+#[derive(Serialize, Debug)]
+struct ProfileListEntry {
+    // File name without .json; what every profile command takes
+    name: String,
+    // Name recorded inside the profile, when it differs from the file name
+    display_name: Option<String>,
+}
+
+// Load order of one deployed folder, as stored in the profile JSON
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+struct ProfileFolderState {
+    name: String,
+    priority: u32,
+    enabled: bool,
+    // The rest of the folder's GCMODSETTINGS entry (Author, ID, Dependencies, ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+}
+
+// Folder name (upper-cased) -> state, read from GCMODSETTINGS data
+fn folder_states_from_settings(root: &SettingsData) -> Vec<ProfileFolderState> {
+    mod_entries(root)
+        .enumerate()
+        .map(|(i, entry)| ProfileFolderState {
+            name: mod_property_value(entry, "Name"),
+            priority: mod_property_value(entry, "ModPriority")
+                .parse()
+                .unwrap_or(i as u32),
+            enabled: mod_property_value(entry, "Enabled").eq_ignore_ascii_case("true"),
+            metadata: mod_entry_metadata(entry),
+        })
+        .collect()
+}
+
+fn settings_disable_all(root: &SettingsData) -> bool {
+    root.properties
+        .iter()
+        .find(|p| p.name == "DisableAllMods")
+        .and_then(|p| p.value.as_deref())
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+// Attaches the load order from a legacy <profile>.mxml sidecar to the profile's
+// entries; folders no entry claims yet are kept as pending
+fn migrate_profile_sidecar(profile: &mut ModProfileData, sidecar: &SettingsData) {
+    profile.disable_all_mods = settings_disable_all(sidecar);
+    for state in folder_states_from_settings(sidecar) {
+        let owner = profile.mods.iter_mut().find(|entry| {
+            entry
+                .installed_options
+                .as_ref()
+                .is_some_and(|folders| folders.iter().any(|f| f.eq_ignore_ascii_case(&state.name)))
+        });
+        match owner {
+            Some(entry) => entry.folders.push(state),
+            None => profile.pending_load_order.push(state),
+        }
+    }
+}
+
+// Runs at startup; each sidecar is kept as <profile>.mxml.bak after migration
+fn migrate_profile_sidecars(app: &AppHandle) -> Result<(), String> {
+    let dir = get_profiles_dir(app)?;
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        let sidecar_path = entry.path();
+        if sidecar_path.extension().and_then(|e| e.to_str()) != Some("mxml") {
+            continue;
+        }
+        let json_path = sidecar_path.with_extension("json");
+        if !json_path.exists() {
+            continue;
+        }
+        let result = (|| -> Result<(), String> {
+            let content = fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
+            let mut profile: ModProfileData =
+                serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            let sidecar: SettingsData =
+                from_str(&fs::read_to_string(&sidecar_path).map_err(|e| e.to_string())?)
+                    .map_err(|e| e.to_string())?;
+            migrate_profile_sidecar(&mut profile, &sidecar);
+
+            let json_str = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+            fs::write(&json_path, json_str).map_err(|e| e.to_string())?;
+            fs::rename(&sidecar_path, sidecar_path.with_extension("mxml.bak"))
+                .map_err(|e| e.to_string())
+        })();
+        match result {
+            Ok(()) => log_internal(
+                app,
+                "INFO",
+                &format!(
+                    "Migrated load order of {} into its profile JSON",
+                    sidecar_path.display()
+                ),
+            ),
+            Err(e) => log_internal(
+                app,
+                "WARN",
+                &format!("Could not migrate {}: {}", sidecar_path.display(), e),
+            ),
+        }
+    }
+    Ok(())
+}
+
+// Builds GCMODSETTINGS for the folders deployed by apply_profile. `deployed` pairs
+// each folder with the index of the profile entry it came from. Folders without a
+// stored state are enabled and go last, in deploy order. Entry metadata comes from
+// the profile, or from `previous` (the settings being replaced) for profiles saved
+// before it was stored.
+fn build_profile_settings(
+    profile: &ModProfileData,
+    deployed: &[(usize, String)],
+    previous: Option<&SettingsData>,
+) -> Result<SettingsData, String> {
+    let previous_metadata = |folder: &str| {
+        previous
+            .into_iter()
+            .flat_map(mod_entries)
+            .find(|entry| mod_property_value(entry, "Name").eq_ignore_ascii_case(folder))
+            .map(mod_entry_metadata)
+            .unwrap_or_default()
+    };
+    // (stored priority, state to write); the state's name is the deployed folder
+    let mut ordered: Vec<(Option<u32>, ProfileFolderState)> = deployed
+        .iter()
+        .map(|(entry_idx, folder)| {
+            let state = profile.mods[*entry_idx]
+                .folders
+                .iter()
+                .chain(profile.pending_load_order.iter())
+                .find(|s| s.name.eq_ignore_ascii_case(folder));
+            let metadata = match state {
+                Some(s) if !s.metadata.is_empty() => s.metadata.clone(),
+                _ => previous_metadata(folder),
+            };
+            let written = ProfileFolderState {
+                name: folder.clone(),
+                priority: 0,
+                enabled: state.is_none_or(|s| s.enabled),
+                metadata,
+            };
+            (state.map(|s| s.priority), written)
+        })
+        .collect();
+    // Stable sort keeps deploy order among equal or missing priorities
+    ordered.sort_by_key(|(priority, _)| priority.unwrap_or(u32::MAX));
+
+    let mut root: SettingsData = from_str(CLEAN_MXML_TEMPLATE).map_err(|e| e.to_string())?;
+    for prop in root.properties.iter_mut() {
+        if prop.name == "DisableAllMods" {
+            prop.value = Some(profile.disable_all_mods.to_string());
+        }
+        if prop.name == "Data" {
+            prop.mods = ordered
+                .iter()
+                .enumerate()
+                .map(|(i, (_, state))| {
+                    build_mod_entry(i, &state.name, state.enabled, &state.metadata)
+                })
+                .collect();
+        }
+    }
+    Ok(root)
+}
+
+#[tauri::command]
+fn list_profiles(app: AppHandle) -> Result<Vec<ProfileListEntry>, String> {
+    Ok(profile_names(&app)?
+        .into_iter()
+        .map(|name| {
+            let display_name = profile_json_path(&app, &name)
+                .ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|c| serde_json::from_str::<ModProfileData>(&c).ok())
+                .map(|data| data.name)
+                .filter(|stored| !stored.is_empty() && *stored != name);
+            ProfileListEntry { name, display_name }
+        })
+        .collect())
+}
+
+// Profile names as stored on disk, Default first
+fn profile_names(app: &AppHandle) -> Result<Vec<String>, String> {
+    let dir = get_profiles_dir(app)?;
+    let mut profiles = Vec::new();
+
+    // 1. Always ensure "Default" is first in the list
+    profiles.push("Default".to_string());
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.path().file_stem() {
+                let name_str = name.to_string_lossy().into_owned();
+                // 2. Filter out "Default" here so it doesn't appear twice
+                if name_str != "Default" && entry.path().extension().unwrap_or_default() == "json" {
+                    profiles.push(name_str);
                 }
             }
         }
     }
+    let mut others: Vec<String> = profiles.drain(1..).collect();
+    others.sort();
+    profiles.extend(others);
 
-    let staging_dir = get_staging_dir(&app)?;
-    let conflict_staging_path = staging_dir.join(format!("conflict_{}", Utc::now().timestamp_millis()));
-
-    let mut successes = Vec::new();
-    let mut conflicts = Vec::new();
+    Ok(profiles)
+}
 
-    // If selected_folders is empty (or just contained "."), fallback to all top-level
-    let items_to_process = if selected_folders.is_empty() || (selected_folders.len() == 1 && selected_folders[0] == ".") {
-        log_internal(&app, "INFO", "No specific folders selected. Scanning all top-level folders.");
-        fs::read_dir(&source_root)
-            .map_err(|e| e.to_string())?
-            .filter_map(Result::ok)
-            .filter(|e| e.path().is_dir())
-            .map(|e| e.file_name().to_string_lossy().into_owned())
-            .collect::<Vec<String>>()
-    } else {
-        log_internal(&app, "INFO", &format!("Selected folders to install: {:?}", selected_folders));
-        selected_folders
-    };
+fn check_profile_installation(
+    app: &AppHandle,
+    profile_name: &str,
+    installation_id: &str,
+) -> Result<(), String> {
+    let active = game_context(app)?.installation;
+    if active.id == installation_id {
+        return Ok(());
+    }
+    let config = load_app_config(app);
+    let tied = config
+        .game_installations
+        .iter()
+        .find(|i| i.id == installation_id)
+        .map(|i| format!("{} ({})", i.path, i.version_type))
+        .unwrap_or_else(|| "an installation that is no longer registered".to_string());
+    Err(format!(
+        "Profile '{}' belongs to {}. Switch to that installation first.",
+        profile_name, tied
+    ))
+}
 
-    struct DeployOp {
-        source: PathBuf,
-        dest_name: String,
+// Ties a profile to an installation, or clears the tie when `installation_id` is None
+#[tauri::command]
+fn set_profile_installation(
+    app: AppHandle,
+    profile_name: String,
+    installation_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &installation_id {
+        let config = load_app_config(&app);
+        if !config.game_installations.iter().any(|i| &i.id == id) {
+            return Err(format!("Unknown installation: {}", id));
+        }
     }
-    let mut ops: Vec<DeployOp> = Vec::new();
 
-    for relative_path_str in items_to_process {
-        // Handle the dot "." explicitly if it still sneaks in
-        let source_path = if relative_path_str == "." {
-            source_root.clone()
-        } else {
-            source_root.join(&relative_path_str)
-        };
+    let json_path = profile_json_path(&app, &profile_name)?;
+    let content = fs::read_to_string(&json_path).map_err(|_| "Profile not found".to_string())?;
+    let mut profile: ModProfileData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    profile.installation_id = installation_id;
 
-        if !source_path.exists() { continue; }
+    let json_str = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json_str).map_err(|e| e.to_string())
+}
 
-        if flatten_paths {
-            // "Smart Extract": Look deeper for the actual mod root
-            let deep_candidates = scan_for_installable_mods(&source_path, &source_path);
+// Builds profile data from what is deployed in the game folder right now: mods are
+// grouped by the archive recorded in their mod_info.json
+fn snapshot_live_profile(game_path: Option<&Path>, profile_name: &str) -> ModProfileData {
+    // Map: ZipFilename -> List of Installed Folder Names
+    let mut profile_map: HashMap<String, Vec<String>> = HashMap::new();
 
-            if !deep_candidates.is_empty() {
-                for deep_rel in deep_candidates {
-                    let deep_source = if deep_rel == "." { source_path.clone() } else { source_path.join(&deep_rel) };
-                    let folder_name = deep_source.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+    let mut live_states: Vec<ProfileFolderState> = Vec::new();
+    let mut disable_all_mods = false;
 
-                    // --- DEDUPLICATION CHECK ---
-                    if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
-                        ops.push(DeployOp { source: deep_source, dest_name: folder_name });
-                    }
-                }
-            } else {
-                // No deep structure found, use current
-                let folder_name = source_path.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+    if let Some(game_path) = game_path {
+        let mods_path = game_path.join("GAMEDATA").join("MODS");
+        if let Ok(entries) = fs::read_dir(mods_path) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    let folder_name = entry.file_name().to_string_lossy().into_owned();
+                    let info_path = entry.path().join("mod_info.json");
 
-                // --- DEDUPLICATION CHECK ---
-                if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
-                    ops.push(DeployOp { source: source_path, dest_name: folder_name });
+                    if let Ok(content) = fs::read_to_string(&info_path) {
+                        if let Ok(json) = serde_json::from_str::<Value>(&content) {
+                            if let Some(source) = json.get("installSource").and_then(|s| s.as_str())
+                            {
+                                if !source.is_empty() {
+                                    profile_map
+                                        .entry(source.to_string())
+                                        .or_default()
+                                        .push(folder_name);
+                                }
+                            }
+                        }
+                    }
                 }
             }
-        } else {
-            // Exact Install: Use the folder structure exactly as selected
-            let folder_name = source_path.file_name().ok_or("Invalid path")?.to_string_lossy().into_owned();
+        }
 
-            // --- DEDUPLICATION CHECK ---
-            if !ops.iter().any(|op| op.dest_name.eq_ignore_ascii_case(&folder_name)) {
-                ops.push(DeployOp { source: source_path, dest_name: folder_name });
+        // Snapshot load order and enabled flags
+        let current_mxml = game_path
+            .join("Binaries")
+            .join("SETTINGS")
+            .join("GCMODSETTINGS.MXML");
+        if let Ok(content) = fs::read_to_string(current_mxml) {
+            if let Ok(root) = from_str::<SettingsData>(&content) {
+                live_states = folder_states_from_settings(&root);
+                disable_all_mods = settings_disable_all(&root);
             }
         }
     }
 
-    for op in ops {
-        let mut conflict_found = false;
-
-        if let Some(info) = read_mod_info(&op.source) {
-            if let Some(mod_id) = info.mod_id {
-                if let Some(old_folder_name) = installed_mods_by_id.get(&mod_id) {
-                    if !conflict_staging_path.exists() { fs::create_dir_all(&conflict_staging_path).map_err(|e| e.to_string())?; }
+    // Convert Map to ProfileModEntry list
+    let mut profile_entries = Vec::new();
+    for (filename, installed_folders) in profile_map {
+        // --- NO HASHING HERE ANYMORE ---
+        // We simply trust the filename linkage.
 
-                    let staged_mod_path = conflict_staging_path.join(&op.dest_name);
+        // Sample metadata from first folder
+        let mut p_mod_id = None;
+        let mut p_file_id = None;
+        let mut p_version = None;
+        let mut p_game_build = None;
 
-                    if let Err(e) = deploy_structure_recursive(&op.source, &staged_mod_path) {
-                        log_internal(&app, "ERROR", &format!("Failed to stage conflict: {}", e));
+        if let Some(first_folder) = installed_folders.first() {
+            if let Some(gp) = game_path {
+                let info_p = gp
+                    .join("GAMEDATA/MODS")
+                    .join(first_folder)
+                    .join("mod_info.json");
+                if let Ok(c) = fs::read_to_string(info_p) {
+                    if let Ok(j) = serde_json::from_str::<Value>(&c) {
+                        p_mod_id = j.get("modId").and_then(|s| s.as_str()).map(String::from);
+                        p_file_id = j.get("fileId").and_then(|s| s.as_str()).map(String::from);
+                        p_version = j.get("version").and_then(|s| s.as_str()).map(String::from);
+                        p_game_build = j
+                            .get("gameBuild")
+                            .and_then(|s| s.as_str())
+                            .map(String::from);
                     }
-
-                    conflicts.push(ModConflictInfo {
-                        new_mod_name: op.dest_name.clone(),
-                        temp_path: staged_mod_path.to_string_lossy().into_owned(),
-                        old_mod_folder_name: old_folder_name.clone(),
-                    });
-                    conflict_found = true;
                 }
             }
         }
 
-        if !conflict_found {
-            let final_dest_path = mods_path.join(&op.dest_name);
-            log_internal(&app, "INFO", &format!("Deploying mod folder: {}", op.dest_name));
-
-            if final_dest_path.exists() {
-                if !conflict_staging_path.exists() { fs::create_dir_all(&conflict_staging_path).map_err(|e| e.to_string())?; }
-                let staged_mod_path = conflict_staging_path.join(&op.dest_name);
-
-                if let Err(e) = deploy_structure_recursive(&op.source, &staged_mod_path) {
-                    log_internal(&app, "ERROR", &format!("Failed to stage overwrite: {}", e));
-                }
+        // Folders the game hasn't registered yet load last, enabled
+        let mut folders: Vec<ProfileFolderState> = installed_folders
+            .iter()
+            .map(|folder| {
+                live_states
+                    .iter()
+                    .find(|s| s.name.eq_ignore_ascii_case(folder))
+                    .map(|s| ProfileFolderState {
+                        name: folder.clone(),
+                        ..s.clone()
+                    })
+                    .unwrap_or(ProfileFolderState {
+                        name: folder.clone(),
+                        priority: u32::MAX,
+                        enabled: true,
+                        metadata: BTreeMap::new(),
+                    })
+            })
+            .collect();
+        folders.sort_by_key(|s| s.priority);
 
-                conflicts.push(ModConflictInfo {
-                    new_mod_name: op.dest_name.clone(),
-                    temp_path: staged_mod_path.to_string_lossy().into_owned(),
-                    old_mod_folder_name: op.dest_name.clone(),
-                });
-            } else {
-                if let Err(e) = deploy_structure_recursive(&op.source, &final_dest_path) {
-                    let err_msg = format!("Failed to deploy {}: {}", op.dest_name, e);
-                    log_internal(&app, "ERROR", &err_msg);
-                    return Err(err_msg);
-                }
+        profile_entries.push(ProfileModEntry {
+            filename,
+            // hash: ... REMOVED
+            mod_id: p_mod_id,
+            file_id: p_file_id,
+            version: p_version,
+            installed_options: Some(installed_folders),
+            folders,
+            game_build: p_game_build,
+        });
+    }
+    // Stable order keeps profile files diffable
+    profile_entries.sort_by(|a, b| a.filename.cmp(&b.filename));
 
-                successes.push(ModInstallInfo {
-                    name: op.dest_name,
-                    temp_path: final_dest_path.to_string_lossy().into_owned(),
-                });
-            }
-        }
+    ModProfileData {
+        name: profile_name.to_string(),
+        mods: profile_entries,
+        disable_all_mods,
+        ..Default::default()
     }
+}
 
-    log_internal(&app, "INFO", "Installation finalization complete.");
+#[tauri::command]
+fn save_active_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
+    let _guard = PROFILE_SYNC_LOCK.lock().map_err(|e| e.to_string())?;
+    let saved = write_profile_snapshot(&app, &profile_name)?;
 
-    Ok(InstallationAnalysis {
-        successes,
-        conflicts,
-        messy_archive_path: None,
-        active_archive_path: None,
-        selection_needed: false,
-        temp_id: None,
-        available_folders: None,
+    update_app_config(&app, |config| {
+        // Older setups only tracked the active profile in the frontend
+        if config.active_profile.is_none() {
+            config.active_profile = Some(profile_name.clone());
+        }
+        if saved && config.active_profile.as_deref() == Some(profile_name.as_str()) {
+            config.active_profile_dirty = false;
+        }
     })
 }
 
-#[tauri::command]
-fn resolve_conflict(
-    new_mod_name: String,
-    old_mod_folder_name: String,
-    temp_mod_path_str: String,
-    replace: bool,
-) -> Result<(), String> {
-    let game_path = find_game_path().ok_or_else(|| "Could not find game path.".to_string())?;
-    let mods_path = game_path.join("GAMEDATA").join("MODS");
-    let old_mod_path = mods_path.join(&old_mod_folder_name);
-    let final_new_mod_path = mods_path.join(&new_mod_name);
-    let temp_mod_path = PathBuf::from(&temp_mod_path_str);
+// Returns false when the save was skipped rather than written
+fn write_profile_snapshot(app: &AppHandle, profile_name: &str) -> Result<bool, String> {
+    // The live settings only hold a test subset during a crash search
+    if load_bisect_session(app).is_some() {
+        log_internal(
+            app,
+            "INFO",
+            "Skipped profile save while a crash search is running.",
+        );
+        return Ok(false);
+    }
+    let json_path = profile_json_path(app, profile_name)?;
 
-    if replace {
-        if old_mod_path.exists() {
-            fs::remove_dir_all(&old_mod_path)
-                .map_err(|e| format!("Failed to remove old mod: {}", e))?;
-        }
-        // CHANGE: Use safe move (temp is in AppData, destination is Game folder)
-        move_dir_safely(&temp_mod_path, &final_new_mod_path)?;
-    } else {
-        // Just delete the staged folder in AppData
-        fs::remove_dir_all(&temp_mod_path)
-            .map_err(|e| format!("Failed to cleanup temp mod folder: {}", e))?;
+    // Keep the installation tie, and never snapshot another installation's mods into it
+    let previous = fs::read_to_string(&json_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<ModProfileData>(&c).ok())
+        .unwrap_or_default();
+    let installation_id = previous.installation_id;
+    if let Some(tied_id) = &installation_id {
+        check_profile_installation(app, profile_name, tied_id)?;
     }
 
-    // Clean up parent conflict container if empty
-    if let Some(parent) = temp_mod_path.parent() {
-        if parent.exists() && parent.read_dir().map_or(false, |mut i| i.next().is_none()) {
-            let _ = fs::remove_dir(parent);
+    // Without the game there's nothing live to snapshot, and an empty one would wipe the profile
+    let game_path = match game_context(app) {
+        Ok(ctx) => ctx.game_path,
+        Err(e) => {
+            log_internal(
+                app,
+                "WARN",
+                &format!("Skipped profile save, game not found: {}", e),
+            );
+            return Ok(false);
+        }
+    };
+    let mut data = snapshot_live_profile(Some(&game_path), profile_name);
+    data.installation_id = installation_id;
+
+    // The active profile follows the live copies of its settings files
+    let (settings_files, problems) = capture_settings_files(&game_path, &previous.settings_files);
+    log_settings_capture_problems(app, &problems);
+    data.settings_files = settings_files;
+
+    // Entries the last apply skipped aren't live, but they still belong to the profile
+    let config = load_app_config(app);
+    if config.active_profile.as_deref() == Some(profile_name)
+        && !config.undeployed_entries.is_empty()
+    {
+        let stored_mods = match &previous.parent {
+            Some(_) => resolve_profile(app, profile_name)?.mods,
+            None => previous.mods,
+        };
+        let is_live = |filename: &str| {
+            data.mods
+                .iter()
+                .any(|e| e.filename.eq_ignore_ascii_case(filename))
+        };
+        let still_undeployed: Vec<String> = config
+            .undeployed_entries
+            .iter()
+            .filter(|filename| !is_live(filename))
+            .cloned()
+            .collect();
+        let kept: Vec<ProfileModEntry> = stored_mods
+            .into_iter()
+            .filter(|entry| {
+                still_undeployed
+                    .iter()
+                    .any(|f| f.eq_ignore_ascii_case(&entry.filename))
+            })
+            .collect();
+        data.mods.extend(kept);
+        if still_undeployed != config.undeployed_entries {
+            update_app_config(app, |config| config.undeployed_entries = still_undeployed)?;
         }
     }
-    Ok(())
-}
 
-#[tauri::command]
-fn delete_settings_file() -> Result<String, String> {
-    if let Some(game_path) = find_game_path() {
-        let settings_file = game_path
-            .join("Binaries")
-            .join("SETTINGS")
-            .join("GCMODSETTINGS.MXML");
-        if settings_file.exists() {
-            fs::remove_file(&settings_file).map_err(|e| {
-                format!(
-                    "Failed to delete file at '{}': {}",
-                    settings_file.display(),
-                    e
-                )
-            })?;
-            Ok("alertDeleteSuccess".to_string())
-        } else {
-            Ok("alertDeleteNotFound".to_string())
-        }
-    } else {
-        Err("alertDeleteError".to_string())
+    // Pending collection order stays until its folders are installed
+    data.pending_load_order = previous.pending_load_order;
+    data.pending_load_order.retain(|state| {
+        !data
+            .mods
+            .iter()
+            .flat_map(|e| e.folders.iter())
+            .any(|f| f.name.eq_ignore_ascii_case(&state.name))
+    });
+
+    // A layered profile only stores what differs from its parent
+    if let Some(parent) = previous.parent {
+        let base = resolve_profile(app, &parent)?;
+        data = ModProfileData {
+            parent: Some(parent),
+            ..layer_over_parent(&base, data)
+        };
     }
-}
 
-#[tauri::command]
-fn detect_game_installation(app: AppHandle) -> Option<GamePaths> {
-    log_internal(&app, "INFO", "Starting Game Detection...");
+    let json_str = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json_str).map_err(|e| e.to_string())?;
 
-    if let Some(path) = find_game_path() {
-        let settings_dir = path.join("Binaries").join("SETTINGS");
+    Ok(true)
+}
 
-        if settings_dir.exists() {
-            log_internal(&app, "INFO", &format!("Found game path: {:?}", path));
+#[derive(Serialize, Debug)]
+struct ProfileSyncStatus {
+    active_profile: Option<String>,
+    dirty: bool,
+}
 
-            // Grant frontend filesystem access to the game directory
-            if let Err(e) = app.fs_scope().allow_directory(&path, true) {
-                log_internal(&app, "WARN", &format!("Failed to expand fs scope for game path: {}", e));
-            } else {
-                log_internal(&app, "INFO", &format!("Expanded fs scope for game path: {:?}", path));
-            }
+// Held while a profile snapshot is written or a switch replaces the MODS folder
+static PROFILE_SYNC_LOCK: Mutex<()> = Mutex::new(());
 
-            // Determine "Version Type" based on OS
-            #[cfg(target_os = "windows")]
-            let v_type = if path.to_string_lossy().contains("Xbox") {
-                "GamePass"
-            } else if path.to_string_lossy().contains("GOG") {
-                "GOG"
-            } else {
-                "Steam"
-            };
+// A skipped or failed save leaves the live changes outside the profile
+fn profile_dirty_after_sync(outcome: &Result<bool, String>) -> bool {
+    !matches!(outcome, Ok(true))
+}
 
-            #[cfg(target_os = "linux")]
-            let v_type = "Steam";
+// Switching away from a dirty profile drops its live changes unless the user agreed to
+fn check_unsaved_switch(sync: &ProfileSyncStatus, discard_unsaved: bool) -> Result<(), String> {
+    if sync.dirty && !discard_unsaved {
+        return Err(format!(
+            "Profile '{}' has changes that could not be saved. Save it or confirm discarding them first.",
+            sync.active_profile.as_deref().unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
 
-            return Some(GamePaths {
-                game_root_path: path.to_string_lossy().into_owned(),
-                settings_root_path: path.to_string_lossy().into_owned(),
-                version_type: v_type.to_string(),
-            });
-        }
+// Saves live changes into the active profile; when that fails the profile is flagged
+// dirty so switching away from it can warn before the changes are wiped
+fn sync_active_profile(app: &AppHandle) {
+    let Ok(_guard) = PROFILE_SYNC_LOCK.lock() else {
+        return;
+    };
+    let Some(active) = load_app_config(app).active_profile else {
+        return;
+    };
+    let outcome = write_profile_snapshot(app, &active);
+    if let Err(e) = &outcome {
+        log_internal(
+            app,
+            "WARN",
+            &format!(
+                "Could not update profile '{}' with live changes: {}",
+                active, e
+            ),
+        );
+    }
+    let dirty = profile_dirty_after_sync(&outcome);
+    if let Err(e) = update_app_config(app, |config| config.active_profile_dirty = dirty) {
+        log_internal(
+            app,
+            "WARN",
+            &format!("Could not save profile sync state: {}", e),
+        );
     }
+}
 
-    log_internal(
-        &app,
-        "WARN",
-        "Game detection failed: No valid installation found.",
-    );
-    None
+// Mod edits return right away; the profile catches up on a worker thread
+fn sync_active_profile_in_background(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || sync_active_profile(&app));
 }
 
+// Retries a failed sync first, so the answer reflects what a switch would actually lose
 #[tauri::command]
-fn open_mods_folder() -> Result<(), String> {
-    if let Some(game_path) = find_game_path() {
-        let mods_path = game_path.join("GAMEDATA").join("MODS");
-        fs::create_dir_all(&mods_path).map_err(|e| {
-            format!(
-                "Could not create MODS folder at '{}': {}",
-                mods_path.display(),
-                e
-            )
-        })?;
-        open::that(&mods_path).map_err(|e| {
-            format!(
-                "Could not open MODS folder at '{}': {}",
-                mods_path.display(),
-                e
-            )
-        })?;
-        Ok(())
-    } else {
-        Err("Game path not found.".to_string())
+fn check_profile_sync(app: AppHandle) -> ProfileSyncStatus {
+    if load_app_config(&app).active_profile_dirty {
+        sync_active_profile(&app);
+    }
+    let config = load_app_config(&app);
+    ProfileSyncStatus {
+        active_profile: config.active_profile,
+        dirty: config.active_profile_dirty,
     }
 }
 
-#[tauri::command]
-fn save_file(app: AppHandle, file_path: String, content: String) -> Result<(), String> {
-    log_internal(&app, "INFO", &format!("Saving MXML to: {}", file_path));
-    fs::write(&file_path, content).map_err(|e| {
-        let err = format!("Failed to write to file '{}': {}", file_path, e);
-        log_internal(&app, "ERROR", &err);
-        err
-    })
+// Where an entry's files come from when the profile is applied
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ProfileEntrySource {
+    Library,
+    Archive,
+    Nexus,
+    Unrecoverable,
 }
 
-#[tauri::command]
-fn resize_window(window: tauri::Window, width: f64) -> Result<(), String> {
-    let current_height = window.outer_size().map_err(|e| e.to_string())?.height;
-    window
-        .set_size(LogicalSize::new(width, current_height as f64))
-        .map_err(|e| e.to_string())?;
-    Ok(())
+impl ProfileEntrySource {
+    // Deployable without downloading anything
+    fn is_local(self) -> bool {
+        matches!(
+            self,
+            ProfileEntrySource::Library | ProfileEntrySource::Archive
+        )
+    }
 }
 
-#[tauri::command]
-fn rename_mod_folder(
-    app: AppHandle,
-    old_name: String,
-    new_name: String,
-) -> Result<Vec<ModRenderData>, String> {
-    log_internal(
-        &app,
-        "INFO",
-        &format!("Requesting rename: '{}' -> '{}'", old_name, new_name),
-    );
+#[derive(Serialize, Debug)]
+struct ProfileEntryStatus {
+    filename: String,
+    source: ProfileEntrySource,
+    mod_id: Option<String>,
+    file_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ProfileValidation {
+    // True when every entry can be deployed without downloading anything
+    ok: bool,
+    entries: Vec<ProfileEntryStatus>,
+}
 
-    let game_path = find_game_path().ok_or_else(|| "Could not find game path.".to_string())?;
-    let mods_path = game_path.join("GAMEDATA").join("MODS");
+#[derive(Serialize, Debug)]
+struct ProfileApplyFailure {
+    filename: String,
+    folder: Option<String>,
+    reason: String,
+    // Skipped for a missing archive the user agreed to go without; not a failed switch
+    #[serde(skip)]
+    missing_archive: bool,
+}
 
-    let old_path = mods_path.join(&old_name);
-    let new_path = mods_path.join(&new_name);
+#[derive(Serialize, Debug, Default)]
+struct ProfileApplyResult {
+    applied: Vec<String>,
+    failed: Vec<ProfileApplyFailure>,
+}
 
-    // 1. Validation
-    if !old_path.exists() {
-        return Err("Original mod folder not found.".to_string());
+fn profile_entry_source(
+    entry: &ProfileModEntry,
+    downloads_dir: &Path,
+    library_dir: &Path,
+) -> ProfileEntrySource {
+    if library_dir
+        .join(format!("{}_unpacked", entry.filename))
+        .is_dir()
+    {
+        ProfileEntrySource::Library
+    } else if downloads_dir.join(&entry.filename).is_file() {
+        ProfileEntrySource::Archive
+    } else if entry.mod_id.is_some() && entry.file_id.is_some() {
+        ProfileEntrySource::Nexus
+    } else {
+        ProfileEntrySource::Unrecoverable
     }
-    if new_path.exists() {
-        return Err("A mod with the new name already exists.".to_string());
+}
+
+#[tauri::command]
+fn validate_profile(app: AppHandle, profile_name: String) -> Result<ProfileValidation, String> {
+    let profile = load_profile_or_live(&app, Some(&profile_name))?;
+    let downloads_dir = get_downloads_dir(&app)?;
+    let library_dir = get_library_dir(&app)?;
+
+    let entries: Vec<ProfileEntryStatus> = profile
+        .mods
+        .iter()
+        .map(|entry| ProfileEntryStatus {
+            filename: entry.filename.clone(),
+            source: profile_entry_source(entry, &downloads_dir, &library_dir),
+            mod_id: entry.mod_id.clone(),
+            file_id: entry.file_id.clone(),
+        })
+        .collect();
+    let ok = entries.iter().all(|e| e.source.is_local());
+    Ok(ProfileValidation { ok, entries })
+}
+
+#[tauri::command]
+async fn apply_profile(
+    app: AppHandle,
+    profile_name: String,
+    discard_unsaved: Option<bool>,
+    allow_missing: Option<bool>,
+) -> Result<ProfileApplyResult, String> {
+    ensure_game_not_running(&app)?;
+    ensure_no_bisect(&app)?;
+    // The mods folder is about to be replaced; don't take unsaved live changes with it
+    check_unsaved_switch(
+        &check_profile_sync(app.clone()),
+        discard_unsaved.unwrap_or(false),
+    )?;
+    // A missing backup is worth a warning, not a blocked switch
+    if let Err(e) = backup_saves_off_thread(&app, "profile_switch").await {
+        log_internal(
+            &app,
+            "WARN",
+            &format!("Could not back up saves before the profile switch: {}", e),
+        );
     }
+    // A background save mid-switch would snapshot the emptied MODS folder
+    let _sync_guard = PROFILE_SYNC_LOCK.lock().map_err(|e| e.to_string())?;
+    let json_path = profile_json_path(&app, &profile_name)?;
+
+    let stored = read_profile_file(&app, &profile_name)?;
+    // Layered profiles deploy their resolved list but keep their own file as a layer
+    let layered = stored.parent.is_some();
+    let mut profile_data = if layered {
+        resolve_profile(&app, &profile_name)?
+    } else {
+        stored
+    };
+    let write_back = !layered && json_path.exists();
 
-    // Reads source zip from mod_info.json and renames the folder inside the Library
-    if let Some(info) = read_mod_info(&old_path) {
-        if let Some(source_zip) = info.install_source {
-            if let Ok(library_dir) = get_library_dir(&app) {
-                let lib_unpacked = library_dir.join(format!("{}_unpacked", source_zip));
-                let lib_old_path = lib_unpacked.join(&old_name);
-                let lib_new_path = lib_unpacked.join(&new_name);
+    if let Some(tied_id) = &profile_data.installation_id {
+        check_profile_installation(&app, &profile_name, tied_id)?;
+    }
 
-                if lib_old_path.exists() && !lib_new_path.exists() {
-                    // Try to rename in library. If it fails, it log it but don't stop the game rename.
-                    if let Err(e) = fs::rename(&lib_old_path, &lib_new_path) {
-                        log_internal(
-                            &app,
-                            "WARN",
-                            &format!("Failed to sync rename to Library: {}", e),
-                        );
-                    } else {
-                        log_internal(
-                            &app,
-                            "INFO",
-                            "Synced folder rename to Library successfully.",
-                        );
-                    }
-                }
-            }
+    // Entries that need a download are skipped; only go ahead once the user has seen them
+    if !allow_missing.unwrap_or(false) {
+        let downloads_dir = get_downloads_dir(&app)?;
+        let library_dir = get_library_dir(&app)?;
+        let missing: Vec<&str> = profile_data
+            .mods
+            .iter()
+            .filter(|entry| !profile_entry_source(entry, &downloads_dir, &library_dir).is_local())
+            .map(|entry| entry.filename.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Profile '{}' has mods with no local archive: {}. Confirm skipping them first.",
+                profile_name,
+                missing.join(", ")
+            ));
         }
     }
 
-    // 2. Rename Folder (Physically moves the folder, keeping the casing of new_name)
-    fs::rename(&old_path, &new_path).map_err(|e| {
-        let err = format!("Failed to rename folder: {}", e);
-        log_internal(&app, "ERROR", &err);
-        err
-    })?;
-
-    // 3. Update XML
-    // Reuse the logic from update_mod_name_in_xml but integrated here to avoid double-parsing.
-    let settings_file = game_path
-        .join("Binaries")
-        .join("SETTINGS")
-        .join("GCMODSETTINGS.MXML");
-    if settings_file.exists() {
-        match update_mod_name_in_xml(old_name.clone(), new_name.clone()) {
-            Ok(new_xml) => {
-                let _ = save_file(
-                    app.clone(),
-                    settings_file.to_string_lossy().to_string(),
-                    new_xml,
-                );
-            }
-            Err(e) => {
-                log_internal(
-                    &app,
-                    "WARN",
-                    &format!("Folder renamed, but XML update failed: {}", e),
-                );
+    // Only once nothing can refuse the switch does the outgoing profile take its settings along
+    capture_active_profile_settings(&app)?;
+    // Re-applying the active profile (or a child of it) must restore what was just captured
+    profile_data.settings_files = resolve_profile(&app, &profile_name)?.settings_files;
+    let game_path = game_context(&app)?.game_path;
+    // Move the current deployment aside so a failed switch can put it back
+    snapshot_deployment(&game_path, &profile_name)?;
+    let deployed =
+        deploy_profile(&app, &profile_name, &mut profile_data, &game_path).and_then(|result| {
+            // A mod that failed to deploy fails the switch; only skipped archives are allowed
+            let errors: Vec<String> = result
+                .failed
+                .iter()
+                .filter(|f| !f.missing_archive)
+                .map(|f| match &f.folder {
+                    Some(folder) => format!("{} ({}): {}", f.filename, folder, f.reason),
+                    None => format!("{}: {}", f.filename, f.reason),
+                })
+                .collect();
+            if !errors.is_empty() {
+                return Err(errors.join("; "));
             }
+            restore_profile_settings(&app, &game_path, &profile_data.settings_files)
+                .map_err(|e| format!("Failed to restore settings files: {}", e))?;
+            Ok(result)
+        });
+    let result = match deployed {
+        Ok(result) => result,
+        Err(e) => {
+            log_internal(&app, "ERROR", &format!("Profile switch failed: {}", e));
+            return match restore_deployment(&game_path) {
+                Ok(()) => Err(format!(
+                    "Profile switch failed: {}. Your previous mods were restored.",
+                    e
+                )),
+                Err(restore_err) => Err(format!(
+                    "Profile switch failed: {}. Restoring the previous mods also failed: {}",
+                    e, restore_err
+                )),
+            };
         }
+    };
+    if let Err(e) = discard_deployment_snapshot(&game_path) {
+        log_internal(
+            &app,
+            "WARN",
+            &format!("Could not remove rollback folder: {}", e),
+        );
     }
-
-    // 4. Return fresh list
-    get_all_mods_for_render(app)
-}
-
-#[tauri::command]
-fn delete_mod(app: AppHandle, mod_name: String) -> Result<Vec<ModRenderData>, String> {
-    log_internal(
-        &app,
-        "INFO",
-        &format!("Requesting deletion of mod: {}", mod_name),
-    );
-
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let settings_file_path = game_path
-        .join("Binaries")
-        .join("SETTINGS")
-        .join("GCMODSETTINGS.MXML");
-    let mod_to_delete_path = game_path.join("GAMEDATA").join("MODS").join(&mod_name);
-
-    if mod_to_delete_path.exists() {
-        if let Err(e) = fs::remove_dir_all(&mod_to_delete_path) {
+    // Only now that the whole switch went through does the profile learn its folder order
+    if write_back {
+        let written = serde_json::to_string_pretty(&profile_data)
+            .map_err(|e| e.to_string())
+            .and_then(|json_str| fs::write(&json_path, json_str).map_err(|e| e.to_string()));
+        if let Err(e) = written {
             log_internal(
                 &app,
-                "ERROR",
-                &format!("Failed to delete folder {}: {}", mod_name, e),
+                "WARN",
+                &format!(
+                    "Could not save the deployed load order to the profile: {}",
+                    e
+                ),
             );
-            return Err(format!("Failed to delete mod folder: {}", e));
         }
-        log_internal(
-            &app,
-            "INFO",
-            &format!("Deleted folder: {:?}", mod_to_delete_path),
-        );
-    } else {
+    }
+
+    let undeployed: Vec<String> = profile_data
+        .mods
+        .iter()
+        .filter(|entry| !result.applied.contains(&entry.filename))
+        .map(|entry| entry.filename.clone())
+        .collect();
+    update_app_config(&app, |config| {
+        config.active_profile = Some(profile_name);
+        config.active_profile_dirty = false;
+        config.undeployed_entries = undeployed;
+    })?;
+    if !result.failed.is_empty() {
         log_internal(
             &app,
             "WARN",
-            &format!("Folder not found for deletion: {:?}", mod_to_delete_path),
+            &format!(
+                "Profile applied without {} missing mod(s).",
+                result.failed.len()
+            ),
         );
     }
+    Ok(result)
+}
 
-    let xml_content = fs::read_to_string(&settings_file_path)
-        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
-    let mut root: SettingsData =
-        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+// Stamps a deployed folder with its entry, keeping anything else its mod_info.json held
+fn write_deployed_mod_info(folder: &Path, entry: &ProfileModEntry) {
+    let info_path = folder.join("mod_info.json");
+    let mut info = fs::read_to_string(&info_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+        .filter(Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    if let Some(obj) = info.as_object_mut() {
+        obj.insert("modId".to_string(), serde_json::json!(entry.mod_id));
+        obj.insert("fileId".to_string(), serde_json::json!(entry.file_id));
+        obj.insert("version".to_string(), serde_json::json!(entry.version));
+        obj.insert(
+            "installSource".to_string(),
+            Value::String(entry.filename.clone()),
+        );
+        if let Some(build) = &entry.game_build {
+            obj.insert("gameBuild".to_string(), Value::String(build.clone()));
+        }
+    }
+    if let Ok(json_str) = serde_json::to_string_pretty(&info) {
+        // Replaced rather than written through: the file may be hardlinked into the library
+        fs::remove_file(&info_path).ok();
+        fs::write(info_path, json_str).ok();
+    }
+}
 
-    for prop in root.properties.iter_mut() {
-        if prop.name == "Data" {
-            prop.mods.retain(|entry| {
-                let entry_name = entry
-                    .properties
-                    .iter()
-                    .find(|p| p.name == "Name")
-                    .and_then(|p| p.value.as_deref())
-                    .unwrap_or("");
+// Deploys every entry into an emptied MODS folder, then writes the matching settings
+// file and records the deployed folders on `profile_data`. Any error here rolls the
+// whole switch back.
+fn deploy_profile(
+    app: &AppHandle,
+    profile_name: &str,
+    profile_data: &mut ModProfileData,
+    game_path: &Path,
+) -> Result<ProfileApplyResult, String> {
+    let mods_dir = game_path.join("GAMEDATA/MODS");
+    let live_mxml = live_settings_path(game_path);
+    log_internal(app, "INFO", &format!("Applying profile: {}", profile_name));
 
-                !entry_name.eq_ignore_ascii_case(&mod_name)
-            });
+    // (entry index, folder name) of every folder deployed below
+    let mut deployed: Vec<(usize, String)> = Vec::new();
 
-            prop.mods.sort_by_key(|entry| {
-                entry
-                    .properties
-                    .iter()
-                    .find(|p| p.name == "ModPriority")
-                    .and_then(|p| p.value.as_ref())
-                    .and_then(|v| v.parse::<u32>().ok())
-                    .unwrap_or(u32::MAX)
+    let downloads_dir = get_downloads_dir(app)?;
+    let library_dir = get_library_dir(app)?; // <--- NEW
+    let total_mods = profile_data.mods.len();
+    let mut result = ProfileApplyResult::default();
+
+    for (i, entry) in profile_data.mods.iter().enumerate() {
+        let archive_path = downloads_dir.join(&entry.filename);
+        let library_folder_name = format!("{}_unpacked", entry.filename);
+        let library_mod_path = library_dir.join(&library_folder_name);
+
+        let current_idx = i + 1;
+
+        let _ = app.emit(
+            "profile-progress",
+            ProfileSwitchProgress {
+                current: current_idx,
+                total: total_mods,
+                current_mod: entry.filename.clone(),
+                file_progress: 0,
+            },
+        );
+
+        let fail = |folder: Option<&str>, reason: String| ProfileApplyFailure {
+            filename: entry.filename.clone(),
+            folder: folder.map(String::from),
+            reason,
+            missing_archive: false,
+        };
+
+        // 1. Ensure Library Exists (Extract if missing)
+        if !library_mod_path.exists() && !archive_path.exists() {
+            let reason = match (&entry.mod_id, &entry.file_id) {
+                (Some(mod_id), Some(file_id)) => format!(
+                    "Archive is missing; download it from Nexus (mod {}, file {})",
+                    mod_id, file_id
+                ),
+                _ => "Archive and library copy are both missing".to_string(),
+            };
+            log_internal(
+                app,
+                "WARN",
+                &format!("Skipped {}: {}", entry.filename, reason),
+            );
+            result.failed.push(ProfileApplyFailure {
+                missing_archive: true,
+                ..fail(None, reason)
             });
-            for (i, mod_entry) in prop.mods.iter_mut().enumerate() {
-                mod_entry.index = i.to_string();
+            continue;
+        }
+        if !library_mod_path.exists() {
+            let app_handle = app.clone();
+            let mod_name_clone = entry.filename.clone();
+
+            let progress_cb = move |pct: u64| {
+                let _ = app_handle.emit(
+                    "profile-progress",
+                    ProfileSwitchProgress {
+                        current: current_idx,
+                        total: total_mods,
+                        current_mod: mod_name_clone.clone(),
+                        file_progress: pct,
+                    },
+                );
+            };
+
+            // Extract to permanent library
+            if let Err(e) = extract_archive(&archive_path, &library_mod_path, progress_cb) {
+                log_internal(
+                    app,
+                    "ERROR",
+                    &format!("Failed to extract {}: {}", entry.filename, e),
+                );
+                result
+                    .failed
+                    .push(fail(None, format!("Extraction failed: {}", e)));
+                continue;
             }
-            break;
+        }
+
+        // 2. Deploy from Library (Instant Hardlink if possible)
+        if library_mod_path.exists() {
+            let has_specific_options = entry
+                .installed_options
+                .as_ref()
+                .map(|o| !o.is_empty())
+                .unwrap_or(false);
+
+            if has_specific_options {
+                if let Some(options) = &entry.installed_options {
+                    for target_folder_name in options {
+                        if let Some(source_path) =
+                            find_folder_in_tree(&library_mod_path, target_folder_name)
+                        {
+                            let dest = mods_dir.join(target_folder_name);
+
+                            // CHANGE: deploy_structure_recursive (Links/Copies) instead of rename (Move)
+                            if let Err(e) = deploy_structure_recursive(&source_path, &dest) {
+                                log_internal(
+                                    app,
+                                    "ERROR",
+                                    &format!("Failed to deploy {}: {}", target_folder_name, e),
+                                );
+                                result.failed.push(fail(Some(target_folder_name), e));
+                                continue;
+                            }
+                            deployed.push((i, target_folder_name.clone()));
+
+                            write_deployed_mod_info(&dest, entry);
+                        } else {
+                            result.failed.push(fail(
+                                Some(target_folder_name),
+                                "Folder not found in the unpacked archive".to_string(),
+                            ));
+                        }
+                    }
+                }
+            } else {
+                // Legacy: Deploy all top-level folders
+                if let Ok(entries) = fs::read_dir(&library_mod_path) {
+                    for fs_entry in entries.filter_map(Result::ok) {
+                        let folder_name = fs_entry.file_name().to_string_lossy().into_owned();
+                        let dest = mods_dir.join(&folder_name);
+                        let src = fs_entry.path();
+
+                        // CHANGE: Deploy
+                        if let Err(e) = deploy_structure_recursive(&src, &dest) {
+                            result.failed.push(fail(Some(&folder_name), e));
+                            continue;
+                        }
+                        deployed.push((i, folder_name.clone()));
+
+                        write_deployed_mod_info(&dest, entry);
+                    }
+                }
+            }
+            // Note: We DO NOT remove the library folder here. It stays for next time.
+            if deployed.iter().any(|(idx, _)| *idx == i) {
+                result.applied.push(entry.filename.clone());
+            }
+
+            // Update progress to 100% immediately since linking is fast
+            let _ = app.emit(
+                "profile-progress",
+                ProfileSwitchProgress {
+                    current: current_idx,
+                    total: total_mods,
+                    current_mod: entry.filename.clone(),
+                    file_progress: 100,
+                },
+            );
         }
     }
 
-    let unformatted_xml =
-        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
-    let mut reader = Reader::from_str(&unformatted_xml);
-    reader.config_mut().trim_text(true);
-    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-    loop {
-        match reader.read_event() {
-            Ok(Event::Eof) => break,
-            Ok(event) => writer.write_event(event).unwrap(),
-            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+    // Still the outgoing profile's settings; its entries fill in missing metadata
+    let previous_settings = fs::read_to_string(&live_mxml)
+        .ok()
+        .and_then(|content| from_str::<SettingsData>(&content).ok());
+    let settings = build_profile_settings(profile_data, &deployed, previous_settings.as_ref())?;
+    fs::write(&live_mxml, serialize_settings_xml(&settings)?).map_err(|e| e.to_string())?;
+
+    // Attach the now-known folders to their entries so the profile JSON holds the full order
+    let applied = folder_states_from_settings(&settings);
+    for (entry_idx, folder) in &deployed {
+        if let Some(state) = applied.iter().find(|s| s.name.eq_ignore_ascii_case(folder)) {
+            let entry = &mut profile_data.mods[*entry_idx];
+            entry
+                .folders
+                .retain(|f| !f.name.eq_ignore_ascii_case(folder));
+            entry.folders.push(ProfileFolderState {
+                name: folder.clone(),
+                ..state.clone()
+            });
         }
     }
-    let buf = writer.into_inner();
-    let xml_body = String::from_utf8(buf)
-        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+    profile_data.pending_load_order.retain(|p| {
+        !deployed
+            .iter()
+            .any(|(_, f)| f.eq_ignore_ascii_case(&p.name))
+    });
 
-    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
-        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
-        .replace(
-            " name=\"Dependencies\" value=\"\"",
-            " name=\"Dependencies\"",
-        )
-        .replace("\"/>", "\" />");
+    Ok(result)
+}
 
-    fs::write(&settings_file_path, &final_content)
-        .map_err(|e| format!("Failed to save updated GCMODSETTINGS.MXML: {}", e))?;
+#[tauri::command]
+fn delete_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
+    if profile_name == "Default" {
+        return Err("Cannot delete Default profile.".to_string());
+    }
+    let json_path = profile_json_path(&app, &profile_name)?;
+    let children = profile_children(&app, &profile_name)?;
+    if !children.is_empty() {
+        return Err(format!(
+            "Profile '{}' is the parent of: {}. Change their parent first.",
+            profile_name,
+            children.join(", ")
+        ));
+    }
+    if json_path.exists() {
+        fs::remove_file(json_path).map_err(|e| e.to_string())?;
+    }
 
-    get_all_mods_for_render(app)
+    update_app_config(&app, |config| {
+        if config
+            .active_profile
+            .as_deref()
+            .is_some_and(|active| active.eq_ignore_ascii_case(&profile_name))
+        {
+            config.active_profile = None;
+            config.active_profile_dirty = false;
+            config.undeployed_entries.clear();
+        }
+    })
 }
 
 #[tauri::command]
-fn reorder_mods(ordered_mod_names: Vec<String>) -> Result<String, String> {
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let settings_file_path = game_path
-        .join("Binaries")
-        .join("SETTINGS")
-        .join("GCMODSETTINGS.MXML");
+fn rename_profile(app: AppHandle, old_name: String, new_name: String) -> Result<String, String> {
+    if old_name == "Default" {
+        return Err("Cannot rename Default profile.".to_string());
+    }
+    let old_json = profile_json_path(&app, &old_name)?;
+    if !old_json.exists() {
+        return Err("Profile not found".to_string());
+    }
+    let new_name = claim_profile_name(&app, &new_name, Some(&old_name))?;
+    let new_json = profile_json_path(&app, &new_name)?;
 
-    let xml_content = fs::read_to_string(&settings_file_path)
-        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
-    let mut root: SettingsData =
-        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+    // Everything is read and prepared first, so a bad child stops the rename before any write
+    let original = fs::read_to_string(&old_json).map_err(|e| e.to_string())?;
+    // The stored name follows the file, as it does for copies
+    let mut data = read_profile_file(&app, &old_name)?;
+    data.name = new_name.clone();
+    let json_str = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
 
-    if let Some(prop) = root.properties.iter_mut().find(|p| p.name == "Data") {
-        let mut mods_map: HashMap<String, ModEntry> = prop
-            .mods
-            .drain(..)
-            .map(|entry| {
-                let name = entry
-                    .properties
-                    .iter()
-                    .find(|p| p.name == "Name")
-                    .and_then(|p| p.value.as_ref())
-                    .cloned()
-                    .unwrap_or_default();
-                (name, entry)
-            })
-            .collect();
+    // Keep layered profiles pointing at their renamed parent
+    let mut children = Vec::new();
+    for child in profile_children(&app, &old_name)? {
+        let child_json = profile_json_path(&app, &child)?;
+        let child_original = fs::read_to_string(&child_json).map_err(|e| e.to_string())?;
+        let mut data = read_profile_file(&app, &child)?;
+        data.parent = Some(new_name.clone());
+        let updated = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+        children.push((child_json, child_original, updated));
+    }
 
-        let mut sorted_mods: Vec<ModEntry> = Vec::new();
-        for (new_priority, mod_name_upper) in ordered_mod_names
-            .iter()
-            .map(|n| n.to_uppercase())
-            .enumerate()
-        {
-            if let Some(mut mod_entry) = mods_map.remove(&mod_name_upper) {
-                let new_order_str = new_priority.to_string();
-                mod_entry.index = new_order_str.clone();
-                if let Some(priority_prop) = mod_entry
-                    .properties
-                    .iter_mut()
-                    .find(|p| p.name == "ModPriority")
-                {
-                    priority_prop.value = Some(new_order_str);
-                }
-                sorted_mods.push(mod_entry);
+    // Reserving the new file first keeps the rename from replacing a profile created
+    // meanwhile; a case-only rename keeps its own file
+    let case_only = old_name.eq_ignore_ascii_case(&new_name);
+    if !case_only {
+        create_profile_file(&new_json, "")?;
+    }
+    fs::rename(&old_json, &new_json).map_err(|e| {
+        if !case_only {
+            fs::remove_file(&new_json).ok();
+        }
+        e.to_string()
+    })?;
+    let mut outcome = fs::write(&new_json, &json_str);
+    let mut touched = Vec::new();
+    if outcome.is_ok() {
+        for (child_json, child_original, updated) in &children {
+            touched.push((child_json, child_original));
+            outcome = fs::write(child_json, updated);
+            if outcome.is_err() {
+                break;
             }
         }
-
-        sorted_mods.extend(mods_map.into_values());
-        prop.mods = sorted_mods;
     }
-
-    let unformatted_xml =
-        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
-
-    let mut reader = Reader::from_str(&unformatted_xml);
-    reader.config_mut().trim_text(true);
-    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-    loop {
-        match reader.read_event() {
-            Ok(Event::Eof) => break,
-            Ok(event) => writer.write_event(event).unwrap(),
-            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
+    if let Err(e) = outcome {
+        // Undo the whole rename so no child is left pointing at a missing parent
+        for (child_json, child_original) in touched {
+            fs::write(child_json, child_original).ok();
         }
+        fs::write(&new_json, &original).ok();
+        fs::rename(&new_json, &old_json).ok();
+        return Err(format!("Could not rename profile: {}", e));
     }
-    let buf = writer.into_inner();
-    let xml_body = String::from_utf8(buf)
-        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
 
-    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
-        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
-        .replace(
-            " name=\"Dependencies\" value=\"\"",
-            " name=\"Dependencies\"",
-        )
-        .replace("\"/>", "\" />");
+    update_app_config(&app, |config| {
+        if config
+            .active_profile
+            .as_deref()
+            .is_some_and(|active| active.eq_ignore_ascii_case(&old_name))
+        {
+            config.active_profile = Some(new_name.clone());
+        }
+    })?;
+    Ok(new_name)
+}
 
-    Ok(final_content)
+#[tauri::command]
+fn create_empty_profile(app: AppHandle, profile_name: String) -> Result<String, String> {
+    let profile_name = claim_profile_name(&app, &profile_name, None)?;
+    let json_path = profile_json_path(&app, &profile_name)?;
+
+    // Empty mod list; applying it writes a clean GCMODSETTINGS
+    let empty_data = ModProfileData {
+        name: profile_name.clone(),
+        ..Default::default()
+    };
+    let json_str = serde_json::to_string_pretty(&empty_data).map_err(|e| e.to_string())?;
+    create_profile_file(&json_path, &json_str)?;
+
+    Ok(profile_name)
 }
 
 #[tauri::command]
-fn update_mod_name_in_xml(old_name: String, new_name: String) -> Result<String, String> {
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let settings_file_path = game_path
-        .join("Binaries")
-        .join("SETTINGS")
-        .join("GCMODSETTINGS.MXML");
+fn check_for_untracked_mods(app: AppHandle) -> bool {
+    if let Ok(GameContext { game_path, .. }) = game_context(&app) {
+        let mods_path = game_path.join("GAMEDATA").join("MODS");
+        if let Ok(entries) = fs::read_dir(mods_path) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    let info_path = entry.path().join("mod_info.json");
 
-    let xml_content = fs::read_to_string(&settings_file_path)
-        .map_err(|e| format!("Failed to read GCMODSETTINGS.MXML: {}", e))?;
-    let mut root: SettingsData =
-        from_str(&xml_content).map_err(|e| format!("Failed to parse GCMODSETTINGS.MXML: {}", e))?;
+                    if !info_path.exists() {
+                        return true; // No file = Untracked
+                    }
 
-    let mut mod_found = false;
-    for prop in root.properties.iter_mut() {
-        if prop.name == "Data" {
-            if let Some(mod_entry) = prop.mods.iter_mut().find(|entry| {
-                if let Some(name_prop) = entry.properties.iter().find(|p| p.name == "Name") {
-                    if let Some(val) = name_prop.value.as_deref() {
-                        val.eq_ignore_ascii_case(&old_name)
+                    if let Ok(content) = fs::read_to_string(&info_path) {
+                        if let Ok(json) = serde_json::from_str::<Value>(&content) {
+                            // STRICT CHECK:
+                            // 1. Get "installSource"
+                            // 2. Ensure it is a String (not null/number)
+                            // 3. Ensure it is NOT empty
+                            let is_valid_source = json
+                                .get("installSource")
+                                .and_then(|v| v.as_str())
+                                .map(|s| !s.is_empty())
+                                .unwrap_or(false); // If key missing or not string, defaults to false
+
+                            if !is_valid_source {
+                                return true;
+                            }
+                        } else {
+                            return true; // Invalid JSON
+                        }
                     } else {
-                        false
+                        return true; // Read error
                     }
-                } else {
-                    false
-                }
-            }) {
-                if let Some(name_prop) = mod_entry.properties.iter_mut().find(|p| p.name == "Name")
-                {
-                    name_prop.value = Some(new_name.to_uppercase());
-                    mod_found = true;
                 }
             }
-            break;
         }
     }
+    false
+}
 
-    if !mod_found {
-        return Err(format!(
-            "Could not find a mod entry with the name '{}' in the XML file.",
-            old_name
-        ));
+#[tauri::command]
+fn get_profile_mod_list(app: AppHandle, profile_name: String) -> Result<Vec<String>, String> {
+    let json_path = profile_json_path(&app, &profile_name)?;
+
+    if !json_path.exists() {
+        // If profile file doesn't exist (e.g. fresh Default), return empty list
+        return Ok(Vec::new());
     }
 
-    let unformatted_xml =
-        to_string(&root).map_err(|e| format!("Failed to serialize XML data: {}", e))?;
+    let data = resolve_profile(&app, &profile_name)?;
 
-    let mut reader = Reader::from_str(&unformatted_xml);
-    reader.config_mut().trim_text(true);
-    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-    loop {
-        match reader.read_event() {
-            Ok(Event::Eof) => break,
-            Ok(event) => writer.write_event(event).unwrap(),
-            Err(e) => return Err(format!("XML formatting error: {:?}", e)),
-        }
+    // Return just the list of filenames
+    let filenames = data.mods.iter().map(|m| m.filename.clone()).collect();
+    Ok(filenames)
+}
+
+#[tauri::command]
+fn copy_profile(app: AppHandle, source_name: String, new_name: String) -> Result<String, String> {
+    let source_json = profile_json_path(&app, &source_name)?;
+    if !source_json.exists() {
+        return Err("Source profile not found.".to_string());
     }
-    let buf = writer.into_inner();
-    let xml_body = String::from_utf8(buf)
-        .map_err(|e| format!("Failed to convert formatted XML to string: {}", e))?;
+    let new_name = claim_profile_name(&app, &new_name, None)?;
+    let new_json = profile_json_path(&app, &new_name)?;
 
-    let final_content = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", xml_body)
-        .replace(" name=\"Data\" value=\"\"", " name=\"Data\"")
-        .replace(
-            " name=\"Dependencies\" value=\"\"",
-            " name=\"Dependencies\"",
-        )
-        .replace("\"/>", "\" />");
+    // Update the "name" field of the copy
+    // (Otherwise the copied profile will internally claim to be the old profile)
+    let content = fs::read_to_string(&source_json).map_err(|e| e.to_string())?;
+    let mut data: ModProfileData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    data.name = new_name.clone();
 
-    Ok(final_content)
+    let new_content = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+    create_profile_file(&new_json, &new_content)?;
+
+    Ok(new_name)
 }
 
-#[tauri::command]
-fn update_mod_id_in_json(mod_folder_name: String, new_mod_id: String) -> Result<(), String> {
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let mod_info_path = game_path
-        .join("GAMEDATA")
-        .join("MODS")
-        .join(&mod_folder_name)
-        .join("mod_info.json");
+// --- PROFILE SETTINGS FILES ---
 
-    if !mod_info_path.exists() {
-        return Err(format!(
-            "mod_info.json not found for mod '{}'.",
-            mod_folder_name
-        ));
+const MAX_SETTINGS_BACKUPS: usize = 10;
+
+fn game_settings_dir(game_path: &Path) -> PathBuf {
+    game_path.join("Binaries").join("SETTINGS")
+}
+
+// GCMODSETTINGS.MXML is generated from the mod list, so it can't be carried as-is
+fn is_profile_settings_file_name(name: &str) -> bool {
+    is_plain_file_name(name) && !name.eq_ignore_ascii_case("GCMODSETTINGS.MXML")
+}
+
+// Profiles store settings files as text; None when the file doesn't exist
+fn read_settings_file(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Err(format!(
+            "{} is not a text file and can't be stored in a profile",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        )),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+// Re-reads each listed file from the game; files that vanished or can no longer be read
+// keep their last capture, and the read errors come back for the caller to log
+fn capture_settings_files(
+    game_path: &Path,
+    files: &[ProfileSettingsFile],
+) -> (Vec<ProfileSettingsFile>, Vec<String>) {
+    let settings_dir = game_settings_dir(game_path);
+    let mut problems = Vec::new();
+    let captured = files
+        .iter()
+        .map(|file| {
+            let live = read_settings_file(&settings_dir.join(&file.name)).unwrap_or_else(|e| {
+                problems.push(e);
+                None
+            });
+            ProfileSettingsFile {
+                name: file.name.clone(),
+                content: live.or_else(|| file.content.clone()),
+            }
+        })
+        .collect();
+    (captured, problems)
+}
+
+fn log_settings_capture_problems(app: &AppHandle, problems: &[String]) {
+    for problem in problems {
+        log_internal(
+            app,
+            "WARN",
+            &format!("Kept the last saved copy of a settings file: {}", problem),
+        );
     }
+}
 
-    let content = fs::read_to_string(&mod_info_path)
-        .map_err(|e| format!("Failed to read mod_info.json: {}", e))?;
+// In-game changes since the last save belong to the profile being switched away from
+fn capture_active_profile_settings(app: &AppHandle) -> Result<(), String> {
+    let Some(active) = load_app_config(app).active_profile else {
+        return Ok(());
+    };
+    let json_path = profile_json_path(app, &active)?;
+    let Some(mut profile) = fs::read_to_string(&json_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<ModProfileData>(&c).ok())
+    else {
+        return Ok(());
+    };
+    if profile.settings_files.is_empty() {
+        return Ok(());
+    }
+    let game_path = game_context(app)?.game_path;
+    let (captured, problems) = capture_settings_files(&game_path, &profile.settings_files);
+    log_settings_capture_problems(app, &problems);
+    if captured == profile.settings_files {
+        return Ok(());
+    }
+    profile.settings_files = captured;
+    let json_str = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json_str).map_err(|e| e.to_string())
+}
 
-    let mut json_value: Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse mod_info.json: {}", e))?;
+fn get_settings_backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = get_singularity_root(app)?.join("settings_backups");
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
 
-    if let Some(obj) = json_value.as_object_mut() {
-        obj.insert("id".to_string(), Value::String(new_mod_id));
-    } else {
-        return Err("mod_info.json is not a valid JSON object.".to_string());
+// Writes the profile's captured settings files over the game's, backing the originals up first
+fn restore_profile_settings(
+    app: &AppHandle,
+    game_path: &Path,
+    files: &[ProfileSettingsFile],
+) -> Result<(), String> {
+    let settings_dir = game_settings_dir(game_path);
+    let to_write: Vec<(&str, &str)> = files
+        .iter()
+        .filter(|f| is_profile_settings_file_name(&f.name))
+        .filter_map(|f| f.content.as_deref().map(|c| (f.name.as_str(), c)))
+        .collect();
+    if to_write.is_empty() {
+        return Ok(());
     }
 
-    let new_content = serde_json::to_string_pretty(&json_value)
-        .map_err(|e| format!("Failed to serialize updated JSON: {}", e))?;
+    let backups_dir = get_settings_backups_dir(app)?;
+    // The suffix keeps two switches within the same second apart; names still sort by time
+    let backup_dir = backups_dir.join(format!(
+        "{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        &Uuid::new_v4().simple().to_string()[..8]
+    ));
+    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    for (name, _) in &to_write {
+        let live = settings_dir.join(name);
+        if live.is_file() {
+            fs::copy(&live, backup_dir.join(name))
+                .map_err(|e| format!("Failed to back up {}: {}", name, e))?;
+        }
+    }
 
-    fs::write(&mod_info_path, new_content)
-        .map_err(|e| format!("Failed to write updated mod_info.json: {}", e))?;
+    let mut backups: Vec<PathBuf> = fs::read_dir(&backups_dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(MAX_SETTINGS_BACKUPS);
+    for old in backups.into_iter().take(excess) {
+        fs::remove_dir_all(old).ok();
+    }
 
+    fs::create_dir_all(&settings_dir).map_err(|e| e.to_string())?;
+    for (i, (name, content)) in to_write.iter().enumerate() {
+        if let Err(e) = fs::write(settings_dir.join(name), content) {
+            // Leave the game's settings as they were; the switch is rolled back as a whole
+            for (written, _) in &to_write[..=i] {
+                let original = backup_dir.join(written);
+                if original.is_file() {
+                    fs::copy(&original, settings_dir.join(written)).ok();
+                } else {
+                    fs::remove_file(settings_dir.join(written)).ok();
+                }
+            }
+            return Err(format!("Failed to write {}: {}", name, e));
+        }
+    }
+    log_internal(
+        app,
+        "INFO",
+        &format!(
+            "Restored profile settings files; originals saved to {}",
+            backup_dir.display()
+        ),
+    );
     Ok(())
 }
 
+// Settings files a profile could carry, as found in the active game's SETTINGS folder
 #[tauri::command]
-fn ensure_mod_info(
-    mod_folder_name: String,
-    mod_id: String,
-    file_id: String,
-    version: String,
-    install_source: String,
-) -> Result<(), String> {
-    let game_path =
-        find_game_path().ok_or_else(|| "Could not find game installation path.".to_string())?;
-    let mod_info_path = game_path
-        .join("GAMEDATA")
-        .join("MODS")
-        .join(&mod_folder_name)
-        .join("mod_info.json");
+fn list_game_settings_files(app: AppHandle) -> Result<Vec<String>, String> {
+    let settings_dir = game_settings_dir(&game_context(&app)?.game_path);
+    let mut names: Vec<String> = fs::read_dir(&settings_dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        // Only text files survive the trip through the profile JSON
+        .filter(|e| e.path().is_file() && read_settings_file(&e.path()).is_ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| is_profile_settings_file_name(name))
+        .collect();
+    names.sort();
+    Ok(names)
+}
 
-    let mut json_value: Value;
+#[tauri::command]
+fn get_profile_settings_files(app: AppHandle, profile_name: String) -> Result<Vec<String>, String> {
+    let profile = load_profile_or_live(&app, Some(&profile_name))?;
+    Ok(profile.settings_files.into_iter().map(|f| f.name).collect())
+}
 
-    if mod_info_path.exists() {
-        let content = fs::read_to_string(&mod_info_path).map_err(|e| e.to_string())?;
-        json_value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    } else {
-        json_value = serde_json::json!({});
+// Sets which settings files the profile carries; newly added ones start from the game's copy
+#[tauri::command]
+fn set_profile_settings_files(
+    app: AppHandle,
+    profile_name: String,
+    files: Vec<String>,
+) -> Result<(), String> {
+    if let Some(bad) = files.iter().find(|f| !is_profile_settings_file_name(f)) {
+        return Err(format!("'{}' can't be stored in a profile.", bad));
     }
+    let json_path = profile_json_path(&app, &profile_name)?;
+    let mut profile = read_profile_file(&app, &profile_name)?;
+    let game_path = game_context(&app).ok().map(|ctx| ctx.game_path);
 
-    if let Some(obj) = json_value.as_object_mut() {
-        if !mod_id.is_empty() {
-            obj.insert("modId".to_string(), Value::String(mod_id));
-        }
-        if !file_id.is_empty() {
-            obj.insert("fileId".to_string(), Value::String(file_id));
-        }
-        if !version.is_empty() {
-            obj.insert("version".to_string(), Value::String(version));
+    let mut settings_files = Vec::new();
+    for name in files {
+        if settings_files
+            .iter()
+            .any(|f: &ProfileSettingsFile| f.name.eq_ignore_ascii_case(&name))
+        {
+            continue;
         }
-        // Save the source zip name so Profile Save knows where this folder came from
-        obj.insert("installSource".to_string(), Value::String(install_source));
+        let existing = profile
+            .settings_files
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(&name))
+            .cloned();
+        let file = match (existing, &game_path) {
+            (Some(file), _) => file,
+            (None, Some(game_path)) => ProfileSettingsFile {
+                content: read_settings_file(&game_settings_dir(game_path).join(&name))?,
+                name,
+            },
+            (None, None) => ProfileSettingsFile {
+                name,
+                content: None,
+            },
+        };
+        settings_files.push(file);
     }
+    profile.settings_files = settings_files;
 
-    let new_content = serde_json::to_string_pretty(&json_value).map_err(|e| e.to_string())?;
-    fs::write(&mod_info_path, new_content).map_err(|e| e.to_string())?;
-
-    Ok(())
+    let json_str = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json_str).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn get_nexus_api_key(app: tauri::AppHandle) -> Result<String, String> {
-    let auth_path = get_auth_file_path(&app)?;
+// --- PROFILE INHERITANCE ---
 
-    if auth_path.exists() {
-        let content = fs::read_to_string(auth_path).map_err(|e| e.to_string())?;
-        // Parse JSON safely
-        let json: Value =
-            serde_json::from_str(&content).map_err(|_| "Invalid auth file".to_string())?;
+const MAX_PROFILE_DEPTH: usize = 16;
 
-        if let Some(key) = json.get("apikey").and_then(|k| k.as_str()) {
-            println!("Loaded API Key from AppData");
-            return Ok(key.to_string());
-        }
+// The profile file as stored, without resolving its parents
+fn read_profile_file(app: &AppHandle, profile_name: &str) -> Result<ModProfileData, String> {
+    let json_path = profile_json_path(app, profile_name)?;
+    if profile_name == "Default" && !json_path.exists() {
+        return Ok(ModProfileData {
+            name: profile_name.to_string(),
+            ..Default::default()
+        });
     }
+    let content = fs::read_to_string(&json_path)
+        .map_err(|_| format!("Profile '{}' not found", profile_name))?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
 
-    Err("No API Key found. Please log in.".to_string())
+// The effective profile: every ancestor's layer applied from the root down
+fn resolve_profile(app: &AppHandle, profile_name: &str) -> Result<ModProfileData, String> {
+    resolve_profile_layers(profile_name, |name| read_profile_file(app, name))
 }
 
-#[tauri::command]
-fn unregister_nxm_protocol() -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-        hkcu.delete_subkey_all("Software\\Classes\\nxm")
-            .map_err(|e| e.to_string())?;
+fn resolve_profile_layers<F>(profile_name: &str, load: F) -> Result<ModProfileData, String>
+where
+    F: Fn(&str) -> Result<ModProfileData, String>,
+{
+    // Child first, root last
+    let mut chain: Vec<ModProfileData> = Vec::new();
+    let mut visited: Vec<String> = Vec::new();
+    let mut next = Some(profile_name.to_string());
+    while let Some(current) = next {
+        if visited.iter().any(|v| v.eq_ignore_ascii_case(&current)) {
+            visited.push(current);
+            return Err(format!(
+                "Profile inheritance loops: {}",
+                visited.join(" -> ")
+            ));
+        }
+        if visited.len() >= MAX_PROFILE_DEPTH {
+            return Err(format!(
+                "Profile '{}' inherits through more than {} levels",
+                profile_name, MAX_PROFILE_DEPTH
+            ));
+        }
+        let profile = load(&current)?;
+        next = profile.parent.clone();
+        visited.push(current);
+        chain.push(profile);
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        let home = std::env::var("HOME").map_err(|_| "Could not find HOME".to_string())?;
-        let desktop_file =
-            PathBuf::from(home).join(".local/share/applications/nxm-handler.desktop");
-        if desktop_file.exists() {
-            fs::remove_file(desktop_file).map_err(|e| e.to_string())?;
+    let mut layers = chain.into_iter().rev();
+    let mut effective = layers.next().ok_or("Profile not found")?;
+    // The root has nothing to remove from or override
+    effective.removed.clear();
+    effective.folder_overrides.clear();
+    for layer in layers {
+        effective = apply_profile_layer(effective, layer);
+    }
+    effective.parent = None;
+    Ok(effective)
+}
+
+fn apply_profile_layer(base: ModProfileData, layer: ModProfileData) -> ModProfileData {
+    let mut mods: Vec<ProfileModEntry> = base
+        .mods
+        .into_iter()
+        .filter(|e| {
+            !layer
+                .removed
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(&e.filename))
+        })
+        .filter(|e| {
+            !layer
+                .mods
+                .iter()
+                .any(|m| m.filename.eq_ignore_ascii_case(&e.filename))
+        })
+        .collect();
+    for folder in mods.iter_mut().flat_map(|e| e.folders.iter_mut()) {
+        if let Some(o) = layer
+            .folder_overrides
+            .iter()
+            .find(|o| o.name.eq_ignore_ascii_case(&folder.name))
+        {
+            folder.priority = o.priority;
+            folder.enabled = o.enabled;
         }
     }
-    Ok(())
+    mods.extend(layer.mods);
+
+    let mut pending_load_order = base.pending_load_order;
+    pending_load_order.retain(|p| {
+        !layer
+            .pending_load_order
+            .iter()
+            .any(|l| l.name.eq_ignore_ascii_case(&p.name))
+    });
+    pending_load_order.extend(layer.pending_load_order);
+
+    ModProfileData {
+        name: layer.name,
+        mods,
+        installation_id: layer.installation_id.or(base.installation_id),
+        disable_all_mods: layer.disable_all_mods,
+        pending_load_order,
+        settings_files: if layer.settings_files.is_empty() {
+            base.settings_files
+        } else {
+            layer.settings_files
+        },
+        parent: None,
+        removed: Vec::new(),
+        folder_overrides: Vec::new(),
+    }
 }
 
-#[tauri::command]
-fn is_protocol_handler_registered() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_path_str) = exe_path.to_str() {
-                let hkcr = RegKey::predef(HKEY_CLASSES_ROOT);
-                if let Ok(command_key) = hkcr.open_subkey("nxm\\shell\\open\\command") {
-                    if let Ok(command_val) = command_key.get_value::<String, _>("") {
-                        return command_val.contains(exe_path_str);
+// Inverse of apply_profile_layer: the smallest layer that turns `parent` into `full`
+fn layer_over_parent(parent: &ModProfileData, full: ModProfileData) -> ModProfileData {
+    let mut mods = Vec::new();
+    let mut folder_overrides = Vec::new();
+    for entry in full.mods.iter() {
+        let inherited = parent.mods.iter().find(|p| {
+            p.filename.eq_ignore_ascii_case(&entry.filename)
+                && p.mod_id == entry.mod_id
+                && p.file_id == entry.file_id
+                && p.version == entry.version
+                && entry_folder_names(p) == entry_folder_names(entry)
+        });
+        match inherited {
+            Some(base) => {
+                for folder in &entry.folders {
+                    let base_state = base
+                        .folders
+                        .iter()
+                        .find(|f| f.name.eq_ignore_ascii_case(&folder.name));
+                    if base_state.map(|b| (b.priority, b.enabled))
+                        != Some((folder.priority, folder.enabled))
+                    {
+                        folder_overrides.push(folder.clone());
                     }
                 }
             }
+            None => mods.push(entry.clone()),
         }
-        return false;
     }
+    let removed = parent
+        .mods
+        .iter()
+        .filter(|p| {
+            !full
+                .mods
+                .iter()
+                .any(|e| e.filename.eq_ignore_ascii_case(&p.filename))
+        })
+        .map(|p| p.filename.clone())
+        .collect();
+
+    ModProfileData {
+        mods,
+        removed,
+        folder_overrides,
+        ..full
+    }
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let home = std::env::var("HOME").unwrap_or_default();
-        let desktop_file =
-            PathBuf::from(home).join(".local/share/applications/nxm-handler.desktop");
-        return desktop_file.exists();
+// Profiles that name `profile_name` as their parent
+fn profile_children(app: &AppHandle, profile_name: &str) -> Result<Vec<String>, String> {
+    let mut children = Vec::new();
+    for name in profile_names(app)? {
+        if let Ok(profile) = read_profile_file(app, &name) {
+            if profile
+                .parent
+                .as_deref()
+                .is_some_and(|p| p.eq_ignore_ascii_case(profile_name))
+            {
+                children.push(name);
+            }
+        }
     }
+    Ok(children)
 }
 
 #[tauri::command]
-fn register_nxm_protocol() -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
-        let exe_path_str = exe_path.to_string_lossy();
-        let command = format!("\"{}\" \"%1\"", exe_path_str);
+fn get_profile_parent(app: AppHandle, profile_name: String) -> Result<Option<String>, String> {
+    Ok(read_profile_file(&app, &profile_name)?.parent)
+}
 
-        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-        let (nxm_key, _) = hkcu
-            .create_subkey("Software\\Classes\\nxm")
-            .map_err(|e| e.to_string())?;
+// Sets or clears a profile's parent. The effective mod list stays the same: the profile
+// is rewritten as a layer over the new parent, or flattened when the parent is cleared.
+#[tauri::command]
+fn set_profile_parent(
+    app: AppHandle,
+    profile_name: String,
+    parent: Option<String>,
+) -> Result<(), String> {
+    let json_path = profile_json_path(&app, &profile_name)?;
+    let current = resolve_profile(&app, &profile_name)?;
+    let stored = read_profile_file(&app, &profile_name)?;
+
+    let updated = match parent.filter(|p| !p.trim().is_empty()) {
+        Some(parent_name) => {
+            // Resolving with the new link in place catches loops through the parent's ancestors
+            let load = |name: &str| {
+                if name.eq_ignore_ascii_case(&profile_name) {
+                    Ok(ModProfileData {
+                        parent: Some(parent_name.clone()),
+                        ..stored.clone()
+                    })
+                } else {
+                    read_profile_file(&app, name)
+                }
+            };
+            resolve_profile_layers(&profile_name, load)?;
+            let base = resolve_profile(&app, &parent_name)?;
+            // A layer can't mix two installations' mods
+            if let (Some(own), Some(parent_id)) = (&stored.installation_id, &base.installation_id) {
+                if own != parent_id {
+                    return Err(format!(
+                        "Profile '{}' belongs to a different game installation than '{}'.",
+                        parent_name, profile_name
+                    ));
+                }
+            }
+            ModProfileData {
+                parent: Some(parent_name),
+                ..layer_over_parent(&base, current)
+            }
+        }
+        None => current,
+    };
 
-        nxm_key
-            .set_value("", &"URL:NXM Protocol")
-            .map_err(|e| e.to_string())?;
-        nxm_key
-            .set_value("URL Protocol", &"")
-            .map_err(|e| e.to_string())?;
+    let json_str = serde_json::to_string_pretty(&updated).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json_str).map_err(|e| e.to_string())
+}
 
-        let (command_key, _) = nxm_key
-            .create_subkey_with_flags("shell\\open\\command", KEY_WRITE)
-            .map_err(|e| e.to_string())?;
-        command_key
-            .set_value("", &command)
-            .map_err(|e| e.to_string())?;
+// --- NEXUS COLLECTIONS ---
 
-        println!("Successfully registered nxm:// protocol handler to current user.");
-    }
+const NEXUS_GRAPHQL_URL: &str = "https://api.nexusmods.com/v2/graphql";
 
-    #[cfg(target_os = "linux")]
-    {
-        let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
-        let home = std::env::var("HOME").map_err(|_| "Could not find HOME".to_string())?;
+// Subset of the collection.json manifest shipped inside every Nexus Collection
+#[derive(Deserialize, Debug, Clone)]
+struct CollectionManifest {
+    info: CollectionInfo,
+    #[serde(default)]
+    mods: Vec<CollectionModEntry>,
+    #[serde(rename = "loadOrder", default)]
+    load_order: Vec<CollectionLoadOrderEntry>,
+}
 
-        // 1. Create the .desktop file content
-        let desktop_content = format!(
-            "[Desktop Entry]\n\
-            Type=Application\n\
-            Name=Singularity Mod Manager\n\
-            Exec=\"{}\" %u\n\
-            StartupNotify=false\n\
-            MimeType=x-scheme-handler/nxm;\n",
-            exe_path.to_string_lossy()
-        );
+#[derive(Deserialize, Debug, Clone)]
+struct CollectionInfo {
+    name: String,
+    #[serde(rename = "domainName", default)]
+    domain_name: Option<String>,
+}
 
-        // 2. Write to ~/.local/share/applications/nxm-handler.desktop
-        let apps_dir = PathBuf::from(&home).join(".local/share/applications");
-        if !apps_dir.exists() {
-            fs::create_dir_all(&apps_dir).map_err(|e| e.to_string())?;
+#[derive(Deserialize, Debug, Clone)]
+struct CollectionModEntry {
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(rename = "domainName", default)]
+    domain_name: Option<String>,
+    source: CollectionModSource,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CollectionModSource {
+    #[serde(rename = "type")]
+    source_type: String,
+    #[serde(rename = "modId", default, deserialize_with = "deserialize_nexus_id")]
+    mod_id: Option<String>,
+    #[serde(rename = "fileId", default, deserialize_with = "deserialize_nexus_id")]
+    file_id: Option<String>,
+    #[serde(rename = "logicalFilename", default)]
+    logical_filename: Option<String>,
+}
+
+// One GCMODSETTINGS entry. `id` is the mod folder name, highest priority last.
+#[derive(Deserialize, Debug, Clone)]
+struct CollectionLoadOrderEntry {
+    id: String,
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+#[derive(Debug, Clone)]
+struct NexusFileInfo {
+    file_name: String,
+    name: Option<String>,
+    version: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct CollectionDownload {
+    mod_id: String,
+    file_id: String,
+    file_name: String,
+    display_name: String,
+    version: Option<String>,
+    optional: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct CollectionImportResult {
+    profile_name: String,
+    queued: Vec<CollectionDownload>,
+    already_downloaded: Vec<String>,
+    skipped: Vec<String>,
+    // key/expires from the originating nxm link, forwarded to each queued download
+    nxm_query: String,
+}
+
+struct CollectionImportPlan {
+    entries: Vec<ProfileModEntry>,
+    queued: Vec<CollectionDownload>,
+    already_downloaded: Vec<String>,
+    skipped: Vec<String>,
+    settings: SettingsData,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// Nexus sends IDs as numbers in some payloads and strings in others
+fn deserialize_nexus_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn build_load_order_settings(
+    load_order: &[CollectionLoadOrderEntry],
+) -> Result<SettingsData, String> {
+    let mut root: SettingsData = from_str(CLEAN_MXML_TEMPLATE).map_err(|e| e.to_string())?;
+
+    if let Some(prop) = root.properties.iter_mut().find(|p| p.name == "Data") {
+        let mut seen = std::collections::HashSet::new();
+        for entry in load_order {
+            if seen.insert(entry.id.to_uppercase()) {
+                let priority = prop.mods.len();
+                prop.mods.push(build_mod_entry(
+                    priority,
+                    &entry.id,
+                    entry.enabled,
+                    &BTreeMap::new(),
+                ));
+            }
         }
-        let desktop_file = apps_dir.join("nxm-handler.desktop");
-        fs::write(&desktop_file, desktop_content).map_err(|e| e.to_string())?;
+    }
+    Ok(root)
+}
 
-        // 3. Register the mimetype using xdg-mime
-        use std::process::Command;
-        Command::new("xdg-mime")
-            .args(["default", "nxm-handler.desktop", "x-scheme-handler/nxm"])
-            .output()
-            .map_err(|e| format!("Failed to run xdg-mime: {}", e))?;
+fn plan_collection_import(
+    manifest: &CollectionManifest,
+    files: &HashMap<(String, String), NexusFileInfo>,
+    downloads_dir: &Path,
+) -> Result<CollectionImportPlan, String> {
+    if let Some(domain) = &manifest.info.domain_name {
+        if domain != "nomanssky" {
+            return Err(format!(
+                "This collection is for '{}', not No Man's Sky.",
+                domain
+            ));
+        }
     }
 
-    Ok(())
-}
+    let mut plan = CollectionImportPlan {
+        entries: Vec::new(),
+        queued: Vec::new(),
+        already_downloaded: Vec::new(),
+        skipped: Vec::new(),
+        settings: build_load_order_settings(&manifest.load_order)?,
+    };
 
-#[tauri::command]
-async fn download_mod_archive(
-    app: AppHandle,
-    download_url: String,
-    file_name: String,
-    download_id: Option<String>,
-) -> Result<DownloadResult, String> {
-    log_internal(
-        &app,
-        "INFO",
-        &format!("Starting download request for: {}", file_name),
-    );
+    for m in &manifest.mods {
+        if m.domain_name.as_deref().is_some_and(|d| d != "nomanssky") {
+            plan.skipped
+                .push(format!("{} (belongs to another game)", m.name));
+            continue;
+        }
 
-    let downloads_path = get_downloads_dir(&app)?;
-    let final_archive_path = downloads_path.join(&file_name);
+        let (mod_id, file_id) = match (&m.source.mod_id, &m.source.file_id) {
+            (Some(mid), Some(fid)) if m.source.source_type == "nexus" => (mid.clone(), fid.clone()),
+            _ => {
+                plan.skipped.push(format!(
+                    "{} (unsupported source '{}')",
+                    m.name, m.source.source_type
+                ));
+                continue;
+            }
+        };
 
-    let mut response = reqwest::get(&download_url).await.map_err(|e| {
-        let err = format!("Failed to initiate HTTP request: {}", e);
-        log_internal(&app, "ERROR", &err);
-        err
-    })?;
+        let Some(file) = files.get(&(mod_id.clone(), file_id.clone())) else {
+            plan.skipped.push(format!(
+                "{} (file {} of mod {} not found on Nexus)",
+                m.name, file_id, mod_id
+            ));
+            continue;
+        };
 
-    if !response.status().is_success() {
-        let err = format!("Download failed with HTTP status: {}", response.status());
-        log_internal(&app, "ERROR", &err);
-        return Err(err);
+        if plan.entries.iter().any(|e| e.filename == file.file_name) {
+            continue;
+        }
+
+        let version = file.version.clone().or_else(|| m.version.clone());
+        plan.entries.push(ProfileModEntry {
+            filename: file.file_name.clone(),
+            mod_id: Some(mod_id.clone()),
+            file_id: Some(file_id.clone()),
+            version: version.clone(),
+            installed_options: None,
+            folders: Vec::new(),
+            game_build: None,
+        });
+
+        if downloads_dir.join(&file.file_name).exists() {
+            plan.already_downloaded.push(file.file_name.clone());
+        } else {
+            plan.queued.push(CollectionDownload {
+                mod_id,
+                file_id,
+                file_name: file.file_name.clone(),
+                display_name: file
+                    .name
+                    .clone()
+                    .or_else(|| m.source.logical_filename.clone())
+                    .unwrap_or_else(|| m.name.clone()),
+                version,
+                optional: m.optional,
+            });
+        }
     }
 
-    let total_size = response.content_length().unwrap_or(0);
-    log_internal(
-        &app,
-        "INFO",
-        &format!("Connection established. Content-Length: {}", total_size),
-    );
+    Ok(plan)
+}
 
-    let mut file = fs::File::create(&final_archive_path)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
+async fn nexus_get(api_key: &str, url: &str) -> Result<String, String> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("apikey", api_key)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
 
-    let mut downloaded: u64 = 0;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Nexus API returned HTTP status: {}",
+            response.status()
+        ));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))
+}
 
-    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
-        file.write_all(&chunk).map_err(|e| e.to_string())?;
-        downloaded += chunk.len() as u64;
+// Resolves every referenced file ID to the archive name Nexus will serve it under
+async fn fetch_nexus_file_infos(
+    app: &AppHandle,
+    api_key: &str,
+    mod_ids: &[String],
+) -> HashMap<(String, String), NexusFileInfo> {
+    let mut files = HashMap::new();
+
+    for mod_id in mod_ids {
+        let listing = match fetch_mod_files_cached(app, Some(api_key), mod_id).await {
+            Ok(listing) => listing,
+            Err(e) => {
+                log_internal(
+                    app,
+                    "WARN",
+                    &format!("Could not list files for mod {}: {}", mod_id, e),
+                );
+                continue;
+            }
+        };
 
-        if let Some(id) = &download_id {
-            if total_size > 0 {
-                let pct = (downloaded * 100) / total_size;
-                // Don't log every percentage to disk, too spammy. Frontend handles visual progress.
-                let _ = app.emit(
-                    "install-progress",
-                    InstallProgressPayload {
-                        id: id.clone(),
-                        step: format!("Downloading: {}%", pct),
-                        progress: Some(pct),
+        for file in &listing.files {
+            let Some(file_id) = json_id_to_string(file.get("file_id")) else {
+                continue;
+            };
+            if let Some(file_name) = file.get("file_name").and_then(|v| v.as_str()) {
+                files.insert(
+                    (mod_id.clone(), file_id),
+                    NexusFileInfo {
+                        file_name: file_name.to_string(),
+                        name: file.get("name").and_then(|v| v.as_str()).map(String::from),
+                        version: file
+                            .get("version")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
                     },
                 );
             }
         }
     }
+    files
+}
 
-    let metadata = fs::metadata(&final_archive_path).map_err(|e| e.to_string())?;
-    let file_size = metadata.len();
+// Downloads the collection archive for a revision and reads its collection.json
+async fn fetch_collection_manifest(
+    app: &AppHandle,
+    api_key: &str,
+    slug: &str,
+    revision: u32,
+) -> Result<CollectionManifest, String> {
+    let query = serde_json::json!({
+        "query": "query CollectionRevision($slug: String!, $revision: Int, $domainName: String) { collectionRevision(slug: $slug, revision: $revision, domainName: $domainName) { downloadLink } }",
+        "variables": { "slug": slug, "revision": revision, "domainName": "nomanssky" }
+    });
+
+    let response = reqwest::Client::new()
+        .post(NEXUS_GRAPHQL_URL)
+        .header("apikey", api_key)
+        .header("Content-Type", "application/json")
+        .body(query.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+
+    let json: Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    let download_link = json
+        .pointer("/data/collectionRevision/downloadLink")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            format!(
+                "Collection '{}' revision {} was not found on Nexus.",
+                slug, revision
+            )
+        })?;
+
+    let links_body = nexus_get(
+        api_key,
+        &format!("https://api.nexusmods.com{}", download_link),
+    )
+    .await?;
+    let links: Value = serde_json::from_str(&links_body).map_err(|e| e.to_string())?;
+    let archive_url = links
+        .pointer("/download_links/0/URI")
+        .and_then(|v| v.as_str())
+        .ok_or("Nexus did not return a download link for this collection.")?;
+
+    let bytes = reqwest::get(archive_url)
+        .await
+        .map_err(|e| format!("Failed to download collection: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download collection: {}", e))?;
+
+    let staging_dir = get_staging_dir(app)?;
+    let extension = if bytes.starts_with(b"PK") {
+        "zip"
+    } else {
+        "7z"
+    };
+    let archive_path = staging_dir.join(format!("collection_{}_{}.{}", slug, revision, extension));
+    let extract_dir = staging_dir.join(format!("collection_{}_{}", slug, revision));
+    fs::write(&archive_path, &bytes).map_err(|e| e.to_string())?;
+
+    let extract_dir_clone = extract_dir.clone();
+    let archive_path_clone = archive_path.clone();
+    let manifest_result =
+        tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+            extract_archive(&archive_path_clone, &extract_dir_clone, |_| {})?;
+            fs::read_to_string(extract_dir_clone.join("collection.json"))
+                .map_err(|e| format!("Collection archive has no collection.json: {}", e))
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = fs::remove_file(&archive_path);
+    let _ = fs::remove_dir_all(&extract_dir);
+
+    serde_json::from_str(&manifest_result?)
+        .map_err(|e| format!("Invalid collection manifest: {}", e))
+}
+
+async fn import_collection(
+    app: &AppHandle,
+    api_key: &str,
+    manifest: CollectionManifest,
+) -> Result<CollectionImportResult, String> {
+    let profile_name = sanitize_profile_name(&manifest.info.name);
+    if profile_name.is_empty() {
+        return Err("The collection has no name.".to_string());
+    }
+    let profile_name = claim_profile_name(app, &profile_name, None)?;
+    let json_path = profile_json_path(app, &profile_name)?;
+
+    let mut mod_ids: Vec<String> = manifest
+        .mods
+        .iter()
+        .filter_map(|m| m.source.mod_id.clone())
+        .collect();
+    mod_ids.sort();
+    mod_ids.dedup();
+
+    let files = fetch_nexus_file_infos(app, api_key, &mod_ids).await;
+    let downloads_dir = get_downloads_dir(app)?;
+    let plan = plan_collection_import(&manifest, &files, &downloads_dir)?;
+
+    // Folder names from the load order can only be matched to archives once installed
+    let data = ModProfileData {
+        name: profile_name.clone(),
+        mods: plan.entries,
+        disable_all_mods: settings_disable_all(&plan.settings),
+        pending_load_order: folder_states_from_settings(&plan.settings),
+        ..Default::default()
+    };
+    let json_str = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+    create_profile_file(&json_path, &json_str)?;
 
     log_internal(
-        &app,
+        app,
         "INFO",
         &format!(
-            "Download finished. File: {:?} (Size: {} bytes)",
-            final_archive_path, file_size
+            "Imported collection '{}': {} to download, {} already downloaded, {} skipped.",
+            profile_name,
+            plan.queued.len(),
+            plan.already_downloaded.len(),
+            plan.skipped.len()
         ),
     );
 
-    let created_time = metadata
-        .created()
-        .map_err(|e| e.to_string())?
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| e.to_string())?
-        .as_secs();
-
-    Ok(DownloadResult {
-        path: final_archive_path.to_string_lossy().into_owned(),
-        size: file_size,
-        created_at: created_time,
+    Ok(CollectionImportResult {
+        profile_name,
+        queued: plan.queued,
+        already_downloaded: plan.already_downloaded,
+        skipped: plan.skipped,
+        nxm_query: String::new(),
     })
 }
 
 #[tauri::command]
-fn show_in_folder(path: String) {
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
-        Command::new("explorer")
-            .args(["/select,", &path])
-            .spawn()
-            .unwrap();
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
-        let p = PathBuf::from(&path);
+async fn import_nexus_collection(
+    app: AppHandle,
+    link: String,
+) -> Result<CollectionImportResult, String> {
+    let nxm_link = parse_nxm_link(&link)?;
+    validate_nxm_link(&nxm_link, Utc::now().timestamp())?;
+    let NxmTarget::Collection { slug, revision } = &nxm_link.target else {
+        return Err("This link does not point to a collection.".to_string());
+    };
+    log_internal(
+        &app,
+        "INFO",
+        &format!("Importing collection '{}' (revision {})", slug, revision),
+    );
 
-        if let Some(parent) = p.parent() {
-            Command::new("xdg-open").arg(parent).spawn().ok();
-        } else {
-            Command::new("xdg-open").arg(path).spawn().ok();
-        }
-    }
+    let api_key = get_nexus_api_key(app.clone())?;
+    let manifest = fetch_collection_manifest(&app, &api_key, slug, *revision).await?;
+    let mut result = import_collection(&app, &api_key, manifest).await?;
+    result.nxm_query = nxm_download_query(&nxm_link);
+    Ok(result)
 }
 
 #[tauri::command]
-fn delete_archive_file(path: String) -> Result<(), String> {
-    if Path::new(&path).exists() {
-        fs::remove_file(path).map_err(|e| e.to_string())?;
-    }
-    Ok(())
+async fn import_collection_manifest(
+    app: AppHandle,
+    manifest_path: String,
+) -> Result<CollectionImportResult, String> {
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read collection manifest: {}", e))?;
+    let manifest: CollectionManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid collection manifest: {}", e))?;
+
+    let api_key = get_nexus_api_key(app.clone())?;
+    import_collection(&app, &api_key, manifest).await
 }
 
-#[tauri::command]
-fn clear_downloads_folder(app: AppHandle) -> Result<(), String> {
-    // 1. Clear Downloads
-    let downloads_path = get_downloads_dir(&app)?;
-    if downloads_path.exists() {
-        let entries = fs::read_dir(&downloads_path).map_err(|e| e.to_string())?;
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    fs::remove_file(path).map_err(|e| e.to_string())?;
-                }
-            }
-        }
-    }
-
-    // 2. Clear Library
-    let library_path = get_library_dir(&app)?;
-    if library_path.exists() {
-        let entries = fs::read_dir(&library_path).map_err(|e| e.to_string())?;
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                // Library contains folders, so remove_dir_all
-                if path.is_dir() {
-                    fs::remove_dir_all(path).map_err(|e| e.to_string())?;
-                }
-            }
-        }
-    }
+// --- PROFILE DIFF ---
 
-    Ok(())
+#[derive(Serialize, Debug)]
+struct ModVersionChange {
+    mod_id: Option<String>,
+    from_filename: String,
+    to_filename: String,
+    from_file_id: Option<String>,
+    to_file_id: Option<String>,
+    from_version: Option<String>,
+    to_version: Option<String>,
 }
 
-#[tauri::command]
-fn launch_game(version_type: String, game_path: String) -> Result<(), String> {
-    match version_type.as_str() {
-        "Steam" => {
-            // Launch via Steam Protocol to ensure overlay/logging works
-            #[cfg(target_os = "linux")]
-            {
-                let is_flatpak = std::env::var("FLATPAK_ID").is_ok()
-                    || std::env::var("SINGULARITY_FLATPAK").is_ok();
-                if is_flatpak {
-                    // xdg-open can't handle steam:// URIs inside the Flatpak sandbox,
-                    // so delegate to the host system via flatpak-spawn
-                    std::process::Command::new("flatpak-spawn")
-                        .args(["--host", "xdg-open", "steam://run/275850"])
-                        .spawn()
-                        .map_err(|e| format!("Failed to launch via flatpak-spawn: {}", e))?;
-                } else {
-                    open::that("steam://run/275850").map_err(|e| e.to_string())?;
-                }
-            }
-            #[cfg(not(target_os = "linux"))]
-            {
-                open::that("steam://run/275850").map_err(|e| e.to_string())?;
-            }
-        }
-        "GOG" | "GamePass" | _ => {
-            // For GOG and GamePass, we try to launch the Binary directly
-            let exe_path = std::path::Path::new(&game_path)
-                .join("Binaries")
-                .join("NMS.exe");
+#[derive(Serialize, Debug)]
+struct ModOptionsChange {
+    filename: String,
+    from: Vec<String>,
+    to: Vec<String>,
+}
 
-            if exe_path.exists() {
-                open::that(exe_path).map_err(|e| e.to_string())?;
-            } else {
-                return Err("Could not find NMS.exe in Binaries folder.".to_string());
-            }
-        }
-    }
-    Ok(())
+// Positions are ranks among the folders both sides have
+#[derive(Serialize, Debug, PartialEq)]
+struct FolderPriorityChange {
+    folder: String,
+    from_position: usize,
+    to_position: usize,
 }
 
-fn get_auth_file_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+#[derive(Serialize, Debug, PartialEq)]
+struct FolderEnabledChange {
+    folder: String,
+    enabled: bool,
+}
 
-    if !app_data_dir.exists() {
-        fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
-    }
+#[derive(Serialize, Debug, Default)]
+struct ProfileDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+    version_changed: Vec<ModVersionChange>,
+    options_changed: Vec<ModOptionsChange>,
+    priority_changed: Vec<FolderPriorityChange>,
+    enabled_changed: Vec<FolderEnabledChange>,
+    disable_all_changed: Option<bool>,
+}
 
-    Ok(app_data_dir.join("auth.json"))
+fn entry_folder_names(entry: &ProfileModEntry) -> Vec<String> {
+    let mut names: Vec<String> = match &entry.installed_options {
+        Some(options) if !options.is_empty() => options.clone(),
+        _ => entry.folders.iter().map(|f| f.name.clone()).collect(),
+    };
+    names.iter_mut().for_each(|n| *n = n.to_uppercase());
+    names.sort();
+    names
 }
 
-// --- PROFILE MANAGEMENT ---
+// Folder (upper-cased) -> state, ordered by priority
+fn profile_folder_order(profile: &ModProfileData) -> Vec<ProfileFolderState> {
+    let mut states: Vec<ProfileFolderState> = profile
+        .mods
+        .iter()
+        .flat_map(|entry| entry.folders.iter())
+        .map(|state| ProfileFolderState {
+            name: state.name.to_uppercase(),
+            ..state.clone()
+        })
+        .collect();
+    states.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    states
+}
 
-fn get_profiles_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let root = get_singularity_root(app)?;
-    let profiles = root.join("profiles");
-    if !profiles.exists() {
-        fs::create_dir_all(&profiles).map_err(|e| e.to_string())?;
+// Indices of one longest strictly increasing subsequence of `values`
+fn longest_increasing_run(values: &[usize]) -> Vec<usize> {
+    let mut lengths = vec![1usize; values.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
     }
-    Ok(profiles)
+    let mut run = Vec::new();
+    let mut current = (0..values.len()).max_by_key(|&i| (lengths[i], std::cmp::Reverse(i)));
+    while let Some(i) = current {
+        run.push(i);
+        current = previous[i];
+    }
+    run.reverse();
+    run
 }
 
-#[tauri::command]
-fn list_profiles(app: AppHandle) -> Result<Vec<String>, String> {
-    let dir = get_profiles_dir(&app)?;
-    let mut profiles = Vec::new();
+// What changes when going from `a` to `b`
+fn diff_profile_data(a: &ModProfileData, b: &ModProfileData) -> ProfileDiff {
+    let mut diff = ProfileDiff::default();
 
-    // 1. Always ensure "Default" is first in the list
-    profiles.push("Default".to_string());
+    // 1. Pair entries: same archive first, then same Nexus mod with another file
+    let mut unmatched_b: Vec<&ProfileModEntry> = b.mods.iter().collect();
+    let mut pairs: Vec<(&ProfileModEntry, &ProfileModEntry)> = Vec::new();
+    let mut unmatched_a: Vec<&ProfileModEntry> = Vec::new();
+    for entry in &a.mods {
+        match unmatched_b
+            .iter()
+            .position(|other| other.filename == entry.filename)
+        {
+            Some(pos) => pairs.push((entry, unmatched_b.remove(pos))),
+            None => unmatched_a.push(entry),
+        }
+    }
+    for entry in unmatched_a {
+        let same_mod = entry.mod_id.as_ref().and_then(|mod_id| {
+            unmatched_b
+                .iter()
+                .position(|other| other.mod_id.as_ref() == Some(mod_id))
+        });
+        match same_mod {
+            Some(pos) => {
+                let other = unmatched_b.remove(pos);
+                diff.version_changed.push(ModVersionChange {
+                    mod_id: entry.mod_id.clone(),
+                    from_filename: entry.filename.clone(),
+                    to_filename: other.filename.clone(),
+                    from_file_id: entry.file_id.clone(),
+                    to_file_id: other.file_id.clone(),
+                    from_version: entry.version.clone(),
+                    to_version: other.version.clone(),
+                });
+                pairs.push((entry, other));
+            }
+            None => diff.removed.push(entry.filename.clone()),
+        }
+    }
+    diff.added = unmatched_b.iter().map(|e| e.filename.clone()).collect();
+
+    // 2. Installed options of archives on both sides
+    for (from, to) in &pairs {
+        let (from_folders, to_folders) = (entry_folder_names(from), entry_folder_names(to));
+        if from.filename == to.filename && from_folders != to_folders {
+            diff.options_changed.push(ModOptionsChange {
+                filename: to.filename.clone(),
+                from: from_folders,
+                to: to_folders,
+            });
+        }
+    }
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.path().file_stem() {
-                let name_str = name.to_string_lossy().into_owned();
-                // 2. Filter out "Default" here so it doesn't appear twice
-                if name_str != "Default" && entry.path().extension().unwrap_or_default() == "json" {
-                    profiles.push(name_str);
-                }
+    // 3. Load order and enabled flags of folders on both sides
+    let from_order = profile_folder_order(a);
+    let to_order = profile_folder_order(b);
+    let shared = |order: &[ProfileFolderState], other: &[ProfileFolderState]| -> Vec<String> {
+        order
+            .iter()
+            .filter(|s| other.iter().any(|o| o.name == s.name))
+            .map(|s| s.name.clone())
+            .collect()
+    };
+    let from_shared = shared(&from_order, &to_order);
+    let to_shared = shared(&to_order, &from_order);
+    let to_positions: Vec<usize> = from_shared
+        .iter()
+        .map(|folder| to_shared.iter().position(|f| f == folder).unwrap_or(0))
+        .collect();
+    // Folders outside the longest run that kept its relative order are the ones that
+    // moved; the rest only shifted around them
+    let kept = longest_increasing_run(&to_positions);
+    for (from_position, folder) in from_shared.iter().enumerate() {
+        if !kept.contains(&from_position) {
+            diff.priority_changed.push(FolderPriorityChange {
+                folder: folder.clone(),
+                from_position,
+                to_position: to_positions[from_position],
+            });
+        }
+        let from_enabled = from_order
+            .iter()
+            .find(|s| &s.name == folder)
+            .map(|s| s.enabled);
+        let to_enabled = to_order
+            .iter()
+            .find(|s| &s.name == folder)
+            .map(|s| s.enabled);
+        if let (Some(from_enabled), Some(to_enabled)) = (from_enabled, to_enabled) {
+            if from_enabled != to_enabled {
+                diff.enabled_changed.push(FolderEnabledChange {
+                    folder: folder.clone(),
+                    enabled: to_enabled,
+                });
             }
         }
     }
-    let mut others: Vec<String> = profiles.drain(1..).collect();
-    others.sort();
-    profiles.extend(others);
 
-    Ok(profiles)
+    if a.disable_all_mods != b.disable_all_mods {
+        diff.disable_all_changed = Some(b.disable_all_mods);
+    }
+    diff
+}
+
+fn load_profile_or_live(
+    app: &AppHandle,
+    profile_name: Option<&str>,
+) -> Result<ModProfileData, String> {
+    match profile_name {
+        None => {
+            let game_path = game_context(app)?.game_path;
+            Ok(snapshot_live_profile(Some(&game_path), "live"))
+        }
+        Some(name) => resolve_profile(app, name),
+    }
 }
 
+// Compares two profiles; None on either side means the mods deployed in the game right now
 #[tauri::command]
-fn save_active_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
-    let profiles_dir = get_profiles_dir(&app)?;
-    let json_path = profiles_dir.join(format!("{}.json", profile_name));
-    let mxml_backup_path = profiles_dir.join(format!("{}.mxml", profile_name));
+fn diff_profiles(
+    app: AppHandle,
+    a: Option<String>,
+    b: Option<String>,
+) -> Result<ProfileDiff, String> {
+    let from = load_profile_or_live(&app, a.as_deref())?;
+    let to = load_profile_or_live(&app, b.as_deref())?;
+    Ok(diff_profile_data(&from, &to))
+}
 
-    // Map: ZipFilename -> List of Installed Folder Names
-    let mut profile_map: HashMap<String, Vec<String>> = HashMap::new();
+// --- PROFILE PACKAGES ---
+// A single zip with manifest.json (profile, load order, Nexus IDs, archive hashes)
+// and, optionally, archives/<file> for mods that can't be fetched from Nexus.
 
-    if let Some(game_path) = find_game_path() {
-        let mods_path = game_path.join("GAMEDATA").join("MODS");
-        if let Ok(entries) = fs::read_dir(mods_path) {
-            for entry in entries.flatten() {
-                if entry.path().is_dir() {
-                    let folder_name = entry.file_name().to_string_lossy().into_owned();
-                    let info_path = entry.path().join("mod_info.json");
+const PROFILE_PACKAGE_FORMAT: u32 = 1;
 
-                    if let Ok(content) = fs::read_to_string(&info_path) {
-                        if let Ok(json) = serde_json::from_str::<Value>(&content) {
-                            if let Some(source) = json.get("installSource").and_then(|s| s.as_str())
-                            {
-                                if !source.is_empty() {
-                                    profile_map
-                                        .entry(source.to_string())
-                                        .or_default()
-                                        .push(folder_name);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PackagedArchive {
+    filename: String,
+    // None when the archive wasn't in the downloads folder at export time
+    sha256: Option<String>,
+    size: Option<u64>,
+    embedded: bool,
+}
 
-        // Backup MXML
-        let current_mxml = game_path
-            .join("Binaries")
-            .join("SETTINGS")
-            .join("GCMODSETTINGS.MXML");
-        if current_mxml.exists() {
-            fs::copy(current_mxml, mxml_backup_path).map_err(|e| e.to_string())?;
-        }
-    }
+#[derive(Serialize, Deserialize, Debug)]
+struct ProfilePackageManifest {
+    format_version: u32,
+    exported_at: String,
+    profile: ModProfileData,
+    archives: Vec<PackagedArchive>,
+}
 
-    // Convert Map to ProfileModEntry list
-    let mut profile_entries = Vec::new();
-    for (filename, installed_folders) in profile_map {
-        // --- NO HASHING HERE ANYMORE ---
-        // We simply trust the filename linkage.
+#[derive(Serialize)]
+struct ProfileExportResult {
+    embedded: Vec<String>,
+    // Non-Nexus archives that were not in the downloads folder
+    missing: Vec<String>,
+}
 
-        // Sample metadata from first folder
-        let mut p_mod_id = None;
-        let mut p_file_id = None;
-        let mut p_version = None;
+#[derive(Serialize, Default)]
+struct ProfileImportResult {
+    profile_name: String,
+    available: Vec<String>,
+    extracted: Vec<String>,
+    queued: Vec<CollectionDownload>,
+    missing: Vec<String>,
+    // Local archives with the same name but different contents than the exporter's
+    hash_mismatches: Vec<String>,
+}
 
-        if let Some(first_folder) = installed_folders.first() {
-            if let Some(gp) = find_game_path() {
-                let info_p = gp
-                    .join("GAMEDATA/MODS")
-                    .join(first_folder)
-                    .join("mod_info.json");
-                if let Ok(c) = fs::read_to_string(info_p) {
-                    if let Ok(j) = serde_json::from_str::<Value>(&c) {
-                        p_mod_id = j.get("modId").and_then(|s| s.as_str()).map(String::from);
-                        p_file_id = j.get("fileId").and_then(|s| s.as_str()).map(String::from);
-                        p_version = j.get("version").and_then(|s| s.as_str()).map(String::from);
-                    }
-                }
-            }
-        }
+#[derive(Debug, PartialEq)]
+enum ArchiveSource {
+    Downloads { hash_matches: bool },
+    Library,
+    Package,
+    Nexus,
+    Missing,
+}
 
-        profile_entries.push(ProfileModEntry {
-            filename,
-            // hash: ... REMOVED
-            mod_id: p_mod_id,
-            file_id: p_file_id,
-            version: p_version,
-            installed_options: Some(installed_folders),
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+// Package contents are untrusted; only plain file names may touch the disk
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name)
+}
+
+fn resolve_archive_source(
+    entry: &ProfileModEntry,
+    archive: Option<&PackagedArchive>,
+    downloads_dir: &Path,
+    library_dir: &Path,
+) -> ArchiveSource {
+    let local = downloads_dir.join(&entry.filename);
+    if local.is_file() {
+        let expected = archive.and_then(|a| a.sha256.as_deref());
+        let hash_matches = match expected {
+            Some(expected) => sha256_file(&local).is_ok_and(|actual| actual == expected),
+            None => true,
+        };
+        return ArchiveSource::Downloads { hash_matches };
+    }
+    if library_dir
+        .join(format!("{}_unpacked", entry.filename))
+        .is_dir()
+    {
+        return ArchiveSource::Library;
+    }
+    if archive.is_some_and(|a| a.embedded) {
+        return ArchiveSource::Package;
+    }
+    if entry.mod_id.is_some() && entry.file_id.is_some() {
+        return ArchiveSource::Nexus;
+    }
+    ArchiveSource::Missing
+}
+
+// Hashing and copying archives can take minutes, so packages are built off the command thread
+#[tauri::command]
+async fn export_profile(
+    app: AppHandle,
+    profile_name: String,
+    output_path: String,
+    embed_local_archives: bool,
+) -> Result<ProfileExportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        write_profile_package(&app, &profile_name, &output_path, embed_local_archives)
+    })
+    .await
+    .map_err(|e| format!("Profile export task failed: {}", e))?
+}
+
+fn write_profile_package(
+    app: &AppHandle,
+    profile_name: &str,
+    output_path: &str,
+    embed_local_archives: bool,
+) -> Result<ProfileExportResult, String> {
+    // Packages are self-contained, so layered profiles are exported flattened
+    let mut profile = resolve_profile(app, profile_name)?;
+    // Installation IDs only mean something on this machine
+    profile.installation_id = None;
+
+    let downloads_dir = get_downloads_dir(app)?;
+    let mut result = ProfileExportResult {
+        embedded: Vec::new(),
+        missing: Vec::new(),
+    };
+    let mut archives = Vec::new();
+    for entry in &profile.mods {
+        let path = downloads_dir.join(&entry.filename);
+        let exists = path.is_file();
+        let from_nexus = entry.mod_id.is_some() && entry.file_id.is_some();
+        let embedded = embed_local_archives && !from_nexus && exists;
+        if !from_nexus && !exists {
+            result.missing.push(entry.filename.clone());
+        }
+        if embedded {
+            result.embedded.push(entry.filename.clone());
+        }
+        archives.push(PackagedArchive {
+            filename: entry.filename.clone(),
+            sha256: if exists {
+                Some(sha256_file(&path)?)
+            } else {
+                None
+            },
+            size: fs::metadata(&path).ok().map(|m| m.len()),
+            embedded,
         });
     }
 
-    let data = ModProfileData {
-        name: profile_name,
-        mods: profile_entries,
+    let manifest = ProfilePackageManifest {
+        format_version: PROFILE_PACKAGE_FORMAT,
+        exported_at: Utc::now().to_rfc3339(),
+        profile,
+        archives,
     };
 
-    let json_str = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    fs::write(&json_path, json_str).map_err(|e| e.to_string())?;
+    let file = fs::File::create(output_path).map_err(|e| e.to_string())?;
+    let mut writer = zip::ZipWriter::new(file);
+    let deflated = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    // Mod archives are already compressed
+    let stored = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .large_file(true);
+
+    writer
+        .start_file("manifest.json", deflated)
+        .map_err(|e| e.to_string())?;
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    writer
+        .write_all(manifest_json.as_bytes())
+        .map_err(|e| e.to_string())?;
+    for filename in &result.embedded {
+        writer
+            .start_file(format!("archives/{}", filename), stored)
+            .map_err(|e| e.to_string())?;
+        let mut archive =
+            fs::File::open(downloads_dir.join(filename)).map_err(|e| e.to_string())?;
+        io::copy(&mut archive, &mut writer).map_err(|e| e.to_string())?;
+    }
+    writer.finish().map_err(|e| e.to_string())?;
 
-    Ok(())
+    log_internal(
+        app,
+        "INFO",
+        &format!(
+            "Exported profile '{}' to {} ({} archives embedded, {} missing)",
+            profile_name,
+            output_path,
+            result.embedded.len(),
+            result.missing.len()
+        ),
+    );
+    Ok(result)
+}
+
+// A name the user typed must already be valid; one from the package is only a suggestion
+// and gets cleaned up, since the package may come from another system or be hand-made
+fn import_profile_name(chosen: Option<&str>, manifest_name: &str) -> Result<String, String> {
+    if let Some(chosen) = chosen {
+        return validate_profile_name(chosen);
+    }
+    let name = sanitize_profile_name(manifest_name);
+    if name.is_empty() {
+        return Err("The profile has no name.".to_string());
+    }
+    Ok(name)
 }
 
 #[tauri::command]
-async fn apply_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
-    let dir = get_profiles_dir(&app)?;
-    let json_path = dir.join(format!("{}.json", profile_name));
-    let mxml_backup_path = dir.join(format!("{}.mxml", profile_name));
+async fn import_profile(
+    app: AppHandle,
+    package_path: String,
+    profile_name: Option<String>,
+) -> Result<ProfileImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        read_profile_package(&app, &package_path, profile_name.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Profile import task failed: {}", e))?
+}
 
-    let profile_data: ModProfileData = if profile_name == "Default" && !json_path.exists() {
-        ModProfileData {
-            name: "Default".to_string(),
-            mods: vec![],
-        }
-    } else {
-        let content =
-            fs::read_to_string(&json_path).map_err(|_| "Profile not found".to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())?
+fn read_profile_package(
+    app: &AppHandle,
+    package_path: &str,
+    profile_name: Option<&str>,
+) -> Result<ProfileImportResult, String> {
+    let file = fs::File::open(package_path).map_err(|e| e.to_string())?;
+    let mut package = ZipArchive::new(file).map_err(|e| format!("Not a profile package: {}", e))?;
+    let manifest: ProfilePackageManifest = {
+        let entry = package
+            .by_name("manifest.json")
+            .map_err(|_| "Not a profile package: manifest.json is missing.".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Invalid profile package: {}", e))?
     };
+    if manifest.format_version > PROFILE_PACKAGE_FORMAT {
+        return Err("This profile package was made by a newer version of Singularity.".to_string());
+    }
 
-    let game_path = find_game_path().ok_or("Game path not found")?;
-    let mods_dir = game_path.join("GAMEDATA/MODS");
+    let name = import_profile_name(profile_name, &manifest.profile.name)?;
+    let name = claim_profile_name(app, &name, None)?;
+    let json_path = profile_json_path(app, &name)?;
 
-    // Clean Game Folder
-    if mods_dir.exists() {
-        for entry in fs::read_dir(&mods_dir).map_err(|e| e.to_string())? {
-            let entry = entry.map_err(|e| e.to_string())?;
-            if entry.path().is_dir() || entry.path().extension().unwrap_or_default() == "pak" {
-                if entry.path().is_dir() {
-                    fs::remove_dir_all(entry.path()).ok();
-                } else {
-                    fs::remove_file(entry.path()).ok();
+    let downloads_dir = get_downloads_dir(app)?;
+    let library_dir = get_library_dir(app)?;
+    let mut result = ProfileImportResult {
+        profile_name: name.clone(),
+        ..Default::default()
+    };
+    let mut profile = manifest.profile;
+    profile.name = name.clone();
+    profile.installation_id = None;
+    // Packages are exported flattened; layer fields from a hand-made manifest would tie the
+    // import to whatever profile here happens to share the parent's name
+    profile.parent = None;
+    profile.removed.clear();
+    profile.folder_overrides.clear();
+    profile.mods.retain(|entry| {
+        let plain = is_plain_file_name(&entry.filename);
+        if !plain {
+            result.missing.push(entry.filename.clone());
+        }
+        plain
+    });
+
+    for entry in &profile.mods {
+        let archive = manifest
+            .archives
+            .iter()
+            .find(|a| a.filename == entry.filename);
+        match resolve_archive_source(entry, archive, &downloads_dir, &library_dir) {
+            ArchiveSource::Downloads { hash_matches } => {
+                if !hash_matches {
+                    result.hash_mismatches.push(entry.filename.clone());
+                }
+                result.available.push(entry.filename.clone());
+            }
+            ArchiveSource::Library => result.available.push(entry.filename.clone()),
+            ArchiveSource::Package => {
+                let dest = downloads_dir.join(&entry.filename);
+                let extracted = (|| -> Result<(), String> {
+                    let mut source = package
+                        .by_name(&format!("archives/{}", entry.filename))
+                        .map_err(|e| e.to_string())?;
+                    let mut out = fs::File::create(&dest).map_err(|e| e.to_string())?;
+                    io::copy(&mut source, &mut out).map_err(|e| e.to_string())?;
+                    let expected = archive.and_then(|a| a.sha256.as_deref());
+                    if expected.is_some_and(|h| sha256_file(&dest).ok().as_deref() != Some(h)) {
+                        return Err("checksum mismatch".to_string());
+                    }
+                    Ok(())
+                })();
+                match extracted {
+                    Ok(()) => result.extracted.push(entry.filename.clone()),
+                    Err(e) => {
+                        fs::remove_file(&dest).ok();
+                        log_internal(
+                            app,
+                            "WARN",
+                            &format!("Could not extract {} from package: {}", entry.filename, e),
+                        );
+                        result.missing.push(entry.filename.clone());
+                    }
                 }
             }
+            ArchiveSource::Nexus => result.queued.push(CollectionDownload {
+                mod_id: entry.mod_id.clone().unwrap_or_default(),
+                file_id: entry.file_id.clone().unwrap_or_default(),
+                file_name: entry.filename.clone(),
+                display_name: entry.filename.clone(),
+                version: entry.version.clone(),
+                optional: false,
+            }),
+            ArchiveSource::Missing => result.missing.push(entry.filename.clone()),
         }
     }
 
-    let live_mxml = game_path
-        .join("Binaries")
-        .join("SETTINGS")
-        .join("GCMODSETTINGS.MXML");
-    println!("Applying Profile: {}", profile_name);
-
-    if mxml_backup_path.exists() {
-        let mut src = fs::File::open(&mxml_backup_path).map_err(|e| e.to_string())?;
-        let mut dst = fs::File::create(&live_mxml).map_err(|e| e.to_string())?;
-        io::copy(&mut src, &mut dst).map_err(|e| e.to_string())?;
-    } else {
-        fs::write(&live_mxml, CLEAN_MXML_TEMPLATE).map_err(|e| e.to_string())?;
-    }
+    let json_str = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    create_profile_file(&json_path, &json_str)?;
 
-    let downloads_dir = get_downloads_dir(&app)?;
-    let library_dir = get_library_dir(&app)?; // <--- NEW
-    let total_mods = profile_data.mods.len();
+    log_internal(
+        app,
+        "INFO",
+        &format!(
+            "Imported profile '{}': {} available, {} from package, {} to download, {} missing",
+            name,
+            result.available.len(),
+            result.extracted.len(),
+            result.queued.len(),
+            result.missing.len()
+        ),
+    );
+    Ok(result)
+}
 
-    for (i, entry) in profile_data.mods.iter().enumerate() {
-        let archive_path = downloads_dir.join(&entry.filename);
-        let library_folder_name = format!("{}_unpacked", entry.filename);
-        let library_mod_path = library_dir.join(&library_folder_name);
+// --- NEXUS METADATA CACHE ---
+const MOD_INFO_TTL_SECS: i64 = 24 * 60 * 60;
+const MOD_FILES_TTL_SECS: i64 = 60 * 60;
+const IMAGE_TTL_SECS: u64 = 7 * 24 * 60 * 60;
 
-        let current_idx = i + 1;
+// One file per mod: Singularity/metadata_cache/mods/<mod_id>.json
+#[derive(Serialize, Deserialize, Default)]
+struct ModMetadataCacheEntry {
+    mod_id: String,
+    #[serde(default)]
+    info: Option<Value>,
+    #[serde(default)]
+    info_fetched_at: i64,
+    // Nexus file entries keyed by file ID
+    #[serde(default)]
+    files: HashMap<String, Value>,
+    #[serde(default)]
+    files_fetched_at: i64,
+}
 
-        app.emit(
-            "profile-progress",
-            ProfileSwitchProgress {
-                current: current_idx,
-                total: total_mods,
-                current_mod: entry.filename.clone(),
-                file_progress: 0,
-            },
-        )
-        .unwrap();
+#[derive(Serialize)]
+struct ModFilesResult {
+    files: Vec<Value>,
+    // True when the list came from an expired cache entry because Nexus was unreachable
+    stale: bool,
+}
 
-        // 1. Ensure Library Exists (Extract if missing)
-        if !library_mod_path.exists() && archive_path.exists() {
-            let app_handle = app.clone();
-            let mod_name_clone = entry.filename.clone();
+fn get_metadata_cache_dir(app: &AppHandle, kind: &str) -> Result<PathBuf, String> {
+    let dir = get_singularity_root(app)?.join("metadata_cache").join(kind);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
 
-            let progress_cb = move |pct: u64| {
-                let _ = app_handle.emit(
-                    "profile-progress",
-                    ProfileSwitchProgress {
-                        current: current_idx,
-                        total: total_mods,
-                        current_mod: mod_name_clone.clone(),
-                        file_progress: pct,
-                    },
-                );
-            };
+fn json_id_to_string(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        _ => None,
+    }
+}
 
-            // Extract to permanent library
-            if let Err(e) = extract_archive(&archive_path, &library_mod_path, progress_cb) {
-                println!("Failed to extract {}: {}", entry.filename, e);
-                continue;
-            }
-        }
+fn metadata_cache_path(app: &AppHandle, mod_id: &str) -> Result<PathBuf, String> {
+    if mod_id.is_empty() || !mod_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid mod ID: {}", mod_id));
+    }
+    Ok(get_metadata_cache_dir(app, "mods")?.join(format!("{}.json", mod_id)))
+}
 
-        // 2. Deploy from Library (Instant Hardlink if possible)
-        if library_mod_path.exists() {
-            let has_specific_options = entry
-                .installed_options
-                .as_ref()
-                .map(|o| !o.is_empty())
-                .unwrap_or(false);
+fn read_metadata_cache(app: &AppHandle, mod_id: &str) -> Result<ModMetadataCacheEntry, String> {
+    let path = metadata_cache_path(app, mod_id)?;
+    let entry = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<ModMetadataCacheEntry>(&content).ok())
+        .unwrap_or_else(|| ModMetadataCacheEntry {
+            mod_id: mod_id.to_string(),
+            ..Default::default()
+        });
+    Ok(entry)
+}
 
-            if has_specific_options {
-                if let Some(options) = &entry.installed_options {
-                    for target_folder_name in options {
-                        if let Some(source_path) =
-                            find_folder_in_tree(&library_mod_path, target_folder_name)
-                        {
-                            let dest = mods_dir.join(target_folder_name);
+fn write_metadata_cache(app: &AppHandle, entry: &ModMetadataCacheEntry) -> Result<(), String> {
+    let path = metadata_cache_path(app, &entry.mod_id)?;
+    let json = serde_json::to_string_pretty(entry).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
 
-                            // CHANGE: deploy_structure_recursive (Links/Copies) instead of rename (Move)
-                            if let Err(e) = deploy_structure_recursive(&source_path, &dest) {
-                                println!("Failed to deploy {}: {}", target_folder_name, e);
-                                continue;
-                            }
+fn store_cached_files(entry: &mut ModMetadataCacheEntry, files: &[Value], now: i64) {
+    entry.files = files
+        .iter()
+        .filter_map(|file| json_id_to_string(file.get("file_id")).map(|id| (id, file.clone())))
+        .collect();
+    entry.files_fetched_at = now;
+}
 
-                            let info_path = dest.join("mod_info.json");
-                            let info_json = serde_json::json!({
-                                "modId": entry.mod_id,
-                                "fileId": entry.file_id,
-                                "version": entry.version,
-                                "installSource": entry.filename
-                            });
-                            if let Ok(json_str) = serde_json::to_string_pretty(&info_json) {
-                                fs::write(info_path, json_str).ok();
-                            }
-                        }
-                    }
-                }
-            } else {
-                // Legacy: Deploy all top-level folders
-                if let Ok(entries) = fs::read_dir(&library_mod_path) {
-                    for fs_entry in entries.filter_map(Result::ok) {
-                        let folder_name = fs_entry.file_name().to_string_lossy().into_owned();
-                        let dest = mods_dir.join(&folder_name);
-                        let src = fs_entry.path();
+fn cached_files_sorted(entry: &ModMetadataCacheEntry) -> Vec<Value> {
+    let mut files: Vec<Value> = entry.files.values().cloned().collect();
+    files.sort_by_key(|f| {
+        f.get("uploaded_timestamp")
+            .and_then(|t| t.as_i64())
+            .unwrap_or(0)
+    });
+    files
+}
 
-                        // CHANGE: Deploy
-                        deploy_structure_recursive(&src, &dest).ok();
-
-                        let info_path = dest.join("mod_info.json");
-                        let info_json = serde_json::json!({
-                            "modId": entry.mod_id,
-                            "fileId": entry.file_id,
-                            "version": entry.version,
-                            "installSource": entry.filename
-                        });
-                        if let Ok(json_str) = serde_json::to_string_pretty(&info_json) {
-                            fs::write(info_path, json_str).ok();
-                        }
-                    }
-                }
-            }
-            // Note: We DO NOT remove the library folder here. It stays for next time.
+// Returns the Nexus file list for a mod, refreshing it once the TTL has passed.
+// When Nexus can't be reached an expired cache entry is still better than nothing.
+async fn fetch_mod_files_cached(
+    app: &AppHandle,
+    api_key: Option<&str>,
+    mod_id: &str,
+) -> Result<ModFilesResult, String> {
+    let mut entry = read_metadata_cache(app, mod_id)?;
+    let now = Utc::now().timestamp();
+    let has_cache = entry.files_fetched_at > 0;
+
+    if has_cache && now - entry.files_fetched_at < MOD_FILES_TTL_SECS {
+        return Ok(ModFilesResult {
+            files: cached_files_sorted(&entry),
+            stale: false,
+        });
+    }
 
-            // Update progress to 100% immediately since linking is fast
-            app.emit(
-                "profile-progress",
-                ProfileSwitchProgress {
-                    current: current_idx,
-                    total: total_mods,
-                    current_mod: entry.filename.clone(),
-                    file_progress: 100,
-                },
-            )
-            .unwrap();
+    let fetched = match api_key {
+        Some(api_key) => {
+            let url = format!(
+                "https://api.nexusmods.com/v1/games/nomanssky/mods/{}/files.json",
+                mod_id
+            );
+            nexus_get(api_key, &url)
+                .await
+                .and_then(|body| serde_json::from_str::<Value>(&body).map_err(|e| e.to_string()))
+        }
+        None => Err("Not logged in to Nexus.".to_string()),
+    };
+
+    match fetched {
+        Ok(json) => {
+            let files = json
+                .get("files")
+                .and_then(|f| f.as_array())
+                .cloned()
+                .unwrap_or_default();
+            store_cached_files(&mut entry, &files, now);
+            if let Err(e) = write_metadata_cache(app, &entry) {
+                log_internal(
+                    app,
+                    "WARN",
+                    &format!("Failed to cache files for mod {}: {}", mod_id, e),
+                );
+            }
+            Ok(ModFilesResult {
+                files,
+                stale: false,
+            })
+        }
+        Err(e) if has_cache => {
+            log_internal(
+                app,
+                "WARN",
+                &format!("Using cached file list for mod {} ({})", mod_id, e),
+            );
+            Ok(ModFilesResult {
+                files: cached_files_sorted(&entry),
+                stale: true,
+            })
         }
+        Err(e) => Err(e),
     }
-    Ok(())
 }
 
 #[tauri::command]
-fn delete_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
-    let dir = get_profiles_dir(&app)?;
-    let json_path = dir.join(format!("{}.json", profile_name));
-    let mxml_path = dir.join(format!("{}.mxml", profile_name));
-    if json_path.exists() {
-        fs::remove_file(json_path).map_err(|e| e.to_string())?;
-    }
-    if mxml_path.exists() {
-        fs::remove_file(mxml_path).map_err(|e| e.to_string())?;
-    }
-    Ok(())
+async fn get_mod_files(app: AppHandle, mod_id: String) -> Result<ModFilesResult, String> {
+    let api_key = load_api_key(&app)?;
+    fetch_mod_files_cached(&app, api_key.as_deref(), mod_id.trim()).await
 }
 
+// Stores mod entries (curated list / Nexus API shape) as they are fetched.
 #[tauri::command]
-fn rename_profile(app: AppHandle, old_name: String, new_name: String) -> Result<(), String> {
-    let dir = get_profiles_dir(&app)?;
-    let old_json = dir.join(format!("{}.json", old_name));
-    let old_mxml = dir.join(format!("{}.mxml", old_name));
-    let new_json = dir.join(format!("{}.json", new_name));
-    let new_mxml = dir.join(format!("{}.mxml", new_name));
+fn cache_mod_metadata(app: AppHandle, entries: Vec<Value>) -> Result<usize, String> {
+    let now = Utc::now().timestamp();
+    let mut stored = 0;
 
-    if old_json.exists() {
-        fs::rename(old_json, new_json).map_err(|e| e.to_string())?;
-    }
-    if old_mxml.exists() {
-        fs::rename(old_mxml, new_mxml).map_err(|e| e.to_string())?;
+    for mut info in entries {
+        let Some(mod_id) = json_id_to_string(info.get("mod_id")) else {
+            continue;
+        };
+        let mut entry = read_metadata_cache(&app, &mod_id)?;
+
+        if let Some(files) = info.as_object_mut().and_then(|obj| obj.remove("files")) {
+            if let Some(files) = files.as_array() {
+                store_cached_files(&mut entry, files, now);
+            }
+        }
+        entry.info = Some(info);
+        entry.info_fetched_at = now;
+        write_metadata_cache(&app, &entry)?;
+        stored += 1;
     }
-    Ok(())
+    Ok(stored)
 }
 
+// Returns cached entries in the curated list shape, with `files` folded back in.
+// Used when the curated list can't be loaded, so installed mods keep their names and authors.
 #[tauri::command]
-fn create_empty_profile(app: AppHandle, profile_name: String) -> Result<(), String> {
-    let dir = get_profiles_dir(&app)?;
-    let json_path = dir.join(format!("{}.json", profile_name));
-    let mxml_path = dir.join(format!("{}.mxml", profile_name));
-
-    if json_path.exists() {
-        return Err("Profile already exists".to_string());
-    }
-
-    // 1. Create Empty Mod List (JSON)
-    let empty_data = ModProfileData {
-        name: profile_name,
-        mods: Vec::new(),
+fn get_cached_mod_metadata(
+    app: AppHandle,
+    mod_ids: Option<Vec<String>>,
+) -> Result<Vec<Value>, String> {
+    let dir = get_metadata_cache_dir(&app, "mods")?;
+    let mod_ids: Vec<String> = match mod_ids {
+        Some(ids) => ids,
+        None => fs::read_dir(&dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(".json").map(String::from)
+            })
+            .collect(),
     };
-    let json_str = serde_json::to_string_pretty(&empty_data).map_err(|e| e.to_string())?;
-    fs::write(&json_path, json_str).map_err(|e| e.to_string())?;
 
-    // 2. Create Clean GCMODSETTINGS (MXML)
-    fs::write(&mxml_path, CLEAN_MXML_TEMPLATE).map_err(|e| e.to_string())?;
+    let now = Utc::now().timestamp();
+    let mut results = Vec::new();
+    for mod_id in mod_ids {
+        let entry = read_metadata_cache(&app, mod_id.trim())?;
+        let Some(Value::Object(mut info)) = entry.info.clone() else {
+            continue;
+        };
+        info.insert(
+            "files".to_string(),
+            Value::Array(cached_files_sorted(&entry)),
+        );
+        info.insert(
+            "cache_stale".to_string(),
+            Value::Bool(now - entry.info_fetched_at >= MOD_INFO_TTL_SECS),
+        );
+        results.push(Value::Object(info));
+    }
+    Ok(results)
+}
 
-    Ok(())
+fn sniff_image_content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else if bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
 }
 
+// Thumbnails are cached by URL hash; they rarely change, so the TTL is long.
 #[tauri::command]
-fn check_for_untracked_mods() -> bool {
-    if let Some(game_path) = find_game_path() {
-        let mods_path = game_path.join("GAMEDATA").join("MODS");
-        if let Ok(entries) = fs::read_dir(mods_path) {
-            for entry in entries.flatten() {
-                if entry.path().is_dir() {
-                    let info_path = entry.path().join("mod_info.json");
+async fn get_cached_image(app: AppHandle, url: String) -> Result<HttpResponse, String> {
+    let key = hex::encode(Sha256::digest(url.as_bytes()));
+    let path = get_metadata_cache_dir(&app, "images")?.join(format!("{}.img", key));
 
-                    if !info_path.exists() {
-                        return true; // No file = Untracked
-                    }
-
-                    if let Ok(content) = fs::read_to_string(&info_path) {
-                        if let Ok(json) = serde_json::from_str::<Value>(&content) {
-                            // STRICT CHECK:
-                            // 1. Get "installSource"
-                            // 2. Ensure it is a String (not null/number)
-                            // 3. Ensure it is NOT empty
-                            let is_valid_source = json
-                                .get("installSource")
-                                .and_then(|v| v.as_str())
-                                .map(|s| !s.is_empty())
-                                .unwrap_or(false); // If key missing or not string, defaults to false
+    let cached = fs::read(&path).ok();
+    let age = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .map(|d| d.as_secs());
+
+    let bytes = match (cached, age) {
+        (Some(bytes), Some(age)) if age < IMAGE_TTL_SECS => bytes,
+        (cached, _) => {
+            let fetched = async {
+                let response = reqwest::get(&url)
+                    .await
+                    .map_err(|e| format!("HTTP request failed: {}", e))?;
+                if !response.status().is_success() {
+                    return Err(format!("HTTP status: {}", response.status()));
+                }
+                response
+                    .bytes()
+                    .await
+                    .map(|b| b.to_vec())
+                    .map_err(|e| format!("Failed to read response bytes: {}", e))
+            }
+            .await;
 
-                            if !is_valid_source {
-                                return true;
-                            }
-                        } else {
-                            return true; // Invalid JSON
-                        }
-                    } else {
-                        return true; // Read error
+            match (fetched, cached) {
+                (Ok(bytes), _) => {
+                    if let Err(e) = fs::write(&path, &bytes) {
+                        log_internal(
+                            &app,
+                            "WARN",
+                            &format!("Failed to cache image {}: {}", url, e),
+                        );
                     }
+                    bytes
                 }
+                (Err(_), Some(bytes)) => bytes,
+                (Err(e), None) => return Err(e),
             }
         }
-    }
-    false
-}
-
-#[tauri::command]
-fn get_profile_mod_list(app: AppHandle, profile_name: String) -> Result<Vec<String>, String> {
-    let dir = get_profiles_dir(&app)?;
-    let json_path = dir.join(format!("{}.json", profile_name));
+    };
 
-    if !json_path.exists() {
-        // If profile file doesn't exist (e.g. fresh Default), return empty list
-        return Ok(Vec::new());
-    }
+    let mut headers = HashMap::new();
+    headers.insert(
+        "content-type".to_string(),
+        sniff_image_content_type(&bytes).to_string(),
+    );
+    Ok(HttpResponse {
+        status: 200,
+        status_text: "OK".to_string(),
+        body: general_purpose::STANDARD.encode(&bytes),
+        headers,
+    })
+}
 
-    let content = fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
-    let data: ModProfileData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+// --- NEXUS SSO LOGIN ---
+const NEXUS_SSO_URL: &str = "wss://sso.nexusmods.com";
+const SSO_DEFAULT_TIMEOUT_SECS: u64 = 300;
+const SSO_MAX_RECONNECTS: u32 = 5;
+const SSO_RECONNECT_DELAY_SECS: u64 = 2;
+const SSO_CANCELLED: &str = "Login cancelled.";
+
+#[derive(Default)]
+struct SsoLoginState {
+    // (login id, cancel signal) of the login currently waiting on the SSO socket
+    pending: Mutex<Option<(String, tokio::sync::oneshot::Sender<()>)>>,
+}
 
-    // Return just the list of filenames
-    let filenames = data.mods.iter().map(|m| m.filename.clone()).collect();
-    Ok(filenames)
+#[derive(Serialize, Clone)]
+struct SsoLoginProgress {
+    state: String,
+    message: Option<String>,
 }
 
-#[tauri::command]
-fn copy_profile(app: AppHandle, source_name: String, new_name: String) -> Result<(), String> {
-    let dir = get_profiles_dir(&app)?;
-    let source_json = dir.join(format!("{}.json", source_name));
-    let source_mxml = dir.join(format!("{}.mxml", source_name));
+fn emit_sso_progress(app: &AppHandle, state: &str, message: Option<String>) {
+    let _ = app.emit(
+        "nexus-login-progress",
+        SsoLoginProgress {
+            state: state.to_string(),
+            message,
+        },
+    );
+}
 
-    let new_json = dir.join(format!("{}.json", new_name));
-    let new_mxml = dir.join(format!("{}.mxml", new_name));
+// Waits for the browser to hand back an API key over the SSO socket. If the socket drops after
+// Nexus has issued a connection token, the same session is resumed with a fresh handshake.
+async fn run_sso_login(
+    app: &AppHandle,
+    timeout: std::time::Duration,
+    cancel_rx: &mut tokio::sync::oneshot::Receiver<()>,
+) -> Result<String, String> {
+    let uuid = Uuid::new_v4().to_string();
+    let sso_url =
+        Url::parse(NEXUS_SSO_URL).map_err(|e| format!("Failed to parse WebSocket URL: {}", e))?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    let timeout_err = format!(
+        "Login timed out after {} seconds without a response from the browser.",
+        timeout.as_secs()
+    );
 
-    if new_json.exists() {
-        return Err("A profile with that name already exists.".to_string());
-    }
-    if !source_json.exists() {
-        return Err("Source profile not found.".to_string());
-    }
+    let mut connection_token: Option<String> = None;
+    let mut reconnects: u32 = 0;
+    let mut browser_opened = false;
 
-    // 1. Copy the JSON file
-    fs::copy(&source_json, &new_json).map_err(|e| format!("Failed to copy JSON: {}", e))?;
+    loop {
+        let drop_reason = 'session: {
+            if reconnects == 0 {
+                emit_sso_progress(app, "connecting", None);
+                log_internal(app, "INFO", &format!("Connecting to: {}", sso_url));
+            } else {
+                emit_sso_progress(
+                    app,
+                    "reconnecting",
+                    Some(format!("Attempt {} of {}", reconnects, SSO_MAX_RECONNECTS)),
+                );
+                log_internal(
+                    app,
+                    "WARN",
+                    &format!("Reconnecting to Nexus SSO (attempt {})", reconnects),
+                );
+            }
 
-    // 2. Open the new JSON and update the "name" field inside it
-    // (Otherwise the copied profile will internally claim to be the old profile)
-    let content = fs::read_to_string(&new_json).map_err(|e| e.to_string())?;
-    let mut data: ModProfileData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    data.name = new_name.clone();
+            let connected = tokio::select! {
+                _ = &mut *cancel_rx => return Err(SSO_CANCELLED.to_string()),
+                _ = tokio::time::sleep_until(deadline) => return Err(timeout_err),
+                res = connect_async(sso_url.to_string()) => res,
+            };
+            let ws_stream = match connected {
+                Ok((ws_stream, _)) => ws_stream,
+                Err(e) => break 'session format!("Failed to connect to Nexus WebSocket: {}", e),
+            };
 
-    let new_content = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    fs::write(&new_json, new_content).map_err(|e| e.to_string())?;
+            log_internal(app, "INFO", "WebSocket connection established");
+            let (mut write, mut read) = ws_stream.split();
 
-    // 3. Copy the MXML file
-    if source_mxml.exists() {
-        fs::copy(&source_mxml, &new_mxml).map_err(|e| format!("Failed to copy MXML: {}", e))?;
-    } else {
-        // If source had no MXML (rare), make a clean one
-        fs::write(&new_mxml, CLEAN_MXML_TEMPLATE).map_err(|e| e.to_string())?;
-    }
+            // A null token starts a new session; the connection token resumes the existing one.
+            let msg = serde_json::json!({
+                "id": uuid,
+                "token": connection_token,
+                "protocol": 2
+            });
+            if let Err(e) = write.send(Message::Text(msg.to_string().into())).await {
+                break 'session format!("Failed to send handshake: {}", e);
+            }
 
-    Ok(())
-}
+            if !browser_opened {
+                let auth_url = format!(
+                    "https://www.nexusmods.com/sso?id={}&application=syzzle07-singularity",
+                    uuid
+                );
+                open::that(auth_url).map_err(|e| e.to_string())?;
+                browser_opened = true;
+            }
+            emit_sso_progress(app, "waiting_for_browser", None);
 
-#[tauri::command]
-async fn login_to_nexus(app: tauri::AppHandle) -> Result<String, String> {
-    log_internal(&app, "INFO", "Starting Nexus login process...");
+            loop {
+                let next = tokio::select! {
+                    _ = &mut *cancel_rx => return Err(SSO_CANCELLED.to_string()),
+                    _ = tokio::time::sleep_until(deadline) => return Err(timeout_err),
+                    next = read.next() => next,
+                };
 
-    // 1. Generate a unique Request ID (UUID)
-    let uuid = Uuid::new_v4().to_string();
+                let text = match next {
+                    None => break 'session "Connection closed by Nexus.".to_string(),
+                    Some(Err(e)) => break 'session e.to_string(),
+                    Some(Ok(Message::Close(_))) => {
+                        break 'session "Connection closed by Nexus.".to_string()
+                    }
+                    Some(Ok(Message::Text(text))) => text.to_string(),
+                    Some(Ok(_)) => continue,
+                };
 
-    // 2. Construct the WebSocket URL
-    let sso_url = Url::parse("wss://sso.nexusmods.com").map_err(|e| {
-        let err = format!("Failed to parse WebSocket URL: {}", e);
-        log_internal(&app, "ERROR", &err);
-        err
-    })?;
+                let response: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
 
-    log_internal(&app, "INFO", &format!("Connecting to: {}", sso_url));
+                if response.get("success").and_then(|s| s.as_bool()) == Some(false) {
+                    let reason = response
+                        .get("error")
+                        .and_then(|e| e.as_str())
+                        .unwrap_or("unknown error");
+                    return Err(format!("Nexus refused the connection: {}", reason));
+                }
 
-    // 3. Open the connection
-    let (ws_stream, _) = connect_async(sso_url.to_string())
-        .await
-        .map_err(|e| {
-            let err = format!("Failed to connect to Nexus WebSocket: {}", e);
-            log_internal(&app, "ERROR", &err);
-            err
-        })?;
+                if let Some(data) = response.get("data") {
+                    if let Some(token) = data.get("connection_token").and_then(|t| t.as_str()) {
+                        connection_token = Some(token.to_string());
+                    }
+                    if let Some(api_key) = data.get("api_key").and_then(|k| k.as_str()) {
+                        return Ok(api_key.to_string());
+                    }
+                }
+            }
+        };
 
-    log_internal(&app, "INFO", "WebSocket connection established");
+        // Without a connection token there is no session to resume.
+        if connection_token.is_none() || reconnects >= SSO_MAX_RECONNECTS {
+            return Err(drop_reason);
+        }
+        log_internal(
+            app,
+            "WARN",
+            &format!("Nexus SSO connection lost: {}", drop_reason),
+        );
+        reconnects += 1;
 
-    let (mut write, mut read) = ws_stream.split();
+        tokio::select! {
+            _ = &mut *cancel_rx => return Err(SSO_CANCELLED.to_string()),
+            _ = tokio::time::sleep_until(deadline) => return Err(timeout_err),
+            _ = tokio::time::sleep(std::time::Duration::from_secs(SSO_RECONNECT_DELAY_SECS)) => {}
+        }
+    }
+}
 
-    // 4. Send the Handshake
-    let msg = serde_json::json!({
-        "id": uuid,
-        "token": null,
-        "protocol": 2
-    });
+#[tauri::command]
+async fn login_to_nexus(
+    app: tauri::AppHandle,
+    state: State<'_, SsoLoginState>,
+    timeout_secs: Option<u64>,
+) -> Result<String, String> {
+    log_internal(&app, "INFO", "Starting Nexus login process...");
 
-    log_internal(&app, "INFO", &format!("Sending handshake: {}", msg));
+    let login_id = Uuid::new_v4().to_string();
+    let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel();
+    let previous = state
+        .pending
+        .lock()
+        .unwrap()
+        .replace((login_id.clone(), cancel_tx));
+    if let Some((_, previous_tx)) = previous {
+        log_internal(&app, "INFO", "Cancelling previous login attempt.");
+        let _ = previous_tx.send(());
+    }
 
-    write
-        .send(Message::Text(msg.to_string().into()))
-        .await
-        .map_err(|e| {
-            let err = format!("Failed to send handshake: {}", e);
-            log_internal(&app, "ERROR", &err);
-            err
-        })?;
+    let timeout =
+        std::time::Duration::from_secs(timeout_secs.unwrap_or(SSO_DEFAULT_TIMEOUT_SECS).max(1));
+    let result = run_sso_login(&app, timeout, &mut cancel_rx).await;
 
-    // 5. Open the User's Browser to authorize
-    let auth_url = format!(
-        "https://www.nexusmods.com/sso?id={}&application=syzzle07-singularity",
-        uuid
-    );
-    open::that(auth_url).map_err(|e| e.to_string())?;
-
-    // 6. Wait for the response (The API Key)
-    while let Some(message) = read.next().await {
-        let message = message.map_err(|e| e.to_string())?;
-
-        if let Message::Text(text) = message {
-            let text_str = text.to_string();
-            let response: Value = serde_json::from_str(&text_str).map_err(|e| e.to_string())?;
-
-            // Check for success data
-            if let Some(data) = response.get("data") {
-                if let Some(api_key) = data.get("api_key").and_then(|k| k.as_str()) {
-                    // 7. SAVE THE KEY (AppData)
-                    let auth_path = get_auth_file_path(&app)?;
-                    let auth_data = serde_json::json!({ "apikey": api_key });
-
-                    fs::write(
-                        &auth_path,
-                        serde_json::to_string_pretty(&auth_data).unwrap(),
-                    )
-                    .map_err(|e| format!("Failed to save auth file: {}", e))?;
-
-                    println!("API Key saved to: {:?}", auth_path);
-                    return Ok(api_key.to_string());
-                }
-            }
+    {
+        let mut pending = state.pending.lock().unwrap();
+        if pending.as_ref().is_some_and(|(id, _)| *id == login_id) {
+            *pending = None;
+        }
+    }
 
-            // Check for errors
-            if let Some(success) = response.get("success").and_then(|s| s.as_bool()) {
-                if !success {
-                    return Err("Nexus refused the connection.".to_string());
+    match result {
+        Ok(api_key) => {
+            // SAVE THE KEY (Secret Store)
+            match store_api_key(&app, &api_key) {
+                Ok(true) => {}
+                Ok(false) => emit_sso_progress(&app, "stored_obfuscated", None),
+                Err(e) => {
+                    emit_sso_progress(&app, "failed", Some(e.clone()));
+                    return Err(e);
                 }
             }
+            emit_sso_progress(&app, "received", None);
+            log_internal(&app, "INFO", "Nexus login completed.");
+            Ok(api_key)
+        }
+        Err(e) if e == SSO_CANCELLED => {
+            emit_sso_progress(&app, "cancelled", None);
+            log_internal(&app, "INFO", "Nexus login cancelled.");
+            Err(e)
+        }
+        Err(e) => {
+            emit_sso_progress(&app, "failed", Some(e.clone()));
+            log_internal(&app, "ERROR", &format!("Nexus login failed: {}", e));
+            Err(e)
         }
     }
+}
 
-    Err("Connection closed before authentication finished.".to_string())
+#[tauri::command]
+fn cancel_login(state: State<'_, SsoLoginState>) -> bool {
+    match state.pending.lock().unwrap().take() {
+        Some((_, cancel_tx)) => cancel_tx.send(()).is_ok(),
+        None => false,
+    }
 }
 
 #[tauri::command]
 fn logout_nexus(app: tauri::AppHandle) -> Result<(), String> {
-    let auth_path = get_auth_file_path(&app)?;
-
-    if auth_path.exists() {
-        fs::remove_file(auth_path).map_err(|e| e.to_string())?;
-        println!("Logged out. Auth file deleted.");
-    }
+    delete_api_key(&app)?;
+    log_internal(&app, "INFO", "Logged out. Nexus API key removed.");
     Ok(())
 }
 
@@ -2932,24 +8777,22 @@ async fn set_downloads_path(app: AppHandle, new_path: String) -> Result<(), Stri
     }
 
     let config_path = get_config_file_path(&app)?;
+    let _guard = lock_config();
     let mut config = if config_path.exists() {
         let c = fs::read_to_string(&config_path).unwrap_or_default();
         serde_json::from_str(&c).unwrap_or(GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
             legacy_migration_done: true,
+            ..Default::default()
         })
     } else {
         GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
             legacy_migration_done: true,
+            ..Default::default()
         }
     };
 
     config.custom_download_path = Some(target_path.to_string_lossy().into_owned());
-    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(config_path, json).map_err(|e| e.to_string())?;
+    write_config_file(&config_path, &config)?;
 
     log_internal(&app, "INFO", "Downloads path updated successfully.");
     Ok(())
@@ -3046,28 +8889,8 @@ fn get_staging_contents(
 
 #[tauri::command]
 async fn run_legacy_migration(app: AppHandle) -> Result<(), String> {
-    let config_path = get_config_file_path(&app)?;
-
     // 1. Load Config
-    let mut config: GlobalAppConfig = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-
-        // FIX 1: Added custom_library_path here
-        serde_json::from_str(&content).unwrap_or(GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
-            legacy_migration_done: false,
-        })
-    } else {
-        // FIX 2: Added custom_library_path here
-        GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
-            legacy_migration_done: false,
-        }
-    };
-
-    if config.legacy_migration_done {
+    if load_app_config(&app).legacy_migration_done {
         return Ok(());
     }
 
@@ -3104,7 +8927,7 @@ async fn run_legacy_migration(app: AppHandle) -> Result<(), String> {
     );
 
     // 3. Scan Installed Mods in Game Folder
-    if let Some(game_path) = find_game_path() {
+    if let Ok(GameContext { game_path, .. }) = game_context(&app) {
         let mods_path = game_path.join("GAMEDATA/MODS");
 
         if let Ok(entries) = fs::read_dir(mods_path) {
@@ -3164,9 +8987,8 @@ async fn run_legacy_migration(app: AppHandle) -> Result<(), String> {
     }
 
     // 4. Update Config
-    config.legacy_migration_done = true;
-    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(config_path, json).map_err(|e| e.to_string())?;
+    // Re-read: other settings may have changed while the migration ran
+    update_app_config(&app, |config| config.legacy_migration_done = true)?;
 
     Ok(())
 }
@@ -3215,24 +9037,22 @@ async fn set_library_path(app: AppHandle, new_path: String) -> Result<(), String
     }
 
     let config_path = get_config_file_path(&app)?;
+    let _guard = lock_config();
     let mut config = if config_path.exists() {
         let c = fs::read_to_string(&config_path).unwrap_or_default();
         serde_json::from_str(&c).unwrap_or(GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
             legacy_migration_done: true,
+            ..Default::default()
         })
     } else {
         GlobalAppConfig {
-            custom_download_path: None,
-            custom_library_path: None,
             legacy_migration_done: true,
+            ..Default::default()
         }
     };
 
     config.custom_library_path = Some(target_path.to_string_lossy().into_owned());
-    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(config_path, json).map_err(|e| e.to_string())?;
+    write_config_file(&config_path, &config)?;
 
     Ok(())
 }
@@ -3261,11 +9081,203 @@ fn check_library_existence(
     Ok(results)
 }
 
+// --- NXM LINKS ---
+
+const NEXUS_GAME_DOMAIN: &str = "nomanssky";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NxmTarget {
+    ModFile { mod_id: u64, file_id: u64 },
+    Collection { slug: String, revision: u32 },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct NxmLink {
+    game_domain: String,
+    target: NxmTarget,
+    key: Option<String>,
+    expires: Option<i64>,
+    user_id: Option<u64>,
+}
+
+// A link waiting for the frontend, or the reason it was rejected
+#[derive(Serialize, Clone, Debug)]
+struct NxmIntent {
+    link: String,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct NxmQueue {
+    ui_ready: bool,
+    pending: Vec<NxmIntent>,
+}
+
+// nxm://{game}/mods/{mod_id}/files/{file_id}?key=..&expires=..&user_id=..
+// nxm://{game}/collections/{slug}/revisions/{revision}
+fn parse_nxm_link(link: &str) -> Result<NxmLink, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("Invalid Nexus link: {}", e))?;
+    if url.scheme() != "nxm" {
+        return Err("Not an nxm:// link.".to_string());
+    }
+
+    let game_domain = url
+        .host_str()
+        .filter(|h| !h.is_empty())
+        .ok_or("The Nexus link has no game.")?
+        .to_lowercase();
+
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|s| s.filter(|seg| !seg.is_empty()).collect())
+        .unwrap_or_default();
+
+    let target = match segments.as_slice() {
+        ["mods", mod_id, "files", file_id] => NxmTarget::ModFile {
+            mod_id: mod_id
+                .parse()
+                .map_err(|_| format!("Invalid mod ID in Nexus link: {}", mod_id))?,
+            file_id: file_id
+                .parse()
+                .map_err(|_| format!("Invalid file ID in Nexus link: {}", file_id))?,
+        },
+        ["collections", slug, "revisions", revision] => NxmTarget::Collection {
+            slug: slug.to_string(),
+            revision: revision
+                .parse()
+                .map_err(|_| format!("Invalid collection revision: {}", revision))?,
+        },
+        _ => return Err("The received Nexus link was malformed.".to_string()),
+    };
+
+    let mut key = None;
+    let mut expires = None;
+    let mut user_id = None;
+    for (name, value) in url.query_pairs() {
+        match name.as_ref() {
+            "key" => key = Some(value.into_owned()),
+            "expires" => {
+                expires = Some(
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid expiry in Nexus link: {}", value))?,
+                )
+            }
+            "user_id" => {
+                user_id = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid user ID in Nexus link: {}", value))?,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    Ok(NxmLink {
+        game_domain,
+        target,
+        key,
+        expires,
+        user_id,
+    })
+}
+
+// Query string for download_link.json; free accounts need the key/expires pair from the link
+fn nxm_download_query(link: &NxmLink) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    if let (Some(key), Some(expires)) = (&link.key, link.expires) {
+        query.append_pair("key", key);
+        query.append_pair("expires", &expires.to_string());
+    }
+    if let Some(user_id) = link.user_id {
+        query.append_pair("user_id", &user_id.to_string());
+    }
+    query.finish()
+}
+
+fn validate_nxm_link(link: &NxmLink, now: i64) -> Result<(), String> {
+    if link.game_domain != NEXUS_GAME_DOMAIN {
+        return Err(format!(
+            "This link is for '{}', not No Man's Sky.",
+            link.game_domain
+        ));
+    }
+
+    match (&link.key, link.expires) {
+        (Some(key), Some(_)) => {
+            let valid_key = !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_key {
+                return Err("The download key in this Nexus link is invalid.".to_string());
+            }
+        }
+        (None, None) => {}
+        _ => {
+            return Err(
+                "The Nexus link must contain both a download key and an expiry.".to_string(),
+            )
+        }
+    }
+
+    if let Some(expires) = link.expires {
+        if expires <= now {
+            let when = chrono::DateTime::from_timestamp(expires, 0)
+                .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| expires.to_string());
+            return Err(format!(
+                "This download link expired on {}. Please click \"Mod Manager Download\" on Nexus again.",
+                when
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn resolve_nxm_intent(link: &str) -> NxmIntent {
+    let error = parse_nxm_link(link)
+        .and_then(|parsed| validate_nxm_link(&parsed, Utc::now().timestamp()))
+        .err();
+    NxmIntent {
+        link: link.to_string(),
+        error,
+    }
+}
+
+// Emits the link to the frontend, or queues it until the UI has asked for pending links
+fn route_nxm_link(app: &AppHandle, link: &str) {
+    let intent = resolve_nxm_intent(link);
+    if let Some(err) = &intent.error {
+        log_internal(app, "WARN", &format!("Rejected NXM link: {}", err));
+    }
+
+    if let Some(state) = app.try_state::<StartupState>() {
+        let mut queue = state.nxm_queue.lock().unwrap();
+        if !queue.ui_ready {
+            queue.pending.push(intent);
+            return;
+        }
+    }
+
+    match intent.error {
+        Some(err) => {
+            let _ = app.emit("nxm-link-error", err);
+        }
+        None => {
+            let _ = app.emit("nxm-link-received", intent.link);
+        }
+    }
+}
+
 #[tauri::command]
-fn check_startup_intent(state: State<'_, StartupState>) -> Option<String> {
-    let mut pending = state.pending_nxm.lock().unwrap();
-    // Return the link and clear it so it doesn't trigger twice
-    pending.take()
+fn check_startup_intent(state: State<'_, StartupState>) -> Vec<NxmIntent> {
+    let mut queue = state.nxm_queue.lock().unwrap();
+    // From now on links go straight to the frontend as events
+    queue.ui_ready = true;
+    std::mem::take(&mut queue.pending)
 }
 
 #[tauri::command]
@@ -3391,12 +9403,16 @@ fn main() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
         .manage(StartupState {
-            pending_nxm: Mutex::new(None),
+            nxm_queue: Mutex::new(NxmQueue::default()),
         })
+        .manage(SsoLoginState::default())
+        .manage(ApiKeyState::default())
+        .manage(GameContextState::default())
+        .manage(GameProcessState::default())
         .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
             println!("New instance detected, args: {:?}", argv);
-            if let Some(nxm_link) = argv.iter().find(|arg| arg.starts_with("nxm://")) {
-                app.emit("nxm-link-received", nxm_link.clone()).unwrap();
+            for nxm_link in argv.iter().filter(|arg| arg.starts_with("nxm://")) {
+                route_nxm_link(app, nxm_link);
             }
             if let Some(window) = app.get_webview_window("main") {
                 window.unminimize().unwrap();
@@ -3411,6 +9427,23 @@ fn main() {
 
             log_internal(app_handle, "INFO", "=== SINGULARITY MANAGER STARTUP ===");
 
+            start_game_monitor(app_handle.clone());
+
+            if let Err(e) = migrate_profile_sidecars(app_handle) {
+                log_internal(
+                    app_handle,
+                    "WARN",
+                    &format!("Profile migration skipped: {}", e),
+                );
+            }
+            if let Err(e) = recover_interrupted_apply(app_handle) {
+                log_internal(
+                    app_handle,
+                    "WARN",
+                    &format!("Rollback recovery skipped: {}", e),
+                );
+            }
+
             // Expand fs scope for app data directories
             if let Ok(app_data) = app_handle.path().app_data_dir() {
                 let _ = app_handle.fs_scope().allow_directory(&app_data, true);
@@ -3421,16 +9454,14 @@ fn main() {
 
             let args: Vec<String> = std::env::args().collect();
 
-            // 2. Capture Cold Start Link
-            if let Some(nxm_link) = args.iter().find(|arg| arg.starts_with("nxm://")) {
+            // 2. Capture Cold Start Links (queued until the frontend is ready)
+            for nxm_link in args.iter().filter(|arg| arg.starts_with("nxm://")) {
                 log_internal(
                     app_handle,
                     "INFO",
                     &format!("Startup Argument detected (NXM Link): {}", nxm_link),
                 );
-                if let Some(state) = app.try_state::<StartupState>() {
-                    *state.pending_nxm.lock().unwrap() = Some(nxm_link.clone());
-                }
+                route_nxm_link(app_handle, nxm_link);
             }
 
             let window = app.get_webview_window("main").unwrap();
@@ -3526,6 +9557,11 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             check_startup_intent,
             detect_game_installation,
+            list_game_installations,
+            set_active_installation,
+            set_game_path,
+            remove_game_installation,
+            set_profile_installation,
             open_mods_folder,
             save_file,
             delete_settings_file,
@@ -3547,16 +9583,43 @@ fn main() {
             delete_archive_file,
             clear_downloads_folder,
             launch_game,
+            get_launch_strategy,
+            validate_launch,
+            get_game_status,
+            get_play_sessions,
+            start_mod_bisect,
+            get_bisect_status,
+            record_bisect_result,
+            finish_mod_bisect,
+            list_save_backups,
+            create_save_backup,
+            restore_save_backup,
+            export_profile,
+            import_profile,
+            diff_profiles,
+            set_launch_strategy,
             list_profiles,
             save_active_profile,
             apply_profile,
+            validate_profile,
+            check_profile_sync,
+            list_game_settings_files,
+            get_profile_settings_files,
+            set_profile_settings_files,
+            get_profile_parent,
+            set_profile_parent,
             delete_profile,
             rename_profile,
             create_empty_profile,
             check_for_untracked_mods,
             get_profile_mod_list,
             copy_profile,
+            get_mod_files,
+            cache_mod_metadata,
+            get_cached_mod_metadata,
+            get_cached_image,
             login_to_nexus,
+            cancel_login,
             logout_nexus,
             get_downloads_path,
             set_downloads_path,
@@ -3573,8 +9636,1279 @@ fn main() {
             check_library_existence,
             rename_mod_folder,
             is_app_installed,
-            http_request
+            http_request,
+            import_nexus_collection,
+            import_collection_manifest
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION_FIXTURE: &str = include_str!("../tests/fixtures/nexus_collection.json");
+
+    fn fixture_file_lookup() -> HashMap<(String, String), NexusFileInfo> {
+        let mut files = HashMap::new();
+        files.insert(
+            ("101".to_string(), "1001".to_string()),
+            NexusFileInfo {
+                file_name: "Better Scanner-101-1-2-0-1700000000.zip".to_string(),
+                name: Some("Better Scanner".to_string()),
+                version: Some("1.2.0".to_string()),
+            },
+        );
+        files.insert(
+            ("202".to_string(), "2002".to_string()),
+            NexusFileInfo {
+                file_name: "Fast Refiners-202-3-0-1700000001.zip".to_string(),
+                name: None,
+                version: None,
+            },
+        );
+        files
+    }
+
+    #[test]
+    fn parses_collection_links() {
+        let link = parse_nxm_link("nxm://nomanssky/collections/abc123/revisions/7").unwrap();
+        assert_eq!(
+            link.target,
+            NxmTarget::Collection {
+                slug: "abc123".to_string(),
+                revision: 7
+            }
+        );
+        assert!(parse_nxm_link("nxm://nomanssky/collections/abc123").is_err());
+        assert!(parse_nxm_link("nxm://nomanssky/collections/abc123/revisions/latest").is_err());
+    }
+
+    #[test]
+    fn parses_mod_file_links() {
+        let link = parse_nxm_link(
+            "nxm://nomanssky/mods/123/files/456?key=AbC-12_x&expires=1700000000&user_id=789",
+        )
+        .unwrap();
+        assert_eq!(link.game_domain, "nomanssky");
+        assert_eq!(
+            link.target,
+            NxmTarget::ModFile {
+                mod_id: 123,
+                file_id: 456
+            }
+        );
+        assert_eq!(link.key.as_deref(), Some("AbC-12_x"));
+        assert_eq!(link.expires, Some(1_700_000_000));
+        assert_eq!(link.user_id, Some(789));
+
+        assert!(parse_nxm_link("https://nomanssky/mods/123/files/456").is_err());
+        assert!(parse_nxm_link("nxm://nomanssky/mods/abc/files/456").is_err());
+        assert!(parse_nxm_link("nxm://nomanssky/mods/123/files/456?expires=soon").is_err());
+    }
+
+    #[test]
+    fn validates_nxm_links() {
+        let now = 1_700_000_000;
+        let valid =
+            parse_nxm_link("nxm://nomanssky/mods/1/files/2?key=abc&expires=1700000100&user_id=3")
+                .unwrap();
+        assert!(validate_nxm_link(&valid, now).is_ok());
+        assert!(validate_nxm_link(&valid, 1_700_000_100).is_err());
+
+        let premium = parse_nxm_link("nxm://nomanssky/mods/1/files/2").unwrap();
+        assert!(validate_nxm_link(&premium, now).is_ok());
+
+        let other_game = parse_nxm_link("nxm://skyrim/mods/1/files/2").unwrap();
+        assert!(validate_nxm_link(&other_game, now)
+            .unwrap_err()
+            .contains("skyrim"));
+
+        let key_only = parse_nxm_link("nxm://nomanssky/mods/1/files/2?key=abc").unwrap();
+        assert!(validate_nxm_link(&key_only, now).is_err());
+
+        let bad_key =
+            parse_nxm_link("nxm://nomanssky/mods/1/files/2?key=a%20b&expires=1700000100").unwrap();
+        assert!(validate_nxm_link(&bad_key, now).is_err());
+
+        let collection = parse_nxm_link(
+            "nxm://nomanssky/collections/abc123/revisions/4?key=abc&expires=1700000100",
+        )
+        .unwrap();
+        assert_eq!(
+            nxm_download_query(&collection),
+            "key=abc&expires=1700000100"
+        );
+        assert_eq!(nxm_download_query(&premium), "");
+    }
+
+    #[test]
+    fn plans_collection_import_from_fixture() {
+        let manifest: CollectionManifest = serde_json::from_str(COLLECTION_FIXTURE).unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let downloads_dir = temp.path();
+        fs::write(
+            downloads_dir.join("Fast Refiners-202-3-0-1700000001.zip"),
+            b"",
+        )
+        .unwrap();
+
+        let plan =
+            plan_collection_import(&manifest, &fixture_file_lookup(), downloads_dir).unwrap();
+
+        let filenames: Vec<&str> = plan.entries.iter().map(|e| e.filename.as_str()).collect();
+        assert_eq!(
+            filenames,
+            [
+                "Better Scanner-101-1-2-0-1700000000.zip",
+                "Fast Refiners-202-3-0-1700000001.zip"
+            ]
+        );
+        assert_eq!(plan.entries[1].version.as_deref(), Some("3.0"));
+
+        assert_eq!(plan.queued.len(), 1);
+        assert_eq!(plan.queued[0].mod_id, "101");
+        assert_eq!(plan.queued[0].file_id, "1001");
+        assert_eq!(
+            plan.already_downloaded,
+            ["Fast Refiners-202-3-0-1700000001.zip"]
+        );
+        assert_eq!(plan.skipped.len(), 2);
+
+        let xml = serialize_settings_xml(&plan.settings).unwrap();
+        let root: SettingsData = from_str(&xml).unwrap();
+        let data = root.properties.iter().find(|p| p.name == "Data").unwrap();
+        let order: Vec<(String, String)> = data
+            .mods
+            .iter()
+            .map(|m| {
+                let get = |name: &str| {
+                    m.properties
+                        .iter()
+                        .find(|p| p.name == name)
+                        .and_then(|p| p.value.clone())
+                        .unwrap_or_default()
+                };
+                (get("Name"), get("Enabled"))
+            })
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("FASTREFINERS".to_string(), "true".to_string()),
+                ("BETTERSCANNER".to_string(), "true".to_string()),
+                ("BETTERSCANNER_OPTIONAL".to_string(), "false".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_collections_for_other_games() {
+        let manifest: CollectionManifest = serde_json::from_str(
+            r#"{ "info": { "name": "Other", "domainName": "skyrimspecialedition" }, "mods": [] }"#,
+        )
+        .unwrap();
+        assert!(plan_collection_import(&manifest, &HashMap::new(), &env::temp_dir()).is_err());
+    }
+
+    #[test]
+    fn parses_library_folders_vdf() {
+        let vdf = parse_vdf(
+            r#"
+"libraryfolders"
+{
+    // comment lines are ignored
+    "0"
+    {
+        "path"      "/home/deck/.local/share/Steam"
+        "apps" { "275850" "15000000000" }
+    }
+    "1"
+    {
+        "path"      "/run/media/mmcblk0p1"
+        "label"     "SD \"Card\""
+    }
+    "contentstatsid"    "-123"
+}
+"#,
+        )
+        .unwrap();
+
+        let libraries = vdf.get("LibraryFolders").unwrap();
+        let paths: Vec<&str> = libraries
+            .entries()
+            .iter()
+            .filter_map(|(_, v)| v.get("path").and_then(|p| p.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            ["/home/deck/.local/share/Steam", "/run/media/mmcblk0p1"]
+        );
+        assert_eq!(
+            libraries
+                .get("1")
+                .and_then(|l| l.get("label"))
+                .and_then(|l| l.as_str()),
+            Some("SD \"Card\"")
+        );
+        assert!(parse_vdf("\"AppState\" { \"installdir\" ").is_err());
+    }
+
+    #[test]
+    fn finds_app_in_secondary_steam_library() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let library = root.join("sdcard");
+        let steamapps = library.join("steamapps");
+        fs::create_dir_all(steamapps.join("common/No Man's Sky/Binaries")).unwrap();
+        fs::write(
+            steamapps.join("appmanifest_275850.acf"),
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"275850\"\n\t\"installdir\"\t\t\"No Man's Sky\"\n}\n",
+        )
+        .unwrap();
+
+        let libraries = [root.join("empty"), library.clone()];
+        assert_eq!(
+            find_app_in_steam_libraries(&libraries, NMS_STEAM_APP_ID),
+            Some(steamapps.join("common").join("No Man's Sky"))
+        );
+        assert_eq!(find_app_in_steam_libraries(&libraries, "1"), None);
+    }
+
+    #[test]
+    fn parses_linux_launcher_records() {
+        let heroic = r#"{ "installed": [
+            { "appName": "1207658924", "install_path": "/home/deck/Games/Heroic/Witcher" },
+            { "appName": "1446213994", "install_path": "/home/deck/Games/Heroic/NMS" }
+        ] }"#;
+        assert_eq!(
+            parse_heroic_gog_installed(heroic),
+            [(
+                PathBuf::from("/home/deck/Games/Heroic/NMS"),
+                "1446213994".to_string()
+            )]
+        );
+
+        let legendary = r#"{ "Fortnite": { "title": "Fortnite", "install_path": "/games/fn" },
+            "nms": { "title": "No Man's Sky", "install_path": "/games/No Mans Sky" } }"#;
+        assert_eq!(
+            parse_legendary_installed(legendary),
+            [(PathBuf::from("/games/No Mans Sky"), "nms".to_string())]
+        );
+        assert_eq!(
+            lutris_slug_from_config_name("no-mans-sky-1690000000.yml"),
+            "no-mans-sky"
+        );
+        assert_eq!(lutris_slug_from_config_name("nms.yml"), "nms");
+
+        let lutris = "game:\n  exe: /home/deck/Games/gog/no-mans-sky/drive_c/GOG Games/No Man's Sky/Binaries/NMS.exe\n  prefix: /home/deck/Games/gog/no-mans-sky\nwine:\n  version: lutris-GE\n";
+        let root = parse_lutris_game_yaml(lutris).unwrap();
+        assert_eq!(
+            root,
+            PathBuf::from("/home/deck/Games/gog/no-mans-sky/drive_c/GOG Games/No Man's Sky")
+        );
+        assert_eq!(guess_version_type(&root), "GOG");
+        assert_eq!(
+            guess_version_type(Path::new("/mnt/steamapps/common/No Man's Sky")),
+            "Steam"
+        );
+
+        let bottle = "Name: Games\nExternal_Programs:\n  6f1c:\n    name: No Man's Sky\n    path: /bottles/Games/drive_c/GOG Games/No Man's Sky/Binaries/NMS.exe\n  91aa:\n    name: Other\n    path: /bottles/Games/drive_c/Other/Binaries/Other.exe\n";
+        assert_eq!(
+            parse_bottle_yaml(bottle),
+            [PathBuf::from(
+                "/bottles/Games/drive_c/GOG Games/No Man's Sky"
+            )]
+        );
+    }
+
+    #[test]
+    fn replaces_config_file_atomically() {
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("config.json");
+        fs::write(&config_path, "{\"active_profile\": \"Old\"}").unwrap();
+
+        let config = GlobalAppConfig {
+            active_profile: Some("New".to_string()),
+            ..Default::default()
+        };
+        write_config_file(&config_path, &config).unwrap();
+
+        let saved: GlobalAppConfig =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved.active_profile.as_deref(), Some("New"));
+        // The temporary file was renamed into place, not left behind
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn merges_detected_installations_into_registry() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let steam = root.join("steam/No Man's Sky");
+        let gog = root.join("gog/No Man's Sky");
+        fs::create_dir_all(steam.join("Binaries")).unwrap();
+        fs::create_dir_all(gog.join("Binaries")).unwrap();
+
+        let mut config = GlobalAppConfig::default();
+        config.game_installations.push(GameInstallation {
+            id: "manual01".to_string(),
+            path: root.join("unplugged").to_string_lossy().into_owned(),
+            version_type: "GOG".to_string(),
+            source: "manual".to_string(),
+            game_build: None,
+            launcher_id: None,
+            launch: None,
+        });
+        merge_detected_installations(
+            &mut config,
+            &[
+                DetectedInstall::new(steam.clone(), "Steam", "steam"),
+                DetectedInstall::new(gog.clone(), "GOG", "heroic"),
+            ],
+        );
+        assert_eq!(config.game_installations.len(), 3);
+        assert_eq!(config.active_installation.as_deref(), Some("manual01"));
+
+        // Rescans keep IDs stable and drop detected copies that disappeared
+        let gog_id = installation_id_for_path(&gog);
+        config.active_installation = Some(gog_id.clone());
+        fs::remove_dir_all(&steam).unwrap();
+        merge_detected_installations(
+            &mut config,
+            &[DetectedInstall::new(gog.clone(), "GOG", "heroic")],
+        );
+        let ids: Vec<&str> = config
+            .game_installations
+            .iter()
+            .map(|i| i.id.as_str())
+            .collect();
+        assert_eq!(ids, ["manual01", gog_id.as_str()]);
+        assert_eq!(config.active_installation, Some(gog_id.clone()));
+
+        // An unavailable active copy is kept rather than silently replaced
+        fs::remove_dir_all(&gog).unwrap();
+        merge_detected_installations(&mut config, &[]);
+        let ids: Vec<&str> = config
+            .game_installations
+            .iter()
+            .map(|i| i.id.as_str())
+            .collect();
+        assert_eq!(ids, ["manual01", gog_id.as_str()]);
+        assert_eq!(config.active_installation, Some(gog_id));
+    }
+
+    #[test]
+    fn manual_game_path_takes_priority() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let portable = root.join("NMS Portable");
+        fs::create_dir_all(portable.join("Binaries")).unwrap();
+        assert_eq!(
+            missing_game_folder_parts(&portable),
+            ["Binaries/SETTINGS", "GAMEDATA"]
+        );
+        fs::create_dir_all(portable.join("Binaries/SETTINGS")).unwrap();
+        fs::create_dir_all(portable.join("GAMEDATA")).unwrap();
+        assert!(missing_game_folder_parts(&portable).is_empty());
+
+        let detected = root.join("steamapps/common/No Man's Sky");
+        fs::create_dir_all(detected.join("Binaries")).unwrap();
+        let mut config = GlobalAppConfig::default();
+        merge_detected_installations(
+            &mut config,
+            &[DetectedInstall::new(detected, "Steam", "steam")],
+        );
+
+        let manual = add_manual_installation(&mut config, &portable, guess_version_type(&portable));
+        assert_eq!(manual.version_type, "GOG");
+        assert_eq!(config.game_installations[0].id, manual.id);
+        assert_eq!(config.active_installation, Some(manual.id.clone()));
+
+        // A later rescan must not demote or duplicate the manual entry
+        merge_detected_installations(&mut config, &[]);
+        assert_eq!(config.game_installations.len(), 2);
+        assert_eq!(config.active_installation, Some(manual.id));
+    }
+
+    #[test]
+    fn detects_game_builds() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let steam_game = root.join("steamapps/common/No Man's Sky");
+        fs::create_dir_all(steam_game.join("Binaries")).unwrap();
+        fs::write(
+            root.join("steamapps/appmanifest_275850.acf"),
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"275850\"\n\t\"buildid\"\t\t\"17512345\"\n}\n",
+        )
+        .unwrap();
+        let mut installation = GameInstallation {
+            id: "steam".to_string(),
+            path: steam_game.to_string_lossy().into_owned(),
+            version_type: "Steam".to_string(),
+            source: "steam".to_string(),
+            game_build: None,
+            launcher_id: None,
+            launch: None,
+        };
+        assert_eq!(
+            detect_game_build(&installation).as_deref(),
+            Some("17512345")
+        );
+
+        // Without store metadata the exe fingerprint is used, and it follows the exe
+        installation.version_type = "GOG".to_string();
+        assert_eq!(detect_game_build(&installation), None);
+        fs::write(steam_game.join("Binaries/NMS.exe"), b"MZ build one").unwrap();
+        let first = detect_game_build(&installation).unwrap();
+        assert!(first.starts_with("exe-"));
+        fs::write(steam_game.join("Binaries/NMS.exe"), b"MZ build two!").unwrap();
+        assert_ne!(detect_game_build(&installation).unwrap(), first);
+
+        assert_eq!(
+            mod_build_status(Some("100"), Some("200")),
+            Some(ModBuildStatus::DifferentBuild)
+        );
+        assert_eq!(mod_build_status(Some("200"), Some("200")), None);
+        assert_eq!(
+            mod_build_status(None, Some("200")),
+            Some(ModBuildStatus::Unrecorded)
+        );
+        assert_eq!(mod_build_status(Some("100"), None), None);
+    }
+
+    #[test]
+    fn expands_custom_launch_commands() {
+        let game_path = Path::new("/games/No Man's Sky");
+        let args = launch_template_args(
+            r#"PROTON_LOG=1 gamemoderun wine "{exe}" -dx12 'two words'"#,
+            game_path,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "PROTON_LOG=1",
+                "gamemoderun",
+                "wine",
+                "/games/No Man's Sky/Binaries/NMS.exe",
+                "-dx12",
+                "two words"
+            ]
+        );
+        // Unquoted placeholders must survive the apostrophe in the path
+        assert_eq!(
+            launch_template_args("wine {exe}", game_path).unwrap(),
+            ["wine", "/games/No Man's Sky/Binaries/NMS.exe"]
+        );
+        assert_eq!(
+            parse_env_assignment(&args[0]),
+            Some(("PROTON_LOG".to_string(), "1".to_string()))
+        );
+        assert_eq!(parse_env_assignment("/usr/bin/wine"), None);
+        assert_eq!(parse_env_assignment("--opt=value"), None);
+        assert!(split_command_line("wine \"unterminated").is_err());
+
+        let strategy: LaunchStrategy =
+            serde_json::from_str(r#"{ "type": "lutris_rungame", "game": "no-mans-sky" }"#).unwrap();
+        assert_eq!(
+            strategy,
+            LaunchStrategy::LutrisRungame {
+                game: "no-mans-sky".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn reports_pre_launch_problems() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let mods_dir = root.join("GAMEDATA/MODS");
+        for dir in ["BASE/METADATA", "ADDON/METADATA", "EMPTY"] {
+            fs::create_dir_all(mods_dir.join(dir)).unwrap();
+        }
+        fs::write(mods_dir.join("BASE/METADATA/REALITY.MBIN"), "a").unwrap();
+        fs::write(mods_dir.join("ADDON/METADATA/REALITY.MBIN"), "b").unwrap();
+        // Files every mod ships that the game never loads
+        for (dir, file) in [
+            ("BASE", "readme.txt"),
+            ("ADDON", "README.TXT"),
+            ("BASE", "LICENSE"),
+            ("ADDON", "LICENSE"),
+        ] {
+            fs::write(mods_dir.join(dir).join(file), "docs").unwrap();
+        }
+        fs::write(mods_dir.join("OLD.pak"), "").unwrap();
+        fs::create_dir_all(root.join("Binaries/SETTINGS")).unwrap();
+
+        let entry = |index: usize, name: &str, enabled: bool, deps: &str| {
+            format!(
+                r#"<Property name="Data" value="GcModSettingsInfo" _index="{}"><Property name="Name" value="{}" /><Property name="Enabled" value="{}" /><Property name="Dependencies">{}</Property></Property>"#,
+                index, name, enabled, deps
+            )
+        };
+        let settings = format!(
+            r#"<Data template="GcModSettings"><Property name="DisableAllMods" value="true" /><Property name="Data">{}{}{}{}</Property></Data>"#,
+            entry(0, "BASE", true, ""),
+            entry(
+                1,
+                "ADDON",
+                true,
+                r#"<Property value="BASE" /><Property value="MISSING" />"#
+            ),
+            entry(2, "EMPTY", true, ""),
+            entry(3, "GONE", false, ""),
+        );
+        fs::write(root.join("Binaries/SETTINGS/GCMODSETTINGS.MXML"), settings).unwrap();
+
+        let report = build_launch_report(&root);
+        let codes: Vec<&str> = report.issues.iter().map(|i| i.code.as_str()).collect();
+        assert!(!report.ok);
+        assert_eq!(
+            codes,
+            [
+                "stray_pak",
+                "all_mods_disabled",
+                "mod_folder_empty",
+                "dependency_missing",
+                "file_conflict"
+            ]
+        );
+        assert!(report.issues[3].message.contains("MISSING"));
+        assert!(report.issues[4].message.contains("both change 1 file(s)"));
+
+        fs::write(root.join("Binaries/SETTINGS/GCMODSETTINGS.MXML"), "<Data").unwrap();
+        let report = build_launch_report(&root);
+        assert_eq!(report.issues.last().unwrap().code, "settings_invalid");
+    }
+
+    #[test]
+    fn detects_nms_in_process_cmdlines() {
+        let proton = b"python3\0/home/deck/.steam/steam/steamapps/common/Proton 9.0/proton\0waitforexitandrun\0/home/deck/.steam/steam/steamapps/common/No Man's Sky/Binaries/NMS.exe\0";
+        assert!(cmdline_runs_nms(proton));
+        assert!(cmdline_runs_nms(
+            b"Z:\\games\\No Man's Sky\\Binaries\\nms.exe\0-dx12\0"
+        ));
+        assert!(!cmdline_runs_nms(
+            b"/usr/bin/singularity\0--open\0NMS.exe.bak\0"
+        ));
+        assert!(!cmdline_runs_nms(b""));
+    }
+
+    #[test]
+    fn bisects_to_single_culprit() {
+        let mods: Vec<String> = ["A", "B", "C", "D", "E"]
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        let mut session = BisectSession {
+            started_at: String::new(),
+            settings_path: String::new(),
+            original_settings: String::new(),
+            testing: bisect_half(&mods),
+            suspects: mods.clone(),
+            steps: Vec::new(),
+            culprit: None,
+        };
+
+        // D is the culprit: a run crashes exactly when D is enabled
+        while !session.testing.is_empty() {
+            let crashed = session.testing.contains(&"D".to_string());
+            advance_bisect(&mut session, crashed);
+        }
+        assert_eq!(session.culprit.as_deref(), Some("D"));
+        assert_eq!(session.steps.len(), 2);
+        assert!(bisect_status(&session).finished);
+        assert_eq!(bisect_status(&session).steps_left, 0);
+
+        // A lone suspect that passes means no mod was to blame
+        let mut session = BisectSession {
+            testing: vec!["A".to_string()],
+            suspects: vec!["A".to_string()],
+            steps: Vec::new(),
+            culprit: None,
+            ..session
+        };
+        advance_bisect(&mut session, false);
+        assert!(session.testing.is_empty());
+        assert_eq!(session.culprit, None);
+
+        // A crash that needs B and D together: elimination leaves E, which is tested
+        // on its own and cleared instead of being blamed
+        let mut session = BisectSession {
+            testing: bisect_half(&mods),
+            suspects: mods.clone(),
+            steps: Vec::new(),
+            culprit: None,
+            ..session
+        };
+        while !session.testing.is_empty() {
+            let crashed = ["B", "D"]
+                .iter()
+                .all(|m| session.testing.contains(&m.to_string()));
+            advance_bisect(&mut session, crashed);
+        }
+        assert_eq!(session.culprit, None);
+        assert_eq!(session.steps.last().unwrap().enabled, ["E"]);
+    }
+
+    // %APPDATA% takes precedence on Windows
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn finds_save_dirs_in_wine_prefixes() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+
+        // Proton keeps the prefix under compatdata in the same library
+        let steamapps = root.join("steamapps");
+        let game_path = steamapps.join("common/No Man's Sky");
+        fs::create_dir_all(&game_path).unwrap();
+        assert_eq!(find_save_dir(&game_path), None);
+        let proton_saves = steamapps
+            .join("compatdata/275850/pfx/drive_c/users/steamuser/AppData/Roaming/HelloGames/NMS");
+        fs::create_dir_all(&proton_saves).unwrap();
+        assert_eq!(find_save_dir(&game_path), Some(proton_saves));
+
+        // Lutris-style install inside its own prefix
+        let drive_c = root.join("lutris/no-mans-sky/drive_c");
+        let game_path = drive_c.join("GOG Games/No Man's Sky");
+        let wine_saves = drive_c.join("users/player/AppData/Roaming/HelloGames/NMS");
+        fs::create_dir_all(&game_path).unwrap();
+        fs::create_dir_all(&wine_saves).unwrap();
+        assert_eq!(find_save_dir(&game_path), Some(wine_saves));
+    }
+
+    #[test]
+    fn migrates_sidecar_load_order_into_profile() {
+        let entry = |filename: &str, folders: &[&str]| ProfileModEntry {
+            filename: filename.to_string(),
+            mod_id: None,
+            file_id: None,
+            version: None,
+            installed_options: Some(folders.iter().map(|f| f.to_string()).collect()),
+            folders: Vec::new(),
+            game_build: None,
+        };
+        let mut profile = ModProfileData {
+            name: "Test".to_string(),
+            mods: vec![
+                entry("a.zip", &["Alpha"]),
+                entry("b.zip", &["Beta", "BetaExtra"]),
+            ],
+            ..Default::default()
+        };
+
+        let mut sidecar: SettingsData = from_str(CLEAN_MXML_TEMPLATE).unwrap();
+        sidecar.properties[0].value = Some("true".to_string());
+        sidecar.properties[1].mods = vec![
+            build_mod_entry(0, "BetaExtra", true, &BTreeMap::new()),
+            build_mod_entry(1, "Alpha", false, &BTreeMap::new()),
+            build_mod_entry(2, "Beta", true, &BTreeMap::new()),
+            build_mod_entry(3, "FromCollection", true, &BTreeMap::new()),
+        ];
+        migrate_profile_sidecar(&mut profile, &sidecar);
+
+        assert!(profile.disable_all_mods);
+        assert_eq!(
+            profile.mods[0].folders,
+            [ProfileFolderState {
+                name: "ALPHA".to_string(),
+                priority: 1,
+                enabled: false,
+                metadata: mod_entry_metadata(&build_mod_entry(1, "Alpha", false, &BTreeMap::new())),
+            }]
+        );
+        assert_eq!(profile.mods[1].folders.len(), 2);
+        assert_eq!(profile.pending_load_order[0].name, "FROMCOLLECTION");
+
+        // A folder without a stored state is appended, enabled
+        let deployed = vec![
+            (0, "Alpha".to_string()),
+            (1, "Beta".to_string()),
+            (1, "BetaExtra".to_string()),
+            (1, "NewFolder".to_string()),
+        ];
+        let settings = build_profile_settings(&profile, &deployed, None).unwrap();
+        let states = folder_states_from_settings(&settings);
+        let names: Vec<&str> = states.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["BETAEXTRA", "ALPHA", "BETA", "NEWFOLDER"]);
+        assert_eq!(
+            states.iter().map(|s| s.priority).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert!(!states[1].enabled && states[3].enabled);
+        assert!(settings_disable_all(&settings));
+    }
+
+    #[test]
+    fn keeps_mod_entry_metadata_across_apply() {
+        let metadata: BTreeMap<String, String> = [
+            ("Author", "Babscoole"),
+            ("ID", "2843"),
+            ("AuthorID", "77"),
+            ("LastUpdated", "1700000000"),
+            ("EnabledVR", "false"),
+            ("Dependencies", "CORELIB"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let mut live: SettingsData = from_str(CLEAN_MXML_TEMPLATE).unwrap();
+        live.properties[1].mods = vec![
+            build_mod_entry(0, "Scanner", true, &metadata),
+            build_mod_entry(1, "Legacy", false, &metadata),
+        ];
+
+        // Snapshot the first folder only, then apply with a new priority
+        let mut states = folder_states_from_settings(&live);
+        assert_eq!(states[0].metadata, metadata);
+        states[0].priority = 5;
+        let profile = ModProfileData {
+            mods: vec![ProfileModEntry {
+                filename: "scanner.zip".to_string(),
+                mod_id: None,
+                file_id: None,
+                version: None,
+                installed_options: None,
+                folders: vec![states[0].clone()],
+                game_build: None,
+            }],
+            ..Default::default()
+        };
+        let deployed = [(0, "Scanner".to_string()), (0, "Legacy".to_string())];
+        let settings = build_profile_settings(&profile, &deployed, Some(&live)).unwrap();
+        let entries: Vec<&ModEntry> = mod_entries(&settings).collect();
+
+        assert_eq!(mod_property_value(entries[0], "Name"), "SCANNER");
+        assert_eq!(mod_property_value(entries[0], "ModPriority"), "0");
+        assert_eq!(mod_entry_metadata(entries[0]), metadata);
+        // Profiles without stored metadata take it from the settings being replaced
+        assert_eq!(mod_property_value(entries[1], "Name"), "LEGACY");
+        assert_eq!(mod_property_value(entries[1], "Enabled"), "true");
+        assert_eq!(mod_entry_metadata(entries[1]), metadata);
+
+        let xml = serialize_settings_xml(&settings).unwrap();
+        assert!(xml.contains(r#"<Property name="Author" value="Babscoole" />"#));
+        assert!(xml.contains(r#"<Property name="Dependencies" value="CORELIB" />"#));
+    }
+
+    #[test]
+    fn keeps_game_build_across_apply() {
+        let temp = tempfile::tempdir().unwrap();
+        let installed = temp
+            .path()
+            .join("before")
+            .join("GAMEDATA")
+            .join("MODS")
+            .join("Scanner");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(
+            installed.join("mod_info.json"),
+            r#"{"modId":"12","fileId":"34","version":"1.0","installSource":"scanner.zip","gameBuild":"18034721"}"#,
+        )
+        .unwrap();
+
+        let profile = snapshot_live_profile(Some(&temp.path().join("before")), "Main");
+        assert_eq!(profile.mods[0].game_build.as_deref(), Some("18034721"));
+
+        // Deployed again into an emptied MODS folder, as apply_profile does
+        let deployed = temp
+            .path()
+            .join("after")
+            .join("GAMEDATA")
+            .join("MODS")
+            .join("Scanner");
+        fs::create_dir_all(&deployed).unwrap();
+        write_deployed_mod_info(&deployed, &profile.mods[0]);
+        let info: Value =
+            serde_json::from_str(&fs::read_to_string(deployed.join("mod_info.json")).unwrap())
+                .unwrap();
+        assert_eq!(info["gameBuild"], "18034721");
+        assert_eq!(info["installSource"], "scanner.zip");
+        // Not reported as Unrecorded after the switch
+        assert!(mod_build_status(info["gameBuild"].as_str(), Some("18034721")).is_none());
+    }
+
+    #[test]
+    fn names_imported_profiles() {
+        assert_eq!(
+            import_profile_name(Some(" Survival "), "ignored").unwrap(),
+            "Survival"
+        );
+        for bad in ["..", "../escape", "C:stuff", "CON", ""] {
+            assert!(
+                import_profile_name(Some(bad), "Fine").is_err(),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+        assert_eq!(
+            import_profile_name(None, "../Shared: Pack").unwrap(),
+            "_Shared_ Pack"
+        );
+        assert_eq!(import_profile_name(None, "aux").unwrap(), "aux_");
+        assert!(import_profile_name(None, "..").is_err());
+    }
+
+    #[test]
+    fn resolves_packaged_archive_sources() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let downloads = root.join("downloads");
+        let library = root.join("library");
+        fs::create_dir_all(&downloads).unwrap();
+        fs::create_dir_all(library.join("unpacked.zip_unpacked")).unwrap();
+        fs::write(downloads.join("local.zip"), "local").unwrap();
+
+        let entry = |filename: &str, nexus: bool| ProfileModEntry {
+            filename: filename.to_string(),
+            mod_id: nexus.then(|| "1".to_string()),
+            file_id: nexus.then(|| "2".to_string()),
+            version: None,
+            installed_options: None,
+            folders: Vec::new(),
+            game_build: None,
+        };
+        let archive = |filename: &str, sha256: Option<String>, embedded: bool| PackagedArchive {
+            filename: filename.to_string(),
+            sha256,
+            size: None,
+            embedded,
+        };
+        let resolve = |e: &ProfileModEntry, a: Option<&PackagedArchive>| {
+            resolve_archive_source(e, a, &downloads, &library)
+        };
+
+        let local_hash = sha256_file(&downloads.join("local.zip")).unwrap();
+        assert_eq!(
+            resolve(
+                &entry("local.zip", true),
+                Some(&archive("local.zip", Some(local_hash), false))
+            ),
+            ArchiveSource::Downloads { hash_matches: true }
+        );
+        assert_eq!(
+            resolve(
+                &entry("local.zip", true),
+                Some(&archive("local.zip", Some("00".into()), false))
+            ),
+            ArchiveSource::Downloads {
+                hash_matches: false
+            }
+        );
+        assert_eq!(
+            resolve(&entry("unpacked.zip", false), None),
+            ArchiveSource::Library
+        );
+        assert_eq!(
+            resolve(
+                &entry("custom.zip", false),
+                Some(&archive("custom.zip", None, true))
+            ),
+            ArchiveSource::Package
+        );
+        assert_eq!(
+            resolve(&entry("nexus.zip", true), None),
+            ArchiveSource::Nexus
+        );
+        assert_eq!(
+            resolve(&entry("custom.zip", false), None),
+            ArchiveSource::Missing
+        );
+
+        assert!(is_plain_file_name("mod v1.2.zip"));
+        assert!(!is_plain_file_name("../evil.zip"));
+        assert!(!is_plain_file_name("dir/evil.zip"));
+        assert!(!is_plain_file_name(""));
+    }
+
+    #[test]
+    fn rolls_back_failed_deployments() {
+        let temp = tempfile::tempdir().unwrap();
+        let game = temp.path().to_path_buf();
+        let mods = game.join("GAMEDATA").join("MODS");
+        let settings = live_settings_path(&game);
+        fs::create_dir_all(mods.join("OldMod")).unwrap();
+        fs::create_dir_all(settings.parent().unwrap()).unwrap();
+        fs::write(mods.join("OldMod").join("a.txt"), "old").unwrap();
+        fs::write(mods.join("loose.pak"), "pak").unwrap();
+        fs::write(mods.join("readme.txt"), "kept").unwrap();
+        fs::write(&settings, "<Data old />").unwrap();
+
+        snapshot_deployment(&game, "Next").unwrap();
+        assert!(!mods.join("OldMod").exists());
+        assert!(!mods.join("loose.pak").exists());
+        assert!(mods.join("readme.txt").exists());
+
+        // Half-finished deploy
+        fs::create_dir_all(mods.join("NewMod")).unwrap();
+        fs::write(&settings, "<Data new />").unwrap();
+
+        restore_deployment(&game).unwrap();
+        assert!(!mods.join("NewMod").exists());
+        assert_eq!(
+            fs::read_to_string(mods.join("OldMod").join("a.txt")).unwrap(),
+            "old"
+        );
+        assert!(mods.join("loose.pak").exists());
+        assert_eq!(fs::read_to_string(&settings).unwrap(), "<Data old />");
+        assert!(!rollback_dir(&game).exists());
+
+        // A switch from a game without a settings file removes the generated one
+        fs::remove_file(&settings).unwrap();
+        snapshot_deployment(&game, "Next").unwrap();
+        fs::write(&settings, "<Data new />").unwrap();
+        restore_deployment(&game).unwrap();
+        assert!(!settings.exists());
+    }
+
+    #[test]
+    fn tracks_unsaved_profile_changes() {
+        assert!(!profile_dirty_after_sync(&Ok(true)));
+        // Skipped during a crash search
+        assert!(profile_dirty_after_sync(&Ok(false)));
+        assert!(profile_dirty_after_sync(&Err("disk full".to_string())));
+
+        let status = |dirty: bool| ProfileSyncStatus {
+            active_profile: Some("Main".to_string()),
+            dirty,
+        };
+        assert!(check_unsaved_switch(&status(false), false).is_ok());
+        assert!(check_unsaved_switch(&status(true), true).is_ok());
+        let err = check_unsaved_switch(&status(true), false).unwrap_err();
+        assert!(err.contains("'Main'"));
+
+        let temp = tempfile::tempdir().unwrap();
+        let game = temp.path().join("Game");
+        let live = live_settings_path(&game);
+        fs::create_dir_all(live.parent().unwrap()).unwrap();
+        assert!(is_live_settings_path(&live, &game));
+        let spelled = game
+            .join("Binaries")
+            .join("SETTINGS")
+            .join("..")
+            .join("SETTINGS")
+            .join("GCMODSETTINGS.MXML");
+        assert_ne!(spelled, live);
+        assert!(is_live_settings_path(&spelled, &game));
+        fs::write(&live, "<Data />").unwrap();
+        assert!(is_live_settings_path(&spelled, &game));
+        assert!(!is_live_settings_path(
+            &game.join("GCMODSETTINGS.MXML"),
+            &game
+        ));
+    }
+
+    #[test]
+    fn captures_profile_settings_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let game = temp.path().to_path_buf();
+        let settings_dir = game_settings_dir(&game);
+        fs::create_dir_all(&settings_dir).unwrap();
+        fs::write(settings_dir.join("TKGRAPHICSSETTINGS.MXML"), "<graphics />").unwrap();
+
+        let file = |name: &str, content: Option<&str>| ProfileSettingsFile {
+            name: name.to_string(),
+            content: content.map(String::from),
+        };
+        let (captured, problems) = capture_settings_files(
+            &game,
+            &[
+                file("TKGRAPHICSSETTINGS.MXML", None),
+                file("GONE.MXML", Some("<old />")),
+            ],
+        );
+        assert!(problems.is_empty());
+        assert_eq!(
+            captured,
+            vec![
+                file("TKGRAPHICSSETTINGS.MXML", Some("<graphics />")),
+                file("GONE.MXML", Some("<old />")),
+            ]
+        );
+
+        // Binary files can't be stored as text; the last capture stays and the problem is reported
+        fs::write(settings_dir.join("BINARY.DAT"), [0xff, 0xfe, 0x00]).unwrap();
+        let (captured, problems) =
+            capture_settings_files(&game, &[file("BINARY.DAT", Some("<old />"))]);
+        assert_eq!(captured, vec![file("BINARY.DAT", Some("<old />"))]);
+        assert_eq!(problems.len(), 1);
+
+        assert!(is_profile_settings_file_name("GCUSERSETTINGSDATA.MXML"));
+        assert!(!is_profile_settings_file_name("gcmodsettings.mxml"));
+        assert!(!is_profile_settings_file_name("../TKGRAPHICSSETTINGS.MXML"));
+    }
+
+    #[test]
+    fn validates_profile_names() {
+        assert_eq!(
+            validate_profile_name("  Survival v2 ").unwrap(),
+            "Survival v2"
+        );
+        assert_eq!(
+            validate_profile_name("Photo mode (ultra)").unwrap(),
+            "Photo mode (ultra)"
+        );
+        for bad in [
+            "",
+            "   ",
+            "../escape",
+            "a/b",
+            "a\\b",
+            "C:stuff",
+            "what?",
+            ".hidden",
+            "trailing.",
+            "con",
+            "Lpt1.json",
+        ] {
+            assert!(
+                validate_profile_name(bad).is_err(),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+        assert!(validate_profile_name(&"x".repeat(MAX_PROFILE_NAME_LEN + 1)).is_err());
+
+        assert_eq!(sanitize_profile_name("../Best: Mods?"), "_Best_ Mods_");
+        assert_eq!(sanitize_profile_name("..."), "");
+        assert_eq!(sanitize_profile_name("NUL"), "NUL_");
+        let long = sanitize_profile_name(&"y".repeat(200));
+        assert!(validate_profile_name(&long).is_ok());
+    }
+
+    #[test]
+    fn never_overwrites_existing_profiles() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("Survival.json");
+        create_profile_file(&path, "{\"name\":\"Survival\"}").unwrap();
+        let err = create_profile_file(&path, "{}").unwrap_err();
+        assert_eq!(err, "A profile named 'Survival' already exists.");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"name\":\"Survival\"}"
+        );
+    }
+
+    #[test]
+    fn resolves_layered_profiles() {
+        let entry = |filename: &str, folders: &[(&str, u32)]| ProfileModEntry {
+            filename: filename.to_string(),
+            mod_id: Some("1".to_string()),
+            file_id: Some("2".to_string()),
+            version: None,
+            installed_options: Some(folders.iter().map(|(n, _)| n.to_string()).collect()),
+            folders: folders
+                .iter()
+                .map(|(name, priority)| ProfileFolderState {
+                    name: name.to_string(),
+                    priority: *priority,
+                    enabled: true,
+                    ..Default::default()
+                })
+                .collect(),
+            game_build: None,
+        };
+        let base = ModProfileData {
+            name: "Base".to_string(),
+            mods: vec![entry("a.zip", &[("A", 0)]), entry("b.zip", &[("B", 1)])],
+            ..Default::default()
+        };
+        let child = ModProfileData {
+            name: "Mine".to_string(),
+            parent: Some("base".to_string()),
+            mods: vec![entry("c.zip", &[("C", 2)])],
+            removed: vec!["B.zip".to_string()],
+            folder_overrides: vec![ProfileFolderState {
+                name: "a".to_string(),
+                priority: 5,
+                enabled: false,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let profiles = [base.clone(), child.clone()];
+        let load = |name: &str| {
+            profiles
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(name))
+                .cloned()
+                .ok_or_else(|| format!("Profile '{}' not found", name))
+        };
+
+        let effective = resolve_profile_layers("Mine", load).unwrap();
+        assert_eq!(effective.name, "Mine");
+        assert_eq!(effective.parent, None);
+        let names: Vec<&str> = effective.mods.iter().map(|m| m.filename.as_str()).collect();
+        assert_eq!(names, vec!["a.zip", "c.zip"]);
+        assert_eq!(effective.mods[0].folders[0].priority, 5);
+        assert!(!effective.mods[0].folders[0].enabled);
+
+        // Saving the effective list back over the parent reproduces the layer
+        let layer = layer_over_parent(&base, effective.clone());
+        assert_eq!(layer.removed, vec!["b.zip".to_string()]);
+        assert_eq!(layer.mods.len(), 1);
+        assert_eq!(layer.mods[0].filename, "c.zip");
+        assert_eq!(layer.folder_overrides.len(), 1);
+        assert_eq!(layer.folder_overrides[0].name, "A");
+
+        let looped = [
+            ModProfileData {
+                parent: Some("Mine".to_string()),
+                ..base
+            },
+            child,
+        ];
+        let load_looped = |name: &str| {
+            looped
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(name))
+                .cloned()
+                .ok_or_else(|| format!("Profile '{}' not found", name))
+        };
+        assert!(resolve_profile_layers("Mine", load_looped)
+            .unwrap_err()
+            .contains("loops"));
+    }
+
+    #[test]
+    fn classifies_profile_entry_sources() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let downloads = root.join("downloads");
+        let library = root.join("library");
+        fs::create_dir_all(&downloads).unwrap();
+        fs::create_dir_all(library.join("both.zip_unpacked")).unwrap();
+        fs::write(downloads.join("both.zip"), "both").unwrap();
+        fs::write(downloads.join("archive.zip"), "archive").unwrap();
+
+        let entry = |filename: &str, nexus: bool| ProfileModEntry {
+            filename: filename.to_string(),
+            mod_id: nexus.then(|| "1".to_string()),
+            file_id: nexus.then(|| "2".to_string()),
+            version: None,
+            installed_options: None,
+            folders: Vec::new(),
+            game_build: None,
+        };
+        let source = |e: &ProfileModEntry| profile_entry_source(e, &downloads, &library);
+
+        assert_eq!(
+            source(&entry("both.zip", false)),
+            ProfileEntrySource::Library
+        );
+        assert_eq!(
+            source(&entry("archive.zip", false)),
+            ProfileEntrySource::Archive
+        );
+        assert_eq!(source(&entry("gone.zip", true)), ProfileEntrySource::Nexus);
+        assert_eq!(
+            source(&entry("gone.zip", false)),
+            ProfileEntrySource::Unrecoverable
+        );
+    }
+
+    #[test]
+    fn diffs_profiles() {
+        let entry = |filename: &str, mod_id: &str, file_id: &str, folders: &[(&str, u32, bool)]| {
+            ProfileModEntry {
+                filename: filename.to_string(),
+                mod_id: Some(mod_id.to_string()),
+                file_id: Some(file_id.to_string()),
+                version: None,
+                installed_options: Some(folders.iter().map(|f| f.0.to_string()).collect()),
+                folders: folders
+                    .iter()
+                    .map(|(name, priority, enabled)| ProfileFolderState {
+                        name: name.to_string(),
+                        priority: *priority,
+                        enabled: *enabled,
+                        ..Default::default()
+                    })
+                    .collect(),
+                game_build: None,
+            }
+        };
+        let a = ModProfileData {
+            mods: vec![
+                entry("a.zip", "1", "10", &[("A", 0, true)]),
+                entry("b-v1.zip", "2", "20", &[("B", 1, true)]),
+                entry("c.zip", "3", "30", &[("C", 2, true), ("C_EXTRA", 3, true)]),
+                entry("d.zip", "4", "40", &[("D", 4, true)]),
+            ],
+            ..Default::default()
+        };
+        let b = ModProfileData {
+            mods: vec![
+                entry("new.zip", "5", "50", &[("NEW", 0, true)]),
+                entry("a.zip", "1", "10", &[("A", 1, false)]),
+                entry("b-v2.zip", "2", "21", &[("B", 2, true)]),
+                entry("c.zip", "3", "30", &[("C", 3, true)]),
+            ],
+            disable_all_mods: true,
+            ..Default::default()
+        };
+
+        let diff = diff_profile_data(&a, &b);
+        assert_eq!(diff.added, ["new.zip"]);
+        assert_eq!(diff.removed, ["d.zip"]);
+        assert_eq!(diff.version_changed.len(), 1);
+        assert_eq!(diff.version_changed[0].to_file_id.as_deref(), Some("21"));
+        assert_eq!(diff.options_changed[0].to, ["C"]);
+        // Only the shift from NEW at the top; nothing actually moved
+        assert!(diff.priority_changed.is_empty());
+        assert_eq!(
+            diff.enabled_changed,
+            [FolderEnabledChange {
+                folder: "A".to_string(),
+                enabled: false
+            }]
+        );
+        assert_eq!(diff.disable_all_changed, Some(true));
+
+        // Moving one folder to the end reports only that folder
+        let mut moved = a.clone();
+        moved.mods[0].folders[0].priority = 9;
+        let diff = diff_profile_data(&a, &moved);
+        assert_eq!(
+            diff.priority_changed,
+            [FolderPriorityChange {
+                folder: "A".to_string(),
+                from_position: 0,
+                to_position: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn swaps_restored_saves_in_after_extract() {
+        let root = tempfile::tempdir().unwrap();
+        let backup_src = root.path().join("backup_src");
+        fs::create_dir_all(&backup_src).unwrap();
+        fs::write(backup_src.join("save.hg"), "old game").unwrap();
+        let zip_path = root.path().join("backup.zip");
+        zip_directory(&backup_src, &zip_path).unwrap();
+
+        let save_dir = root.path().join("st_123");
+        fs::create_dir_all(&save_dir).unwrap();
+        fs::write(save_dir.join("save.hg"), "new game").unwrap();
+        fs::write(save_dir.join("save2.hg"), "extra slot").unwrap();
+
+        let archive = ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        restore_saves_from_archive(archive, &save_dir).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(save_dir.join("save.hg")).unwrap(),
+            "old game"
+        );
+        assert!(!save_dir.join("save2.hg").exists());
+        let leftovers: Vec<_> = fs::read_dir(root.path())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("st_123."))
+            .collect();
+        assert!(leftovers.is_empty(), "left behind {:?}", leftovers);
+    }
+}
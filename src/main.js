@@ -3,7 +3,7 @@ import { relaunch } from '@tauri-apps/plugin-process';
 import { getVersion } from '@tauri-apps/api/app';
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { readTextFile, writeTextFile, mkdir } from "@tauri-apps/plugin-fs";
 import { basename, join, appDataDir } from "@tauri-apps/api/path";
 import { getCurrentWindow, LogicalSize } from "@tauri-apps/api/window";
@@ -36,6 +36,7 @@ let NEXUS_API_KEY = "";
 const CURATED_LIST_URL = "https://raw.githubusercontent.com/Syzzle07/SingularityMM/refs/heads/data/curated/curated_list.json";
 let curatedData = [];
 let curatedDataPromise = null;
+let curatedDataFromNetwork = false;
 let downloadHistory = [];
 const nexusFileCache = new Map();
 
@@ -45,14 +46,10 @@ let isPanelOpen = false;
 const SCROLL_SPEED = 5;
 const CACHE_DURATION_MS = 60 * 60 * 1000;
 
-// Function to load images through Tauri HTTP command
+// Function to load images through Tauri (served from the local image cache when possible)
 async function loadImageViaTauri(imgElement, url) {
   try {
-    const response = await invoke('http_request', {
-      url: url,
-      method: 'GET',
-      headers: {}
-    });
+    const response = await invoke('get_cached_image', { url: url });
 
     if (response.status >= 200 && response.status < 300) {
       // The response body is base64 encoded for images
@@ -680,6 +677,7 @@ document.addEventListener('DOMContentLoaded', () => {
       if (response.status === 304 && cachedObj) {
         console.log("Remote list hasn't changed. Extending cache duration.");
         curatedData = cachedObj.data;
+        curatedDataFromNetwork = true;
         // Save it again just to update the 'timestamp' so it doesn't check again for another hour
         await saveCuratedListToCache(cachedObj.data, cachedObj.etag);
         return;
@@ -694,6 +692,7 @@ document.addEventListener('DOMContentLoaded', () => {
       const newEtag = response.headers['etag']; // Get the new ETag
 
       curatedData = freshData;
+      curatedDataFromNetwork = true;
       console.log(`Successfully loaded ${curatedData.length} mods from network.`);
 
       // Save new data + new ETag
@@ -706,11 +705,37 @@ document.addEventListener('DOMContentLoaded', () => {
         console.warn("Using stale cache due to network error.");
         curatedData = cachedObj.data;
       } else {
-        await window.customAlert("Failed to load mod data from the server. Update checks and the browse tab will not work.", "Network Error");
+        // Last resort: the per-mod metadata cache keeps names, authors and images for installed mods
+        try {
+          curatedData = await invoke('get_cached_mod_metadata', { modIds: null });
+        } catch (cacheError) {
+          console.error("Failed to read mod metadata cache:", cacheError);
+        }
+        if (curatedData.length > 0) {
+          console.warn(`Offline: using cached metadata for ${curatedData.length} mods.`);
+        } else {
+          await window.customAlert("Failed to load mod data from the server. Update checks and the browse tab will not work.", "Network Error");
+        }
       }
     }
   }
 
+  // Stores the metadata of installed mods so it is still available offline
+  async function cacheInstalledModMetadata() {
+    if (!curatedDataFromNetwork) return;
+    const installedIds = new Set();
+    for (const modData of appState.modDataCache.values()) {
+      if (modData.local_info?.mod_id) installedIds.add(String(modData.local_info.mod_id));
+    }
+    const entries = curatedData.filter(mod => installedIds.has(String(mod.mod_id)));
+    if (entries.length === 0) return;
+    try {
+      await invoke('cache_mod_metadata', { entries });
+    } catch (error) {
+      console.warn("Failed to update mod metadata cache:", error);
+    }
+  }
+
   // --- NEXUS LOGIN LOGIC ---
   const nexusAuthBtn = document.getElementById('nexusAuthBtn');
   const nexusAccountStatus = document.getElementById('nexusAccountStatus');
@@ -827,11 +852,19 @@ document.addEventListener('DOMContentLoaded', () => {
       localStorage.setItem('autoInstallAfterDownload', this.checked);
     });
 
+    // Set up Pre-Launch Check toggle (on by default)
+    const preLaunchCheckToggle = document.getElementById('preLaunchCheckToggle');
+    preLaunchCheckToggle.checked = localStorage.getItem('preLaunchCheck') !== 'false';
+    preLaunchCheckToggle.addEventListener('change', function () {
+      localStorage.setItem('preLaunchCheck', this.checked);
+    });
+
     // --- 4. HANDLE GAME PATH ---
-    const gamePaths = await gameDetectPromise;
+    let gamePaths = await gameDetectPromise;
+    if (!gamePaths) gamePaths = await offerInstallationSwitch();
 
     if (gamePaths) {
-      console.log(`Detected ${gamePaths.version_type} version of No Man's Sky.`);
+      console.log(`Detected ${gamePaths.version_type} version of No Man's Sky (build ${gamePaths.game_build || 'unknown'}).`);
 
       appState.gamePath = gamePaths.game_root_path;
       appState.settingsPath = gamePaths.settings_root_path;
@@ -862,6 +895,12 @@ document.addEventListener('DOMContentLoaded', () => {
       // If no game, show title error (visual feedback)
       const bannerText = document.querySelector('#globalBanner .banner-text');
       if (bannerText) bannerText.textContent = "Game Not Found";
+
+      const choose = await window.customConfirm(
+        "No Man's Sky could not be found automatically. Do you want to select the game folder yourself?",
+        "Game Not Found"
+      );
+      if (choose) await promptForGamePath();
     }
 
     // --- 5. LOAD MOD LIST (XML) ---
@@ -896,6 +935,7 @@ document.addEventListener('DOMContentLoaded', () => {
 
     // --- 6. SETUP LISTENERS ---
     listen('nxm-link-received', (event) => handleNxmLink(event.payload));
+    listen('nxm-link-error', (event) => window.customAlert(event.payload, "Link Error"));
 
     listen('install-progress', (event) => {
       const payload = event.payload;
@@ -977,16 +1017,21 @@ document.addEventListener('DOMContentLoaded', () => {
     curatedDataPromise.then(() => {
       if (appState.gamePath && appState.modDataCache.size > 0) {
         checkForUpdates(true); // Silent check
+        cacheInstalledModMetadata();
       }
     });
 
     try {
-      // Ask Rust if we have a pending link
-      const pendingLink = await invoke('check_startup_intent');
-      if (pendingLink) {
-        console.log("Found pending startup NXM link:", pendingLink);
-        // Process it just like a normal link event
-        handleNxmLink(pendingLink);
+      // Ask Rust for links that arrived before the UI was ready
+      const pendingLinks = await invoke('check_startup_intent');
+      for (const intent of pendingLinks) {
+        console.log("Found pending startup NXM link:", intent.link);
+        if (intent.error) {
+          await window.customAlert(intent.error, "Link Error");
+        } else {
+          // Process it just like a normal link event
+          await handleNxmLink(intent.link);
+        }
       }
     } catch (e) {
       console.error("Failed to check startup intent:", e);
@@ -1058,10 +1103,17 @@ document.addEventListener('DOMContentLoaded', () => {
         ? `<span class="untracked-indicator" title="${i18n.get('untrackedModTooltip')}"></span>`
         : '';
 
+      const outdatedHtml = modData.build_status === 'different_build'
+        ? `<span class="outdated-build-indicator" title="${i18n.get('outdatedBuildTooltip')}">⚠️</span>`
+        : modData.build_status === 'unrecorded'
+          ? `<span class="outdated-build-indicator unrecorded" title="${i18n.get('unrecordedBuildTooltip')}">❔</span>`
+          : '';
+
       row.innerHTML = `
                 <div class="mod-name-container">
                     <span class="mod-name-text">${modData.folder_name}</span>
                     ${untrackedHtml}
+                    ${outdatedHtml}
                     <span class="update-indicator hidden" data-i18n-title="updateAvailableTooltip" title="Update available"></span>
                 </div>
                 <div class="priority"><input type="text" class="priority-input" value="${index}" readonly></div>
@@ -2215,6 +2267,11 @@ document.addEventListener('DOMContentLoaded', () => {
   async function handleNxmLink(link) {
     console.log(`Frontend received nxm link: ${link}`);
 
+    if (/^nxm:\/\/nomanssky\/collections\//i.test(link)) {
+      await handleCollectionLink(link);
+      return;
+    }
+
     const match = link.match(/nxm:\/\/nomanssky\/mods\/(\d+)\/files\/(\d+)/);
     if (!match || match.length < 3) {
       await window.customAlert('Error: The received Nexus link was malformed.', "Link Error");
@@ -2266,6 +2323,36 @@ document.addEventListener('DOMContentLoaded', () => {
     });
   }
 
+  async function handleCollectionLink(link) {
+    try {
+      window.addAppLog(`Importing Nexus collection: ${link}`, "INFO");
+      const result = await invoke('import_nexus_collection', { link });
+      await refreshProfileList();
+
+      window.addAppLog(`Collection saved as profile "${result.profile_name}". Queued ${result.queued.length} downloads, ${result.skipped.length} skipped.`, "INFO");
+
+      if (result.skipped.length > 0) {
+        await window.customAlert(`These collection entries could not be resolved:\n\n${result.skipped.join('\n')}`, "Collection Import");
+      }
+
+      // Queue every missing archive through the normal download flow
+      for (const file of result.queued) {
+        await startModDownload({
+          modId: file.mod_id,
+          fileId: file.file_id,
+          version: file.version,
+          fileName: file.file_name,
+          displayName: file.display_name,
+          replacingFileId: null,
+          nxmQueryParams: result.nxm_query || ""
+        });
+      }
+    } catch (e) {
+      window.addAppLog(`Collection import failed: ${e}`, "ERROR");
+      await window.customAlert(`Failed to import collection: ${e}`, "Collection Error");
+    }
+  }
+
   const reorderModsByList = async (orderedModNames) => {
     try {
       // 1. Call the new Rust command, passing the desired order.
@@ -2556,17 +2643,12 @@ document.addEventListener('DOMContentLoaded', () => {
     if (nexusFileCache.has(modIdStr)) {
       return nexusFileCache.get(modIdStr);
     }
-    const url = `https://api.nexusmods.com/v1/games/nomanssky/mods/${modIdStr}/files.json`;
-    const headers = { "apikey": NEXUS_API_KEY };
     try {
-      const response = await invoke('http_request', {
-        url: url,
-        method: 'GET',
-        headers: headers
-      });
-      if (response.status < 200 || response.status >= 300) return null;
-      const data = JSON.parse(response.body);
-      nexusFileCache.set(modIdStr, data);
+      // Served from the metadata cache while fresh, or when Nexus is unreachable
+      const data = await invoke('get_mod_files', { modId: modIdStr });
+      if (!data.stale) {
+        nexusFileCache.set(modIdStr, data);
+      }
       return data;
     } catch (error) {
       return null;
@@ -3597,13 +3679,200 @@ document.addEventListener('DOMContentLoaded', () => {
     }
   }
 
+  // --- GAME INSTALLATION SETTINGS ---
+  const gameInstallSelector = document.getElementById('gameInstallSelector');
+
+  async function updateGameInstallUI() {
+    try {
+      const installations = await invoke('list_game_installations');
+      gameInstallSelector.innerHTML = '';
+      for (const installation of installations) {
+        const option = document.createElement('option');
+        option.value = installation.id;
+        option.textContent = `${installation.version_type} - ${installation.path}`;
+        option.disabled = !installation.available;
+        option.selected = installation.active;
+        gameInstallSelector.appendChild(option);
+      }
+    } catch (e) {
+      console.error("Failed to list game installations:", e);
+    }
+  }
+
+  // The selected installation is never swapped silently; when it is missing (unplugged drive,
+  // unmounted SD card) the user decides whether to switch to another known copy
+  async function offerInstallationSwitch() {
+    try {
+      const installations = await invoke('list_game_installations');
+      const active = installations.find(i => i.active);
+      const fallback = installations.find(i => i.available && !i.active);
+      if (!active || active.available || !fallback) return null;
+
+      const switchNow = await window.customConfirm(
+        `The selected installation is unavailable:\n${active.path}\n\nSwitch to ${fallback.version_type} - ${fallback.path}?`,
+        "Installation Unavailable"
+      );
+      if (!switchNow) return null;
+      return await invoke('set_active_installation', { installationId: fallback.id });
+    } catch (e) {
+      console.error("Failed to switch installation:", e);
+      return null;
+    }
+  }
+
+  // Lets the user point at a game folder detection missed (portable installs, odd Wine prefixes)
+  async function promptForGamePath() {
+    const selected = await open({
+      directory: true,
+      multiple: false,
+      title: "Select your No Man's Sky folder"
+    });
+    if (!selected) return false;
+
+    try {
+      await invoke('set_game_path', { path: selected });
+      location.reload();
+      return true;
+    } catch (e) {
+      await window.customAlert(String(e), "Invalid Game Folder");
+      return false;
+    }
+  }
+
+  document.getElementById('setGamePathBtn').addEventListener('click', () => promptForGamePath());
+
+  gameInstallSelector.addEventListener('change', async () => {
+    try {
+      await invoke('set_active_installation', { installationId: gameInstallSelector.value });
+      // Everything (mod list, settings file, launch button) derives from the active installation
+      location.reload();
+    } catch (e) {
+      await window.customAlert("Failed to switch installation: " + e, "Error");
+      updateGameInstallUI();
+    }
+  });
+
+  // --- LAUNCH METHOD SETTINGS ---
+  const launchMethodSelector = document.getElementById('launchMethodSelector');
+  const launchMethodValue = document.getElementById('launchMethodValue');
+  let currentLaunchStrategy = null;
+
+  const launchValuePlaceholders = {
+    heroic_uri: "Heroic app name (e.g. 1446213994)",
+    lutris_rungame: "Lutris game slug or ID",
+    custom: "e.g. PROTON_LOG=1 gamemoderun wine \"{exe}\"",
+  };
+
+  function updateLaunchValueInput() {
+    const type = launchMethodSelector.value;
+    launchMethodValue.classList.toggle('hidden', !(type in launchValuePlaceholders));
+    launchMethodValue.placeholder = launchValuePlaceholders[type] || '';
+  }
+
+  async function updateLaunchMethodUI() {
+    try {
+      currentLaunchStrategy = await invoke('get_launch_strategy');
+      launchMethodSelector.value = currentLaunchStrategy.type;
+      launchMethodValue.value = currentLaunchStrategy.app_name
+        ?? currentLaunchStrategy.game
+        ?? currentLaunchStrategy.command
+        ?? '';
+      updateLaunchValueInput();
+    } catch (e) {
+      console.error("Failed to load launch method:", e);
+    }
+  }
+
+  launchMethodSelector.addEventListener('change', () => {
+    launchMethodValue.value = '';
+    updateLaunchValueInput();
+  });
+
+  document.getElementById('saveLaunchMethodBtn').addEventListener('click', async () => {
+    const type = launchMethodSelector.value;
+    const value = launchMethodValue.value.trim();
+    const strategy = { type };
+    if (type === 'heroic_uri') {
+      strategy.app_name = value;
+      strategy.runner = currentLaunchStrategy?.runner || 'gog';
+    } else if (type === 'lutris_rungame') {
+      strategy.game = value;
+    } else if (type === 'custom') {
+      strategy.command = value;
+      // The form only edits the command; keep variables saved in config.json
+      strategy.env = currentLaunchStrategy?.type === 'custom' ? (currentLaunchStrategy.env || {}) : {};
+    }
+
+    try {
+      await invoke('set_launch_strategy', { installationId: null, strategy });
+      await updateLaunchMethodUI();
+      await window.customAlert("Launch method saved.", "Success");
+    } catch (e) {
+      await window.customAlert("Failed to save launch method: " + e, "Error");
+    }
+  });
+
+  // --- Save Backups ---
+  const saveBackupSelector = document.getElementById('saveBackupSelector');
+
+  const refreshSaveBackups = async () => {
+    let backups = [];
+    try {
+      backups = await invoke('list_save_backups');
+    } catch (e) {
+      console.warn('Could not list save backups:', e);
+    }
+    saveBackupSelector.innerHTML = '';
+    if (backups.length === 0) {
+      saveBackupSelector.add(new Option(i18n.get('noSaveBackups'), ''));
+    }
+    backups.forEach(backup => {
+      const date = new Date(backup.created_at).toLocaleString();
+      const reason = i18n.get(`saveBackupReason_${backup.reason}`);
+      const profile = backup.profile || '-';
+      saveBackupSelector.add(new Option(`${date} · ${profile} · ${reason}`, backup.id));
+    });
+    document.getElementById('restoreSaveBackupBtn').disabled = backups.length === 0;
+  };
+
+  document.getElementById('createSaveBackupBtn').addEventListener('click', async () => {
+    try {
+      const backup = await invoke('create_save_backup');
+      await refreshSaveBackups();
+      if (!backup) await window.customAlert(i18n.get('noSaveFolderMsg'), i18n.get('saveBackupsTitle'));
+    } catch (e) {
+      await window.customAlert(`${e}`, "Error");
+    }
+  });
+
+  document.getElementById('restoreSaveBackupBtn').addEventListener('click', async () => {
+    const backupId = saveBackupSelector.value;
+    if (!backupId) return;
+    const label = saveBackupSelector.selectedOptions[0].textContent;
+    const confirmed = await window.customConfirm(
+      i18n.get('restoreSaveBackupConfirm', { backup: label }),
+      i18n.get('saveBackupsTitle')
+    );
+    if (!confirmed) return;
+    try {
+      await invoke('restore_save_backup', { backupId });
+      await refreshSaveBackups();
+      await window.customAlert(i18n.get('restoreSaveBackupDone'), i18n.get('saveBackupsTitle'));
+    } catch (e) {
+      await window.customAlert(`${e}`, "Error");
+    }
+  });
+
   settingsBtn.addEventListener('click', async () => {
     await updateNXMButtonState();
+    await refreshSaveBackups();
 
     document.getElementById('nxmHandlerStatus').classList.add('hidden');
     settingsModalOverlay.classList.remove('hidden');
     updateDownloadPathUI();
     updateLibraryPathUI();
+    updateGameInstallUI();
+    updateLaunchMethodUI();
   });
   closeSettingsModalBtn.addEventListener('click', () => settingsModalOverlay.classList.add('hidden'));
   settingsModalOverlay.addEventListener('click', (e) => {
@@ -4194,6 +4463,11 @@ document.addEventListener('DOMContentLoaded', () => {
   const profileProgressBar = document.getElementById('profileProgressBar');
   const profileTimeEst = document.getElementById('profileTimeEst');
 
+  // Profiles whose stored name differs from their file show both
+  function profileLabel(profile) {
+    return profile.display_name ? `${profile.name} (${profile.display_name})` : profile.name;
+  }
+
   async function refreshProfileList() {
     try {
       const profiles = await invoke('list_profiles');
@@ -4204,14 +4478,14 @@ document.addEventListener('DOMContentLoaded', () => {
       profileSelect.innerHTML = '';
       profiles.forEach(p => {
         const opt = document.createElement('option');
-        opt.value = p;
-        opt.textContent = p;
+        opt.value = p.name;
+        opt.textContent = profileLabel(p);
         profileSelect.appendChild(opt);
       });
 
       // If the previously selected profile still exists, keep it selected.
       // Otherwise, default to "Default".
-      if (profiles.includes(currentSelection)) {
+      if (profiles.some(p => p.name === currentSelection)) {
         profileSelect.value = currentSelection;
       } else {
         profileSelect.value = 'Default';
@@ -4235,10 +4509,11 @@ document.addEventListener('DOMContentLoaded', () => {
     const profiles = await invoke('list_profiles');
     mpProfileList.innerHTML = '';
 
-    profiles.forEach(p => {
+    profiles.forEach(profile => {
+      const p = profile.name;
       const li = document.createElement('li');
       li.className = 'mp-list-item';
-      li.textContent = p;
+      li.textContent = profileLabel(profile);
 
       // Highlight logic
       if (p === selectedProfileInModal) li.classList.add('active');
@@ -4291,14 +4566,14 @@ document.addEventListener('DOMContentLoaded', () => {
     const name = await window.customPrompt(i18n.get('enterProfileName'), i18n.get('addBtn'));
     if (name && name.trim() !== "") {
       try {
-        await invoke('create_empty_profile', { profileName: name });
+        const createdName = await invoke('create_empty_profile', { profileName: name });
 
         // Update both lists
         await renderManagerList();
         await refreshProfileList();
 
         // Auto-select the new one in modal
-        selectedProfileInModal = name;
+        selectedProfileInModal = createdName;
         renderManagerList();
       } catch (e) { await window.customAlert("Error: " + e, "Error"); }
     }
@@ -4314,7 +4589,7 @@ document.addEventListener('DOMContentLoaded', () => {
     );
     if (newName && newName.trim() !== "") {
       try {
-        await invoke('copy_profile', {
+        const copiedName = await invoke('copy_profile', {
           sourceName: selectedProfileInModal,
           newName: newName
         });
@@ -4322,7 +4597,7 @@ document.addEventListener('DOMContentLoaded', () => {
         await renderManagerList();
         await refreshProfileList();
 
-        selectedProfileInModal = newName; // Select the copy
+        selectedProfileInModal = copiedName; // Select the copy
         renderManagerList();
       } catch (e) { await window.customAlert("Error copying: " + e, "Error"); }
     }
@@ -4340,17 +4615,17 @@ document.addEventListener('DOMContentLoaded', () => {
     );
     if (newName && newName !== selectedProfileInModal) {
       try {
-        await invoke('rename_profile', { oldName: selectedProfileInModal, newName: newName });
+        const renamedTo = await invoke('rename_profile', { oldName: selectedProfileInModal, newName: newName });
 
         // If we renamed the currently ACTIVE profile, update global state
         if (appState.activeProfile === selectedProfileInModal) {
-          appState.activeProfile = newName;
-          localStorage.setItem('activeProfile', newName);
+          appState.activeProfile = renamedTo;
+          localStorage.setItem('activeProfile', renamedTo);
           // Update main dropdown selection too
-          profileSelect.value = newName;
+          profileSelect.value = renamedTo;
         }
 
-        selectedProfileInModal = newName;
+        selectedProfileInModal = renamedTo;
         await renderManagerList();
         await refreshProfileList();
       } catch (e) { await window.customAlert("Error renaming: " + e, "Error"); }
@@ -4381,6 +4656,114 @@ document.addEventListener('DOMContentLoaded', () => {
     }
   });
 
+  // EXPORT / IMPORT (portable profile packages)
+  document.getElementById('mpExportBtn').addEventListener('click', async () => {
+    if (!selectedProfileInModal) return;
+
+    const outputPath = await save({
+      defaultPath: `${selectedProfileInModal}.nmsprofile`,
+      filters: [{ name: 'Singularity Profile', extensions: ['nmsprofile'] }]
+    });
+    if (!outputPath) return;
+
+    const embedLocalArchives = await window.customConfirm(
+      i18n.get('exportEmbedPrompt'),
+      i18n.get('exportBtn'),
+      i18n.get('exportEmbedBtn'),
+      i18n.get('exportSkipEmbedBtn')
+    );
+    try {
+      const result = await invoke('export_profile', {
+        profileName: selectedProfileInModal,
+        outputPath,
+        embedLocalArchives
+      });
+      let msg = i18n.get('exportDoneMsg', { count: result.embedded.length });
+      if (result.missing.length > 0) {
+        msg += `\n\n${i18n.get('exportMissingMsg')}\n${result.missing.join('\n')}`;
+      }
+      await window.customAlert(msg, i18n.get('exportBtn'));
+    } catch (e) { await window.customAlert("Error exporting: " + e, "Error"); }
+  });
+
+  document.getElementById('mpSettingsFilesBtn').addEventListener('click', async () => {
+    if (!selectedProfileInModal) return;
+
+    try {
+      const available = await invoke('list_game_settings_files');
+      const selected = await invoke('get_profile_settings_files', { profileName: selectedProfileInModal });
+      const input = await window.customPrompt(
+        i18n.get('settingsFilesPrompt', { available: available.join('\n') }),
+        i18n.get('settingsFilesBtn'),
+        selected.join(', ')
+      );
+      if (input === null) return;
+
+      const files = input.split(',').map(f => f.trim()).filter(f => f !== '');
+      await invoke('set_profile_settings_files', { profileName: selectedProfileInModal, files });
+      window.addAppLog(`Profile "${selectedProfileInModal}" now carries settings files: ${files.join(', ') || 'none'}`, "INFO");
+    } catch (e) { await window.customAlert("Error: " + e, "Error"); }
+  });
+
+  document.getElementById('mpParentBtn').addEventListener('click', async () => {
+    if (!selectedProfileInModal) return;
+
+    try {
+      const currentParent = await invoke('get_profile_parent', { profileName: selectedProfileInModal });
+      const input = await window.customPrompt(
+        i18n.get('parentProfilePrompt', { profileName: selectedProfileInModal }),
+        i18n.get('parentProfileBtn'),
+        currentParent || ''
+      );
+      if (input === null) return;
+
+      const parent = input.trim() || null;
+      await invoke('set_profile_parent', { profileName: selectedProfileInModal, parent });
+      window.addAppLog(`Profile "${selectedProfileInModal}" parent set to: ${parent || 'none'}`, "INFO");
+    } catch (e) { await window.customAlert("Error: " + e, "Error"); }
+  });
+
+  document.getElementById('mpImportBtn').addEventListener('click', async () => {
+    const packagePath = await open({
+      multiple: false,
+      filters: [{ name: 'Singularity Profile', extensions: ['nmsprofile'] }]
+    });
+    if (!packagePath) return;
+
+    try {
+      const result = await invoke('import_profile', { packagePath, profileName: null });
+      selectedProfileInModal = result.profile_name;
+      await renderManagerList();
+      await refreshProfileList();
+
+      const lines = [i18n.get('importDoneMsg', {
+        profile: result.profile_name,
+        available: result.available.length + result.extracted.length,
+        queued: result.queued.length
+      })];
+      if (result.hash_mismatches.length > 0) {
+        lines.push(`${i18n.get('importMismatchMsg')}\n${result.hash_mismatches.join('\n')}`);
+      }
+      if (result.missing.length > 0) {
+        lines.push(`${i18n.get('importMissingMsg')}\n${result.missing.join('\n')}`);
+      }
+      await window.customAlert(lines.join('\n\n'), i18n.get('importBtn'));
+
+      // Missing Nexus archives go through the normal download flow
+      for (const file of result.queued) {
+        await startModDownload({
+          modId: file.mod_id,
+          fileId: file.file_id,
+          version: file.version,
+          fileName: file.file_name,
+          displayName: file.display_name,
+          replacingFileId: null,
+          nxmQueryParams: ""
+        });
+      }
+    } catch (e) { await window.customAlert("Error importing: " + e, "Error"); }
+  });
+
   // 5. SELECT / APPLY
   document.getElementById('mpSelectBtn').addEventListener('click', async () => {
     if (!selectedProfileInModal) return;
@@ -4446,11 +4829,11 @@ document.addEventListener('DOMContentLoaded', () => {
     const name = await window.customPrompt(i18n.get('enterProfileName'), i18n.get('addBtn'));
     if (name && name.trim() !== "") {
       try {
-        await invoke('create_empty_profile', { profileName: name });
+        const createdName = await invoke('create_empty_profile', { profileName: name });
 
         await refreshProfileList();
 
-        profileSelect.value = name;
+        profileSelect.value = createdName;
         updateApplyButtonVisibility();
 
       } catch (e) { await window.customAlert("Error creating profile: " + e, "Error"); }
@@ -4468,16 +4851,16 @@ document.addEventListener('DOMContentLoaded', () => {
     );
     if (newName && newName !== current) {
       try {
-        await invoke('rename_profile', { oldName: current, newName: newName });
+        const renamedTo = await invoke('rename_profile', { oldName: current, newName: newName });
 
         // If renamed the active profile, update the state
         if (appState.activeProfile === current) {
-          appState.activeProfile = newName;
-          localStorage.setItem('activeProfile', newName);
+          appState.activeProfile = renamedTo;
+          localStorage.setItem('activeProfile', renamedTo);
         }
 
         await refreshProfileList();
-        profileSelect.value = newName;
+        profileSelect.value = renamedTo;
         updateApplyButtonVisibility();
       } catch (e) { await window.customAlert("Error renaming: " + e, "Error"); }
     }
@@ -4530,10 +4913,64 @@ document.addEventListener('DOMContentLoaded', () => {
     }
     // --- SAFETY CHECK END ---
 
+    // Summarize what the switch changes compared to the mods deployed right now
+    let changesSummary = '';
+    try {
+      const diff = await invoke('diff_profiles', { a: null, b: targetProfile });
+      const counts = [
+        ['diffAdded', diff.added.length],
+        ['diffRemoved', diff.removed.length],
+        ['diffUpdated', diff.version_changed.length],
+        ['diffOptionsChanged', diff.options_changed.length],
+        ['diffReordered', diff.priority_changed.length],
+        ['diffToggled', diff.enabled_changed.length]
+      ].filter(([, count]) => count > 0);
+      changesSummary = counts.length === 0
+        ? i18n.get('diffNoChanges')
+        : counts.map(([key, count]) => i18n.get(key, { count })).join('\n');
+    } catch (e) {
+      console.warn('Could not compare profiles:', e);
+    }
+
+    // Pre-apply check: flag mods that have no local archive or library copy
+    let problemsSummary = '';
+    try {
+      const validation = await invoke('validate_profile', { profileName: targetProfile });
+      if (!validation.ok) {
+        const listFor = (source) => validation.entries
+          .filter(e => e.source === source)
+          .map(e => `• ${e.filename}`)
+          .join('\n');
+        const needsDownload = listFor('nexus');
+        const unrecoverable = listFor('unrecoverable');
+        problemsSummary = [
+          needsDownload && i18n.get('validateNeedsDownload', { list: needsDownload }),
+          unrecoverable && i18n.get('validateUnrecoverable', { list: unrecoverable })
+        ].filter(Boolean).join('\n\n');
+      }
+    } catch (e) {
+      console.warn('Could not validate profile:', e);
+    }
+
+    // Live changes the active profile could not pick up are lost by the switch
+    let unsavedWarning = '';
+    let discardUnsaved = false;
+    try {
+      const sync = await invoke('check_profile_sync');
+      if (sync.dirty) {
+        discardUnsaved = true;
+        unsavedWarning = i18n.get('unsavedProfileChanges', { profileName: sync.active_profile || '' });
+      }
+    } catch (e) {
+      console.warn('Could not check profile sync state:', e);
+    }
+
     const confirmed = await window.customConfirm(
       i18n.get('switchProfileMsg', {
         profileName: targetProfile
-      }),
+      }) + (changesSummary ? `\n\n${changesSummary}` : '')
+        + (problemsSummary ? `\n\n${problemsSummary}` : '')
+        + (unsavedWarning ? `\n\n${unsavedWarning}` : ''),
       i18n.get('switchProfileTitle')
     );
 
@@ -4587,7 +5024,11 @@ document.addEventListener('DOMContentLoaded', () => {
 
     try {
       // 1. Backend swaps files
-      await invoke('apply_profile', { profileName: targetProfile });
+      const applyResult = await invoke('apply_profile', {
+        profileName: targetProfile,
+        discardUnsaved,
+        allowMissing: problemsSummary !== ''
+      });
 
       // 2. Frontend syncs history
       await syncDownloadHistoryWithProfile(targetProfile);
@@ -4610,10 +5051,22 @@ document.addEventListener('DOMContentLoaded', () => {
         refreshBrowseTabBadges();
       }, 100);
 
-      await saveCurrentProfile();
+      // No re-save here: the backend already wrote the profile, and a snapshot of the
+      // live folder would drop the entries it could not deploy
 
       profileProgressModal.classList.add('hidden');
-      await window.customAlert(`Profile "${targetProfile}" applied successfully.`, "Success");
+      if (applyResult.failed.length > 0) {
+        const list = applyResult.failed
+          .map(f => `• ${f.folder ? `${f.filename} (${f.folder})` : f.filename}: ${f.reason}`)
+          .join('\n');
+        applyResult.failed.forEach(f => window.addAppLog(`Profile apply failure: ${f.filename}: ${f.reason}`, "WARN"));
+        await window.customAlert(
+          i18n.get('profileAppliedWithFailures', { profileName: targetProfile, list }),
+          i18n.get('profileAppliedWithFailuresTitle')
+        );
+      } else {
+        await window.customAlert(`Profile "${targetProfile}" applied successfully.`, "Success");
+      }
 
     } catch (e) {
       // LOGGING: Failure
@@ -5018,9 +5471,63 @@ document.addEventListener('DOMContentLoaded', () => {
   const launchBtn = document.getElementById('launchGameBtn');
   const launchText = launchBtn.querySelector('.launch-text');
 
+  // Returns false if the user chose to fix the reported problems instead of launching
+  const runPreLaunchCheck = async () => {
+    if (localStorage.getItem('preLaunchCheck') === 'false') return true;
+
+    let report;
+    try {
+      report = await invoke('validate_launch');
+    } catch (error) {
+      console.warn('Pre-launch check failed to run:', error);
+      return true;
+    }
+    if (report.ok) return true;
+
+    const lines = report.issues.map(issue =>
+      `${issue.severity === 'error' ? '❌' : '⚠️'} ${issue.message}`
+    );
+    return await window.customConfirm(
+      `${i18n.get('preLaunchIssuesMsg')}\n\n${lines.join('\n')}`,
+      i18n.get('preLaunchIssuesTitle'),
+      i18n.get('launchAnywayBtn'),
+      i18n.get('fixFirstBtn')
+    );
+  };
+
+  // Mirrors the backend process monitor so the button can't start a second copy
+  let gameRunning = false;
+  const setGameRunning = (running) => {
+    gameRunning = running;
+    launchBtn.classList.remove('is-launching');
+    launchBtn.classList.toggle('is-running', running);
+    launchText.textContent = i18n.get(running ? 'gameRunningText' : 'playButton');
+  };
+
+  listen('game-process-status', async (event) => {
+    setGameRunning(event.payload.running);
+    const session = event.payload.session;
+    if (event.payload.running) return;
+
+    if (bisectStatus && !bisectStatus.finished) {
+      // Let the user confirm the outcome; a short session is only a hint
+      const crashed = await window.customConfirm(
+        i18n.get(session?.suspected_crash ? 'bisectAskCrashLikely' : 'bisectAskCrash'),
+        i18n.get('bisectTitle'),
+        i18n.get('bisectCrashedBtn'),
+        i18n.get('bisectWorkedBtn')
+      );
+      await recordBisectResult(crashed);
+    }
+  });
+  invoke('get_game_status')
+    .then(status => { if (status.running) setGameRunning(true); })
+    .catch(e => console.warn('Could not read game status:', e));
+
   launchBtn.addEventListener('click', async () => {
     if (!appState.gamePath || !appState.versionType) return;
-    if (launchBtn.classList.contains('is-launching')) return; // Prevent double click
+    if (launchBtn.classList.contains('is-launching') || gameRunning) return; // Prevent double click
+    if (!(await runPreLaunchCheck())) return;
 
     // 1. SET UI STATE: LAUNCHING
     const originalText = launchText.textContent;
@@ -5029,13 +5536,11 @@ document.addEventListener('DOMContentLoaded', () => {
 
     try {
       // 2. CALL RUST
-      await invoke('launch_game', {
-        versionType: appState.versionType,
-        gamePath: appState.gamePath
-      });
+      await invoke('launch_game');
 
-      // 3. RESET UI STATE (After a delay)
+      // 3. RESET UI STATE (After a delay, unless the monitor saw the game start)
       setTimeout(() => {
+        if (gameRunning) return;
         launchBtn.classList.remove('is-launching');
         launchText.textContent = originalText;
       }, 10000);
@@ -5048,7 +5553,108 @@ document.addEventListener('DOMContentLoaded', () => {
     }
   });
 
+  // --- Crash Search (mod bisection) ---
+  const bisectBanner = document.getElementById('bisectBanner');
+  const bisectBannerText = document.getElementById('bisectBannerText');
+  const bisectCrashedBtn = document.getElementById('bisectCrashedBtn');
+  const bisectWorkedBtn = document.getElementById('bisectWorkedBtn');
+  const bisectStopBtn = document.getElementById('bisectStopBtn');
+  let bisectStatus = null;
+
+  const reloadLiveSettings = async () => {
+    if (!appState.currentFilePath) return;
+    const content = await readTextFile(appState.currentFilePath);
+    await loadXmlContent(content, appState.currentFilePath);
+  };
+
+  const renderBisectStatus = (status) => {
+    bisectStatus = status;
+    bisectBanner.classList.toggle('hidden', !status);
+    if (!status) return;
+
+    bisectCrashedBtn.classList.toggle('hidden', status.finished);
+    bisectWorkedBtn.classList.toggle('hidden', status.finished);
+    bisectStopBtn.textContent = i18n.get(status.finished ? 'bisectRestoreBtn' : 'bisectStopBtn');
+    if (!status.finished) {
+      bisectBannerText.textContent = i18n.get('bisectProgress', {
+        testing: status.testing.length,
+        suspects: status.suspects.length,
+        left: status.steps_left
+      });
+    } else if (status.culprit) {
+      bisectBannerText.textContent = i18n.get('bisectCulpritFound', { modName: status.culprit });
+    } else {
+      bisectBannerText.textContent = i18n.get('bisectNoCulprit');
+    }
+  };
+
+  const startBisect = async () => {
+    try {
+      renderBisectStatus(await invoke('start_mod_bisect'));
+      await reloadLiveSettings();
+      await window.customAlert(i18n.get('bisectStartedMsg'), i18n.get('bisectTitle'));
+    } catch (e) {
+      await window.customAlert(`${e}`, i18n.get('bisectTitle'));
+    }
+  };
+
+  const recordBisectResult = async (crashed) => {
+    try {
+      renderBisectStatus(await invoke('record_bisect_result', { crashed }));
+      await reloadLiveSettings();
+    } catch (e) {
+      await window.customAlert(`${e}`, i18n.get('bisectTitle'));
+    }
+  };
+
+  document.getElementById('startBisectBtn').addEventListener('click', async () => {
+    settingsModalOverlay.classList.add('hidden');
+    await startBisect();
+  });
+  bisectCrashedBtn.addEventListener('click', () => recordBisectResult(true));
+  bisectWorkedBtn.addEventListener('click', () => recordBisectResult(false));
+  bisectStopBtn.addEventListener('click', async () => {
+    try {
+      await invoke('finish_mod_bisect');
+      renderBisectStatus(null);
+      await reloadLiveSettings();
+    } catch (e) {
+      await window.customAlert(`${e}`, i18n.get('bisectTitle'));
+    }
+  });
+
+  invoke('get_bisect_status')
+    .then(renderBisectStatus)
+    .catch(e => console.warn('Could not read crash search status:', e));
+
+  let nexusLoginPending = false;
+  // Set when no system secret store was available and the key went to the fallback file
+  let nexusKeyObfuscatedOnly = false;
+
+  const loginProgressText = {
+    connecting: "Connecting to Nexus...",
+    waiting_for_browser: "Waiting for browser...",
+    reconnecting: "Connection lost, reconnecting...",
+    received: "Key received.",
+  };
+
+  listen('nexus-login-progress', (event) => {
+    if (event.payload.state === 'stored_obfuscated') {
+      nexusKeyObfuscatedOnly = true;
+    }
+    const text = loginProgressText[event.payload.state];
+    if (nexusLoginPending && text) {
+      nexusAccountStatus.textContent = text;
+    }
+  });
+
   nexusAuthBtn.addEventListener('click', async () => {
+    // CANCEL a login that is still waiting on the browser
+    if (nexusLoginPending) {
+      await invoke('cancel_login');
+      return;
+    }
+
     const isLoggedIn = nexusAccountStatus.classList.contains('logged-in');
 
     // LOGOUT
@@ -5063,22 +5669,29 @@ document.addEventListener('DOMContentLoaded', () => {
 
     // LOGIN
     try {
-      nexusAccountStatus.textContent = "Waiting for browser...";
-      nexusAuthBtn.disabled = true; // Prevent double clicks
+      nexusLoginPending = true;
+      nexusKeyObfuscatedOnly = false;
+      nexusAccountStatus.textContent = "Connecting to Nexus...";
+      nexusAuthBtn.textContent = "Cancel";
 
-      // Calls Rust -> Opens Browser -> Waits for Socket
+      // Calls Rust -> Opens Browser -> Waits for Socket (or cancel/timeout)
       const newKey = await invoke('login_to_nexus');
+      nexusLoginPending = false;
 
       if (newKey) {
-        await window.customAlert("Successfully connected!", "Success");
+        const storageNote = nexusKeyObfuscatedOnly
+          ? "\n\nNo system secret store was found, so your API key was saved to a local file. That file is only obfuscated, not encrypted with a secret: anyone who can read your user files can recover the key."
+          : "";
+        await window.customAlert(`Successfully connected!${storageNote}`, "Success");
         await validateLoginState(newKey);
       }
     } catch (error) {
-      await window.customAlert(`Login Failed: ${error}`, "Error");
+      nexusLoginPending = false;
+      if (String(error) !== "Login cancelled.") {
+        await window.customAlert(`Login Failed: ${error}`, "Error");
+      }
       // Reset UI on failure
       await validateLoginState();
-    } finally {
-      nexusAuthBtn.disabled = false;
     }
   });
 
@@ -5228,7 +5841,14 @@ document.addEventListener('DOMContentLoaded', () => {
       // 2. Initialize Profile System
       await refreshProfileList();
 
-      const savedProfile = localStorage.getItem('activeProfile') || 'Default';
+      // The backend tracks the profile actually applied; localStorage is the fallback
+      let trackedProfile = null;
+      try {
+        trackedProfile = (await invoke('check_profile_sync')).active_profile;
+      } catch (e) {
+        console.warn("Failed to read tracked profile:", e);
+      }
+      const savedProfile = trackedProfile || localStorage.getItem('activeProfile') || 'Default';
       appState.activeProfile = savedProfile;
 
       // Safety Check: Ensure the saved profile actually exists